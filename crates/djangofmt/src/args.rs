@@ -1,11 +1,13 @@
-use crate::line_width::{IndentWidth, LineLength, SelfClosing};
+use crate::attribute_order::AttributeOrder;
+use crate::line_width::{IndentWidth, LineLength, SelfClosing, StyleAttrLayout};
 use crate::logging::LogLevel;
+use crate::range::FormatRange;
 use clap::builder::Styles;
 use clap::builder::styling::{AnsiColor, Effects};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-use djangofmt_lint::RuleSelector;
+use djangofmt_lint::{Rule, RuleSelector};
 use markup_fmt::Language;
 
 /// All configuration options that can be passed "globally",
@@ -14,6 +16,15 @@ use markup_fmt::Language;
 pub struct GlobalConfigArgs {
     #[clap(flatten)]
     log_level_args: LogLevelArgs,
+    /// Read the settings from this file instead of the nearest `.djangofmt.toml`,
+    /// `djangofmt.toml` or `pyproject.toml`, for every file.
+    #[arg(
+        long,
+        global = true,
+        value_name = "CONFIG_PATH",
+        help_heading = "Global options"
+    )]
+    pub config: Option<PathBuf>,
 }
 
 impl GlobalConfigArgs {
@@ -78,6 +89,16 @@ pub struct FileSelectionArgs {
     pub force_exclude: bool,
     #[clap(long, overrides_with("force_exclude"), hide = true)]
     pub no_force_exclude: bool,
+
+    /// Only process the files changed since this git revision (commit, branch or tag), staged
+    /// or not, including untracked files. Read from the local repository.
+    #[arg(long, value_name = "REV")]
+    pub changed_since: Option<String>,
+
+    /// Print the files that would be processed, then exit without processing them.
+    /// With `--verbose`, also log why each excluded candidate was skipped.
+    #[arg(long, conflicts_with = "watch")]
+    pub show_files: bool,
 }
 
 /// CLI arguments controlling how templates are parsed, shared by `format` and `check`.
@@ -96,14 +117,14 @@ pub struct TemplateArgs {
     pub custom_blocks: Option<Vec<String>>,
 }
 
-#[derive(Clone, Debug, Default, clap::Parser)]
-pub struct FormatCommand {
-    /// List of files or directories to format.
-    #[arg(required_unless_present = "stdin_filename")]
-    pub files: Vec<PathBuf>,
-    /// The name of the file when passing it through stdin.
-    #[arg(long, value_name = "PATH")]
-    pub stdin_filename: Option<PathBuf>,
+/// CLI arguments controlling how templates are formatted, shared by `format` and
+/// `show-settings`.
+#[derive(Clone, Debug, Default, clap::Args)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "each bool is a distinct user-facing CLI flag"
+)]
+pub struct FormattingArgs {
     /// Set the line-length [default: 120]
     #[arg(long)]
     pub line_length: Option<LineLength>,
@@ -115,6 +136,9 @@ pub struct FormatCommand {
     /// Self-closing style for void HTML elements (e.g. <br> vs <br />) [default: never]
     #[arg(long, value_enum)]
     pub html_void_self_closing: Option<SelfClosing>,
+    /// Layout of `style` attribute declarations [default: single-line]
+    #[arg(long, value_enum)]
+    pub style_attr_layout: Option<StyleAttrLayout>,
     /// Preserve unquoted HTML attribute values (e.g. prop=True stays unquoted).
     /// Use `--no-preserve-unquoted-attrs` to disable.
     #[arg(long, overrides_with("no_preserve_unquoted_attrs"))]
@@ -122,11 +146,70 @@ pub struct FormatCommand {
     /// Do not preserve unquoted HTML attribute values.
     #[arg(long, overrides_with("preserve_unquoted_attrs"), hide = true)]
     pub no_preserve_unquoted_attrs: bool,
+    /// Normalize the whitespace inside template tags, variables and comments
+    /// (e.g. {{x|lower}} becomes {{ x|lower }}).
+    /// Use `--no-normalize-template-whitespace` to disable.
+    #[arg(long, overrides_with("no_normalize_template_whitespace"))]
+    pub normalize_template_whitespace: bool,
+    /// Do not normalize the whitespace inside template tags, variables and comments.
+    #[arg(long, overrides_with("normalize_template_whitespace"), hide = true)]
+    pub no_normalize_template_whitespace: bool,
+    /// Wrap template tags exceeding the line-length, one keyword argument per line.
+    /// Only applies to the jinja profile. Use `--no-wrap-template-tags` to disable.
+    #[arg(long, overrides_with("no_wrap_template_tags"))]
+    pub wrap_template_tags: bool,
+    /// Do not wrap template tags exceeding the line-length.
+    #[arg(long, overrides_with("wrap_template_tags"), hide = true)]
+    pub no_wrap_template_tags: bool,
+    /// Reorder the attributes of elements: a preset (code-guide, id-first, alphabetical) or a
+    /// comma-separated list of attribute names and globs (e.g. id,class,data-*)
+    #[arg(long, value_name = "ORDER")]
+    pub attribute_order: Option<AttributeOrder>,
+}
+
+#[derive(Clone, Debug, Default, clap::Parser)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "each bool is a distinct user-facing CLI flag"
+)]
+pub struct FormatCommand {
+    /// List of files or directories to format.
+    #[arg(required_unless_present = "stdin_filename")]
+    pub files: Vec<PathBuf>,
+    /// The name of the file when passing it through stdin.
+    #[arg(long, value_name = "PATH")]
+    pub stdin_filename: Option<PathBuf>,
+    /// Don't write formatted files back. Instead, list the files that would be reformatted
+    /// and exit with status 1 if there are any.
+    #[arg(long)]
+    pub check: bool,
+    /// Don't write formatted files back. Instead, print a unified diff of the changes
+    /// and exit with status 1 if there are any.
+    #[arg(long, conflicts_with = "check")]
+    pub diff: bool,
+    /// Only format the nodes covering this range of the file read from stdin, leaving the rest
+    /// untouched. Given as `<start_line:col>-<end_line:col>`, 1-based with an exclusive end;
+    /// either side may be omitted.
+    #[arg(long, value_name = "RANGE")]
+    pub range: Option<FormatRange>,
+    /// With `--changed-since`, only format the top-level nodes overlapping changed lines.
+    #[arg(long, requires = "changed_since")]
+    pub lines_changed_only: bool,
+    /// Disable the cache: format every file, even those known to be formatted already.
+    #[arg(long)]
+    pub no_cache: bool,
+    /// Keep running, formatting files again as they change.
+    #[arg(long, conflicts_with = "stdin_filename")]
+    pub watch: bool,
+    #[clap(flatten)]
+    pub formatting: FormattingArgs,
     #[clap(flatten)]
     pub file_selection: FileSelectionArgs,
 }
 
-#[derive(Copy, Clone, Debug, clap::ValueEnum, Deserialize, Default, PartialEq, Eq)]
+#[derive(
+    Copy, Clone, Debug, clap::ValueEnum, Deserialize, Serialize, Default, PartialEq, Eq, Hash,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum Profile {
     #[default]
@@ -149,6 +232,22 @@ impl Profile {
             _ => None,
         }
     }
+
+    /// Whether template tags may span several lines.
+    ///
+    /// Django only matches tags within a single line, and renders the others as text.
+    #[must_use]
+    pub const fn supports_multiline_tags(self) -> bool {
+        matches!(self, Self::Jinja)
+    }
+
+    /// Whether `-` and `+` markers next to the delimiters of template syntax control whitespace.
+    ///
+    /// Django has no such markers: `{{-1}}` prints minus one.
+    #[must_use]
+    pub const fn supports_whitespace_control(self) -> bool {
+        matches!(self, Self::Jinja)
+    }
 }
 
 impl From<Profile> for Language {
@@ -164,6 +263,14 @@ impl From<Profile> for Language {
 pub enum Commands {
     /// Check files for lint errors
     Check(CheckCommand),
+    /// Explain a lint rule
+    Rule(RuleCommand),
+    /// Run the language server over stdio
+    Server,
+    /// Remove the cache directory
+    Clean,
+    /// Show the settings a file resolves to, and where each one comes from
+    ShowSettings(ShowSettingsCommand),
     /// Generate shell completions
     #[clap(hide = true)]
     Completions {
@@ -182,6 +289,25 @@ pub enum OutputFormat {
     Full,
     /// One line per diagnostic: `path:line:col: rule [*] message`.
     Concise,
+    /// A JSON array of diagnostics and file errors, written to stdout.
+    Json,
+    /// One JSON object per diagnostic or file error, one per line, written to stdout as each
+    /// file is checked.
+    JsonLines,
+    /// A SARIF 2.1.0 log, e.g. for GitHub code scanning, written to stdout.
+    Sarif,
+    /// GitHub Actions workflow commands, shown as annotations, written to stdout.
+    Github,
+    /// A GitLab Code Quality report, written to stdout.
+    Gitlab,
+}
+
+impl OutputFormat {
+    /// Whether diagnostics and file errors are written to stdout for tools rather than logged.
+    #[must_use]
+    pub const fn is_machine_readable(self) -> bool {
+        !matches!(self, Self::Full | Self::Concise)
+    }
 }
 
 /// CLI arguments for selecting which lint rules run.
@@ -201,6 +327,66 @@ pub struct RuleSelectionArgs {
     pub no_preview: bool,
 }
 
+/// How `rule` prints rule documentation.
+#[derive(Copy, Clone, Debug, clap::ValueEnum, Default, PartialEq, Eq)]
+pub enum RuleOutputFormat {
+    /// The rule documentation, as Markdown.
+    #[default]
+    Text,
+    /// A JSON object per rule.
+    Json,
+}
+
+/// How `show-settings` prints the settings.
+#[derive(Copy, Clone, Debug, clap::ValueEnum, Default, PartialEq, Eq)]
+pub enum SettingsOutputFormat {
+    /// One `name = value  # source` line per setting.
+    #[default]
+    Text,
+    /// A JSON object of the settings, each with its value and source.
+    Json,
+}
+
+#[derive(Clone, Debug, Default, clap::Parser)]
+pub struct ShowSettingsCommand {
+    /// File to show the settings of.
+    #[arg(value_name = "PATH")]
+    pub path: PathBuf,
+    /// Output format [default: text]
+    #[arg(long, value_enum)]
+    pub output_format: Option<SettingsOutputFormat>,
+    #[clap(flatten)]
+    pub formatting: FormattingArgs,
+    #[clap(flatten)]
+    pub rule_selection: RuleSelectionArgs,
+}
+
+#[derive(Clone, Debug, Default, clap::Parser)]
+pub struct RuleCommand {
+    /// Rule to explain (e.g. `missing-img-alt`).
+    #[arg(
+        value_parser = parse_rule,
+        required_unless_present = "all",
+        conflicts_with = "all",
+        value_name = "RULE"
+    )]
+    pub rule: Option<Rule>,
+    /// Explain every rule.
+    #[arg(long)]
+    pub all: bool,
+    /// Output format [default: text]
+    #[arg(long, value_enum)]
+    pub output_format: Option<RuleOutputFormat>,
+}
+
+/// Parse a rule name, surfacing unknown names as a clap error.
+fn parse_rule(value: &str) -> Result<Rule, String> {
+    value
+        .trim()
+        .parse::<Rule>()
+        .map_err(|_| format!("unknown rule `{value}`"))
+}
+
 /// Parse a single rule selector, surfacing the grammar error as a clap error.
 fn parse_rule_selector(value: &str) -> Result<RuleSelector, String> {
     value
@@ -240,6 +426,30 @@ pub struct CheckCommand {
     pub show_fixes: bool,
     #[arg(long, overrides_with("show_fixes"), hide = true)]
     pub no_show_fixes: bool,
+    /// Insert a suppression comment above each line with remaining diagnostics.
+    #[arg(long)]
+    pub add_suppressions: bool,
+    /// Only report diagnostics missing from this baseline file. It must exist, unless
+    /// `--update-baseline` creates it.
+    #[arg(long, value_name = "PATH", conflicts_with = "add_suppressions")]
+    pub baseline: Option<PathBuf>,
+    /// Rewrite the baseline entries of the checked files from their current diagnostics, dropping
+    /// stale ones. Entries of other files are kept, unless they no longer exist or are no longer
+    /// selected.
+    #[arg(long, requires = "baseline")]
+    pub update_baseline: bool,
+    /// Show counts for every rule with remaining diagnostics instead of the diagnostics
+    /// themselves. Printed as JSON with `--output-format json`, one object per rule and line with
+    /// `--output-format json-lines`.
+    #[arg(long)]
+    pub statistics: bool,
+    /// Disable the cache: lint every file, even those whose diagnostics are known already.
+    /// The cache is never used with `--fix` or `--add-suppressions`.
+    #[arg(long)]
+    pub no_cache: bool,
+    /// Keep running, checking files again as they change.
+    #[arg(long, conflicts_with_all = ["add_suppressions", "update_baseline"])]
+    pub watch: bool,
     #[clap(flatten)]
     pub rule_selection: RuleSelectionArgs,
     #[clap(flatten)]
@@ -294,6 +504,10 @@ mod tests {
     }
 
     #[test]
+    #[expect(
+        clippy::too_many_lines,
+        reason = "the snapshot of the help lists every option"
+    )]
     fn test_cli_help() {
         assert_cmd_snapshot!(cli().arg("--help"), @r###"
         success: true
@@ -307,6 +521,14 @@ mod tests {
         Commands:
           check
                   Check files for lint errors
+          rule
+                  Explain a lint rule
+          server
+                  Run the language server over stdio
+          clean
+                  Remove the cache directory
+          show-settings
+                  Show the settings a file resolves to, and where each one comes from
           help
                   Print this message or the help of the given subcommand(s)
 
@@ -318,6 +540,28 @@ mod tests {
               --stdin-filename <PATH>
                   The name of the file when passing it through stdin
 
+              --check
+                  Don't write formatted files back. Instead, list the files that would be reformatted and
+                  exit with status 1 if there are any
+
+              --diff
+                  Don't write formatted files back. Instead, print a unified diff of the changes and exit
+                  with status 1 if there are any
+
+              --range <RANGE>
+                  Only format the nodes covering this range of the file read from stdin, leaving the rest
+                  untouched. Given as `<start_line:col>-<end_line:col>`, 1-based with an exclusive end;
+                  either side may be omitted
+
+              --lines-changed-only
+                  With `--changed-since`, only format the top-level nodes overlapping changed lines
+
+              --no-cache
+                  Disable the cache: format every file, even those known to be formatted already
+
+              --watch
+                  Keep running, formatting files again as they change
+
               --line-length <LINE_LENGTH>
                   Set the line-length [default: 120]
 
@@ -340,10 +584,30 @@ mod tests {
                   - always:    Always use self-closing syntax
                   - unchanged: Keep existing style as-is
 
+              --style-attr-layout <STYLE_ATTR_LAYOUT>
+                  Layout of `style` attribute declarations [default: single-line]
+
+                  Possible values:
+                  - single-line:     All declarations on a single line
+                  - auto:            One declaration per line when they exceed the line length
+                  - preserve-breaks: One declaration per line when the source spans several lines
+
               --preserve-unquoted-attrs
                   Preserve unquoted HTML attribute values (e.g. prop=True stays unquoted). Use
                   `--no-preserve-unquoted-attrs` to disable
 
+              --normalize-template-whitespace
+                  Normalize the whitespace inside template tags, variables and comments (e.g. {{x|lower}}
+                  becomes {{ x|lower }}). Use `--no-normalize-template-whitespace` to disable
+
+              --wrap-template-tags
+                  Wrap template tags exceeding the line-length, one keyword argument per line. Only applies
+                  to the jinja profile. Use `--no-wrap-template-tags` to disable
+
+              --attribute-order <ORDER>
+                  Reorder the attributes of elements: a preset (code-guide, id-first, alphabetical) or a
+                  comma-separated list of attribute names and globs (e.g. id,class,data-*)
+
           -h, --help
                   Print help (see a summary with '-h')
 
@@ -364,6 +628,14 @@ mod tests {
                   Enforce exclusions, even for paths passed to djangofmt directly on the command-line. Use
                   `--no-force-exclude` to disable
 
+              --changed-since <REV>
+                  Only process the files changed since this git revision (commit, branch or tag), staged or
+                  not, including untracked files. Read from the local repository
+
+              --show-files
+                  Print the files that would be processed, then exit without processing them. With
+                  `--verbose`, also log why each excluded candidate was skipped
+
         Log levels:
           -v, --verbose
                   Enable verbose logging
@@ -371,6 +643,11 @@ mod tests {
           -q, --quiet
                   Disable all logging
 
+        Global options:
+              --config <CONFIG_PATH>
+                  Read the settings from this file instead of the nearest `.djangofmt.toml`,
+                  `djangofmt.toml` or `pyproject.toml`, for every file
+
         ----- stderr -----
         "###);
     }
@@ -0,0 +1,303 @@
+//! Reordering of the attributes of elements, following the groups of an [`AttributeOrder`].
+//!
+//! Attributes are only moved among their neighbours: a template tag or block among the
+//! attributes (`<div {% if x %}hidden{% endif %}>`) splits them into runs sorted on their own.
+
+use std::borrow::Cow;
+use std::ops::Range;
+use std::str::FromStr;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use markup_fmt::ast::{Attribute, NativeAttribute, Root};
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+
+use crate::template_whitespace::{TemplateSyntax, apply_edits, walk_template_syntax};
+
+/// The order to put the attributes of an element in.
+///
+/// Either a preset, or a list of attribute names and globs (`data-*`), each one a group.
+/// Attributes within a group, and those matching no group (put last), keep their order.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(untagged)]
+pub enum AttributeOrder {
+    Preset(AttributeOrderPreset),
+    Groups(Vec<String>),
+}
+
+/// The built-in [`AttributeOrder`]s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AttributeOrderPreset {
+    /// The order of <https://codeguide.co/#attribute-order>: `class`, `id`, `name`, `data-*`,
+    /// then `src`, `for`, `type`, `href`, `value`, then `title`, `alt`, `role`, `aria-*`.
+    CodeGuide,
+    /// Identifying attributes first: `id`, `class`, `name`, `data-*`, `aria-*`, then event
+    /// handlers.
+    IdFirst,
+    /// Alphabetical order of the attribute names.
+    Alphabetical,
+}
+
+impl AttributeOrderPreset {
+    const ALL: [Self; 3] = [Self::CodeGuide, Self::IdFirst, Self::Alphabetical];
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::CodeGuide => "code-guide",
+            Self::IdFirst => "id-first",
+            Self::Alphabetical => "alphabetical",
+        }
+    }
+
+    const fn groups(self) -> &'static [&'static str] {
+        match self {
+            Self::CodeGuide => &[
+                "class", "id", "name", "data-*", "src", "for", "type", "href", "value", "title",
+                "alt", "role", "aria-*",
+            ],
+            Self::IdFirst => &["id", "class", "name", "data-*", "aria-*", "on*"],
+            Self::Alphabetical => &[],
+        }
+    }
+}
+
+impl AttributeOrder {
+    /// Build the [`AttributeSorter`] following this order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a group isn't a valid glob, which parsing the order rules out.
+    #[must_use]
+    pub fn sorter(&self) -> AttributeSorter {
+        let (groups, alphabetical) = match self {
+            Self::Preset(preset) => (
+                preset.groups().iter().map(ToString::to_string).collect(),
+                *preset == AttributeOrderPreset::Alphabetical,
+            ),
+            Self::Groups(groups) => (groups.clone(), false),
+        };
+        let mut builder = GlobSetBuilder::new();
+        for group in &groups {
+            builder.add(build_glob(group).expect("attribute order groups are validated on parse"));
+        }
+        AttributeSorter {
+            groups: builder
+                .build()
+                .expect("attribute order groups are validated on parse"),
+            group_count: groups.len(),
+            alphabetical,
+        }
+    }
+
+    fn validate(groups: Vec<String>) -> Result<Self, String> {
+        for group in &groups {
+            build_glob(group)
+                .map_err(|err| format!("invalid attribute order group `{group}`: {err}"))?;
+        }
+        Ok(Self::Groups(groups))
+    }
+}
+
+fn build_glob(group: &str) -> Result<globset::Glob, globset::Error> {
+    GlobBuilder::new(group).case_insensitive(true).build()
+}
+
+impl FromStr for AttributeOrder {
+    type Err = String;
+
+    /// Parse a preset name, or a comma-separated list of groups.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AttributeOrderPreset::ALL
+            .into_iter()
+            .find(|preset| preset.name() == s)
+            .map_or_else(
+                || {
+                    Self::validate(
+                        s.split(',')
+                            .map(str::trim)
+                            .filter(|group| !group.is_empty())
+                            .map(String::from)
+                            .collect(),
+                    )
+                },
+                |preset| Ok(Self::Preset(preset)),
+            )
+    }
+}
+
+impl<'de> Deserialize<'de> for AttributeOrder {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Preset(AttributeOrderPreset),
+            Groups(Vec<String>),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Preset(preset) => Ok(Self::Preset(preset)),
+            Raw::Groups(groups) => Self::validate(groups).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// An [`AttributeOrder`] ready to sort attributes.
+#[derive(Clone, Debug)]
+pub struct AttributeSorter {
+    groups: GlobSet,
+    group_count: usize,
+    alphabetical: bool,
+}
+
+impl AttributeSorter {
+    /// The position of the first group matching `name`, after every group when none does.
+    fn group(&self, name: &str) -> usize {
+        self.groups
+            .matches(name)
+            .into_iter()
+            .min()
+            .unwrap_or(self.group_count)
+    }
+
+    /// Sort `names` in place, keeping the order of the names of a same group.
+    fn sort(&self, names: &mut [(usize, &str)]) {
+        if self.alphabetical {
+            names.sort_by_cached_key(|(_, name)| (self.group(name), name.to_ascii_lowercase()));
+        } else {
+            names.sort_by_cached_key(|(_, name)| self.group(name));
+        }
+    }
+}
+
+/// Reorder the attributes of the elements of `source`, parsed as `root`.
+///
+/// Elements with duplicate attributes and nodes following a `djangofmt:ignore` comment are
+/// left untouched.
+#[must_use]
+pub fn order_attributes<'s>(
+    source: &'s str,
+    root: &Root<'s>,
+    sorter: &AttributeSorter,
+) -> Cow<'s, str> {
+    let mut edits = Vec::new();
+    walk_template_syntax(source, root, &mut |syntax| {
+        if let TemplateSyntax::Attributes(attrs) = syntax {
+            order_element(source, attrs, sorter, &mut edits);
+        }
+    });
+    apply_edits(source, edits)
+}
+
+/// Add the edits reordering `attrs`, the attributes of an element, to `edits`.
+fn order_element<'s>(
+    source: &'s str,
+    attrs: &[Attribute<'_>],
+    sorter: &AttributeSorter,
+    edits: &mut Vec<(Range<usize>, &'s str)>,
+) {
+    let mut names = FxHashSet::default();
+    let has_duplicates = attrs.iter().any(|attr| {
+        matches!(attr, Attribute::Native(native) if !names.insert(native.name.to_ascii_lowercase()))
+    });
+    if has_duplicates {
+        return;
+    }
+
+    let runs = attrs.split(|attr| !matches!(attr, Attribute::Native(_)));
+    for run in runs {
+        let attrs: Vec<&NativeAttribute<'_>> = run
+            .iter()
+            .filter_map(|attr| match attr {
+                Attribute::Native(native) => Some(native),
+                _ => None,
+            })
+            .collect();
+        let mut order: Vec<(usize, &str)> = attrs
+            .iter()
+            .enumerate()
+            .map(|(index, attr)| (index, attr.name))
+            .collect();
+        sorter.sort(&mut order);
+        for (position, (index, _)) in order.into_iter().enumerate() {
+            if position != index {
+                let target = attribute_range(source, attrs[position]);
+                let moved = attribute_range(source, attrs[index]);
+                edits.push((target, &source[moved]));
+            }
+        }
+    }
+}
+
+/// The bytes of `attr`, from its name to the closing quote of its value.
+fn attribute_range(source: &str, attr: &NativeAttribute<'_>) -> Range<usize> {
+    let start = attr.name.as_ptr() as usize - source.as_ptr() as usize;
+    let Some((value, offset)) = attr.value else {
+        return start..start + attr.name.len();
+    };
+    let end = offset + value.len();
+    let quoted = matches!(
+        (source.as_bytes().get(offset.wrapping_sub(1)), source.as_bytes().get(end)),
+        (Some(open @ (b'"' | b'\'')), Some(close)) if open == close
+    );
+    start..end + usize::from(quoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::args::Profile;
+
+    fn order(source: &str, order: &str) -> String {
+        let root = djangofmt_lint::parse(source, Profile::Django.into(), &[]).unwrap();
+        let sorter = order.parse::<AttributeOrder>().unwrap().sorter();
+        order_attributes(source, &root, &sorter).into_owned()
+    }
+
+    #[rstest]
+    #[case(
+        r#"<a href="/" class="link" id=home data-x='1' onclick="go()"></a>"#,
+        "id-first",
+        r#"<a id=home class="link" data-x='1' onclick="go()" href="/"></a>"#
+    )]
+    #[case(
+        r#"<input value="x" type="text" class="field" id="a" required>"#,
+        "code-guide",
+        r#"<input class="field" id="a" type="text" value="x" required>"#
+    )]
+    #[case(
+        r#"<div title="t" Class="c" aria-label="l"></div>"#,
+        "alphabetical",
+        r#"<div aria-label="l" Class="c" title="t"></div>"#
+    )]
+    #[case(
+        r#"<div aria-hidden="true" data-b="2" id="x" data-a="1"></div>"#,
+        "id,data-*",
+        r#"<div id="x" data-b="2" data-a="1" aria-hidden="true"></div>"#
+    )]
+    fn orders_attributes(#[case] source: &str, #[case] preset: &str, #[case] expected: &str) {
+        assert_eq!(order(source, preset), expected);
+    }
+
+    #[test]
+    fn never_moves_attributes_across_template_blocks() {
+        let source = r#"<div class="c" id="a" {% if x %}hidden{% endif %} name="n" id2="b"></div>"#;
+        assert_eq!(
+            order(source, "id,name"),
+            r#"<div id="a" class="c" {% if x %}hidden{% endif %} name="n" id2="b"></div>"#
+        );
+    }
+
+    #[test]
+    fn leaves_elements_with_duplicate_attributes() {
+        let source = r#"<div class="a" id="x" class="b"></div>"#;
+        assert_eq!(order(source, "id-first"), source);
+    }
+
+    #[test]
+    fn rejects_invalid_globs() {
+        assert!("id,data-[".parse::<AttributeOrder>().is_err());
+    }
+}
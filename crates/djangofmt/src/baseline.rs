@@ -0,0 +1,367 @@
+//! Baseline files let `check` adopt rules gradually: the diagnostics recorded in the baseline are
+//! accepted, and only the ones not in it are reported.
+//!
+//! Entries are keyed on a fingerprint of the flagged source and its surroundings rather than on
+//! line numbers, so they keep matching when unrelated lines are added or the file is reformatted.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::Hasher;
+use std::path::{Component, Path, PathBuf};
+
+use djangofmt_lint::LintDiagnostic;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Cap on the characters on each side of the span mixed into the fingerprint.
+const CONTEXT_CHARS: usize = 64;
+
+/// A baselined diagnostic, as stored in the baseline file.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    /// Path of the file, relative to the directory of the baseline file.
+    pub path: String,
+    /// Rule code of the diagnostic.
+    pub code: String,
+    /// Hash of the flagged source and its surroundings, see [`fingerprint`].
+    pub fingerprint: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BaselineFile {
+    entries: Vec<BaselineEntry>,
+}
+
+/// The diagnostics accepted by a baseline file, counted so identical ones each take an entry.
+#[derive(Debug)]
+pub struct Baseline {
+    /// Directory entry paths are relative to.
+    root: PathBuf,
+    entries: BTreeMap<BaselineEntry, usize>,
+}
+
+impl Baseline {
+    /// An empty baseline to be written at `path`.
+    #[must_use]
+    pub fn new(path: &Path) -> Self {
+        let root = std::path::absolute(path)
+            .ok()
+            .and_then(|path| path.parent().map(Path::to_path_buf))
+            .unwrap_or_default();
+        Self {
+            root,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Read the baseline file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let file: BaselineFile = serde_json::from_str(&content)
+            .map_err(|err| Error::Baseline(path.to_path_buf(), err))?;
+        let mut baseline = Self::new(path);
+        for entry in file.entries {
+            *baseline.entries.entry(entry).or_default() += 1;
+        }
+        Ok(baseline)
+    }
+
+    /// Write the baseline to `path`, with entries sorted so the file diffs cleanly.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let file = BaselineFile {
+            entries: self
+                .entries
+                .iter()
+                .flat_map(|(entry, count)| std::iter::repeat_n(entry.clone(), *count))
+                .collect(),
+        };
+        let mut content = serde_json::to_string_pretty(&file)
+            .map_err(|err| Error::Baseline(path.to_path_buf(), err))?;
+        content.push('\n');
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Number of entries in the baseline.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.values().sum()
+    }
+
+    /// Returns true if the baseline has no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Record `diagnostic`, found in `source` read from `path`.
+    pub fn insert(&mut self, path: &Path, source: &str, diagnostic: &LintDiagnostic) {
+        *self
+            .entries
+            .entry(self.entry(path, source, diagnostic))
+            .or_default() += 1;
+    }
+
+    /// Drop the entries of the file at `path`.
+    pub fn forget(&mut self, path: &Path) {
+        let path = self.relative_path(path);
+        self.entries.retain(|entry, _| entry.path != path);
+    }
+
+    /// Replace the entries of the file at `path` by `diagnostics`, found in `source`.
+    pub fn replace(&mut self, path: &Path, source: &str, diagnostics: &[LintDiagnostic]) {
+        self.forget(path);
+        for diagnostic in diagnostics {
+            self.insert(path, source, diagnostic);
+        }
+    }
+
+    /// Drop the entries of the files for which `keep` returns false, given their absolute path.
+    pub fn retain_files(&mut self, mut keep: impl FnMut(&Path) -> bool) {
+        let root = &self.root;
+        self.entries
+            .retain(|entry, _| keep(&root.join(&entry.path)));
+    }
+
+    /// Consume the entry matching `diagnostic`, returning whether there was one.
+    pub fn take(&mut self, path: &Path, source: &str, diagnostic: &LintDiagnostic) -> bool {
+        let entry = self.entry(path, source, diagnostic);
+        let Some(count) = self.entries.get_mut(&entry) else {
+            return false;
+        };
+        *count -= 1;
+        if *count == 0 {
+            self.entries.remove(&entry);
+        }
+        true
+    }
+
+    fn entry(&self, path: &Path, source: &str, diagnostic: &LintDiagnostic) -> BaselineEntry {
+        BaselineEntry {
+            path: self.relative_path(path),
+            code: diagnostic.code.to_string(),
+            fingerprint: fingerprint(source, diagnostic),
+        }
+    }
+
+    /// `path` as written in the entries: relative to the baseline file, with `/` separators.
+    fn relative_path(&self, path: &Path) -> String {
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        portable_path(path.strip_prefix(&self.root).unwrap_or(&path))
+    }
+}
+
+/// `path` with `/` separators, so a baseline is shared across platforms.
+fn portable_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            Component::ParentDir => Some("..".into()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Hash the rule code with the flagged source and the rest of the tag it sits in: back to the
+/// closest `<` or `{` and up to the closest `>` or `}`, capped at [`CONTEXT_CHARS`].
+///
+/// Whitespace and quotes are left out: they are what `format` rewrites, and line shifts only
+/// ever touch whitespace. Stopping at tag delimiters keeps edits to neighbouring tags out.
+#[must_use]
+pub fn fingerprint(source: &str, diagnostic: &LintDiagnostic) -> String {
+    let start = floor_char_boundary(source, diagnostic.span.offset() as usize);
+    let end = floor_char_boundary(source, start + diagnostic.span.len() as usize).max(start);
+
+    let mut before: Vec<char> = up_to_delimiter(source[..start].chars().rev(), ['<', '{']);
+    before.reverse();
+    let flagged = significant(source[start..end].chars());
+    let after = up_to_delimiter(source[end..].chars(), ['>', '}']);
+
+    let mut hasher = StableHasher::default();
+    for part in [
+        diagnostic.code.to_string(),
+        before.into_iter().collect(),
+        flagged.collect(),
+        after.into_iter().collect(),
+    ] {
+        hasher.write(part.as_bytes());
+        // Never valid UTF-8, so parts can't run into each other.
+        hasher.write(&[0xff]);
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+/// A 64-bit FNV-1a hasher, for hashes written to files and compared across runs.
+///
+/// Unlike [`std::collections::hash_map::DefaultHasher`], its output is specified: it doesn't
+/// change with the Rust version or dependency versions. Integers are fed little-endian, and
+/// `usize` as a `u64`, so it doesn't change with the platform either.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn significant(chars: impl Iterator<Item = char>) -> impl Iterator<Item = char> {
+    chars.filter(|c| !c.is_whitespace() && *c != '"' && *c != '\'')
+}
+
+/// The significant `chars` up to and including the first of `delimiters`.
+fn up_to_delimiter(chars: impl Iterator<Item = char>, delimiters: [char; 2]) -> Vec<char> {
+    let mut context = Vec::new();
+    for c in significant(chars).take(CONTEXT_CHARS) {
+        context.push(c);
+        if delimiters.contains(&c) {
+            break;
+        }
+    }
+    context
+}
+
+/// The largest char boundary of `source` not after `offset`.
+fn floor_char_boundary(source: &str, offset: usize) -> usize {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Project;
+
+    fn diagnostic(source: &str, code: &'static str, needle: &str) -> LintDiagnostic {
+        LintDiagnostic {
+            code,
+            message: "message".into(),
+            span: djangofmt_lint::span(
+                source.find(needle).expect("needle in source"),
+                needle.len(),
+            ),
+            help: None,
+            fix: None,
+            fix_title: None,
+        }
+    }
+
+    fn fingerprint_of(source: &str, code: &'static str, needle: &str) -> String {
+        fingerprint(source, &diagnostic(source, code, needle))
+    }
+
+    #[test]
+    fn fingerprint_survives_line_shifts_and_reformatting() {
+        let original = "<div>\n<form method='put'></form>\n</div>\n";
+        let shifted = "<p>Intro</p>\n\n<div>\n    <form   method=\"put\"></form>\n</div>\n";
+        assert_eq!(
+            fingerprint_of(original, "invalid-attr-value", "put"),
+            fingerprint_of(shifted, "invalid-attr-value", "put")
+        );
+    }
+
+    #[test]
+    fn fingerprint_depends_on_code_and_content() {
+        let source = "<form method=\"put\"></form>\n<form method=\"patch\"></form>\n";
+        let put = fingerprint_of(source, "invalid-attr-value", "put");
+        assert_ne!(put, fingerprint_of(source, "invalid-attr-value", "patch"));
+        assert_ne!(put, fingerprint_of(source, "use-https", "put"));
+    }
+
+    #[test]
+    fn stable_hasher_matches_fnv1a() {
+        let hash = |bytes: &[u8]| {
+            let mut hasher = StableHasher::default();
+            hasher.write(bytes);
+            hasher.finish()
+        };
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn forget_drops_the_entries_of_a_file() {
+        let project = Project::new();
+        let (a, b) = (project.join("a.html"), project.join("b.html"));
+        let source = "<img>\n";
+        let diagnostic = diagnostic(source, "missing-img-alt", "<img>");
+
+        let mut baseline = Baseline::new(&project.join(".djangofmt-baseline.json"));
+        baseline.insert(&a, source, &diagnostic);
+        baseline.insert(&b, source, &diagnostic);
+        baseline.forget(&a);
+        assert!(!baseline.take(&a, source, &diagnostic));
+        assert!(baseline.take(&b, source, &diagnostic));
+    }
+
+    #[test]
+    fn take_consumes_each_entry_once() {
+        let project = Project::new();
+        let path = project.join("templates/a.html");
+        let source = "<img>\n<img>\n";
+        let diagnostic = diagnostic(source, "missing-img-alt", "<img>");
+
+        let mut baseline = Baseline::new(&project.join(".djangofmt-baseline.json"));
+        baseline.insert(&path, source, &diagnostic);
+        assert_eq!(baseline.len(), 1);
+        assert!(baseline.take(&path, source, &diagnostic));
+        assert!(!baseline.take(&path, source, &diagnostic));
+        assert!(baseline.is_empty());
+    }
+
+    #[test]
+    fn write_then_load_round_trips() {
+        let project = Project::new();
+        let baseline_path = project.join(".djangofmt-baseline.json");
+        let path = project.join("templates/a.html");
+        let source = "<img>\n";
+        let diagnostic = diagnostic(source, "missing-img-alt", "<img>");
+
+        let mut baseline = Baseline::new(&baseline_path);
+        baseline.insert(&path, source, &diagnostic);
+        baseline.write(&baseline_path).unwrap();
+        assert!(
+            project
+                .read(".djangofmt-baseline.json")
+                .contains("\"path\": \"templates/a.html\"")
+        );
+
+        let mut loaded = Baseline::load(&baseline_path).unwrap();
+        assert!(loaded.take(&path, source, &diagnostic));
+    }
+}
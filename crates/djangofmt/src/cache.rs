@@ -0,0 +1,421 @@
+//! Persistent per-file cache, so templates that didn't change since the last run are neither
+//! formatted nor linted again.
+//!
+//! Each command keeps one cache file under `<cache-dir>/<version>/`. An entry records the
+//! modification time and size of a file, a hash of its content and a hash of the settings it was
+//! processed with: it is only used while the settings match and either the metadata or the
+//! content does.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::UNIX_EPOCH;
+
+use djangofmt_lint::{Applicability, Edit, Fix, IsolationLevel, LintDiagnostic, Rule, span};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::baseline::StableHasher;
+use crate::pyproject::PyprojectSettings;
+
+/// Where the cache is stored when `cache-dir` isn't set, relative to the project root.
+pub const DEFAULT_CACHE_DIR: &str = ".djangofmt_cache";
+
+/// The cache directory of the project whose `pyproject.toml` lives in `project_root`.
+#[must_use]
+pub fn cache_dir(project_root: &Path, pyproject: &PyprojectSettings) -> PathBuf {
+    project_root.join(
+        pyproject
+            .cache_dir
+            .as_deref()
+            .unwrap_or_else(|| Path::new(DEFAULT_CACHE_DIR)),
+    )
+}
+
+/// A hash of `value`, to tell the content or the settings a cache entry was made from.
+///
+/// It is written to the cache file, so it is computed with the [`StableHasher`] rather than a
+/// hasher whose output may change with the Rust version.
+#[must_use]
+pub fn cache_key<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = StableHasher::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The metadata that tells whether a file changed without reading it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct FileKey {
+    modified_secs: u64,
+    modified_nanos: u32,
+    size: u64,
+}
+
+impl FileKey {
+    fn from_path(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(Self {
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+            size: metadata.len(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Entry<T> {
+    key: FileKey,
+    /// [`cache_key`] of the content.
+    content: u64,
+    /// [`cache_key`] of the settings.
+    settings: u64,
+    data: T,
+}
+
+/// The cache of one command, holding a `T` per file.
+pub struct Cache<T> {
+    /// The cache directory, marked as such when the cache is first written.
+    root: PathBuf,
+    /// The cache file, in the directory of this djangofmt version.
+    path: PathBuf,
+    /// The entries read from the cache file, keyed by canonical path.
+    entries: BTreeMap<PathBuf, Entry<T>>,
+    /// The entries added during this run, merged into the cache file by [`Cache::persist`].
+    updates: Mutex<Vec<(PathBuf, Entry<T>)>>,
+}
+
+impl<T: Clone + Serialize + DeserializeOwned> Cache<T> {
+    /// Load the cache of the command `name` from `root`.
+    ///
+    /// Empty when there is none yet, or when it can't be read.
+    #[must_use]
+    pub fn load(root: &Path, name: &str) -> Self {
+        let path = root
+            .join(env!("CARGO_PKG_VERSION"))
+            .join(format!("{name}.json"));
+        let entries = fs::read(&path).map_or_else(
+            |_| BTreeMap::new(),
+            |content| {
+                serde_json::from_slice(&content).unwrap_or_else(|err| {
+                    debug!("Ignoring invalid cache {}: {err}", path.display());
+                    BTreeMap::new()
+                })
+            },
+        );
+        Self {
+            root: root.to_path_buf(),
+            path,
+            entries,
+            updates: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Look up the file at the canonical `path`, processed with settings hashing to `settings`.
+    ///
+    /// `None` when its metadata can't be read, in which case it can't be cached either.
+    #[must_use]
+    pub fn file<'a>(&'a self, path: &'a Path, settings: u64) -> Option<CachedFile<'a, T>> {
+        Some(CachedFile {
+            cache: self,
+            path,
+            key: FileKey::from_path(path)?,
+            settings,
+        })
+    }
+
+    /// Write the cache file back with the entries of this run, dropping those of deleted files.
+    pub fn persist(self) -> io::Result<()> {
+        let updates = self
+            .updates
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut entries = self.entries;
+        let len_before = entries.len();
+        entries.retain(|path, _| path.exists());
+        if updates.is_empty() && entries.len() == len_before {
+            return Ok(());
+        }
+        entries.extend(updates);
+
+        if !self.root.exists() {
+            fs::create_dir_all(&self.root)?;
+            // Keep the cache out of version control and backups.
+            fs::write(self.root.join(".gitignore"), "*\n")?;
+            fs::write(
+                self.root.join("CACHEDIR.TAG"),
+                "Signature: 8a477f597d28d172789f06886806bc55\n",
+            )?;
+        }
+        let Some(directory) = self.path.parent() else {
+            return Ok(());
+        };
+        fs::create_dir_all(directory)?;
+        // Write to a temporary file first so concurrent runs never read a partial cache.
+        let temporary = self
+            .path
+            .with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&temporary, serde_json::to_vec(&entries)?)?;
+        fs::rename(&temporary, &self.path)
+    }
+}
+
+/// A file looked up in a [`Cache`].
+pub struct CachedFile<'a, T> {
+    cache: &'a Cache<T>,
+    path: &'a Path,
+    /// The metadata of the file when it was looked up, before it is read.
+    key: FileKey,
+    settings: u64,
+}
+
+impl<T: Clone> CachedFile<'_, T> {
+    fn entry(&self) -> Option<&Entry<T>> {
+        self.cache
+            .entries
+            .get(self.path)
+            .filter(|entry| entry.settings == self.settings)
+    }
+
+    /// The cached data, when the file's metadata didn't change.
+    #[must_use]
+    pub fn get(&self) -> Option<T> {
+        self.entry()
+            .filter(|entry| entry.key == self.key)
+            .map(|entry| entry.data.clone())
+    }
+
+    /// The cached data, when `source`, the content of the file, didn't change even though its
+    /// metadata did (e.g. it was checked out again). The entry is refreshed to the new metadata.
+    #[must_use]
+    pub fn get_by_content(&self, source: &str) -> Option<T> {
+        let content = cache_key(source);
+        let data = self
+            .entry()
+            .filter(|entry| entry.content == content)
+            .map(|entry| entry.data.clone())?;
+        self.insert(content, data.clone());
+        Some(data)
+    }
+
+    /// Record `data` as the result of processing `source`, the content of the file.
+    pub fn set(&self, source: &str, data: T) {
+        self.insert(cache_key(source), data);
+    }
+
+    fn insert(&self, content: u64, data: T) {
+        let entry = Entry {
+            key: self.key,
+            content,
+            settings: self.settings,
+            data,
+        };
+        self.cache
+            .updates
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((self.path.to_path_buf(), entry));
+    }
+}
+
+/// A [`LintDiagnostic`] in a form that can be stored in the cache.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedDiagnostic {
+    rule: Rule,
+    message: String,
+    offset: usize,
+    len: usize,
+    help: Option<String>,
+    fix: Option<CachedFix>,
+    fix_title: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedFix {
+    edits: Vec<CachedEdit>,
+    safe: bool,
+    isolation_group: Option<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedEdit {
+    start: usize,
+    end: usize,
+    content: Option<String>,
+}
+
+impl CachedDiagnostic {
+    /// `None` when `diagnostic` isn't reported by a known rule, so it couldn't be replayed.
+    #[must_use]
+    pub fn new(diagnostic: &LintDiagnostic) -> Option<Self> {
+        Some(Self {
+            rule: diagnostic.code.parse().ok()?,
+            message: diagnostic.message.to_string(),
+            offset: diagnostic.span.offset() as usize,
+            len: diagnostic.span.len() as usize,
+            help: diagnostic.help.as_deref().map(str::to_string),
+            fix: diagnostic.fix.as_ref().map(|fix| CachedFix {
+                edits: fix
+                    .edits()
+                    .iter()
+                    .map(|edit| CachedEdit {
+                        start: edit.start(),
+                        end: edit.end(),
+                        content: edit.content().map(str::to_string),
+                    })
+                    .collect(),
+                safe: fix.applicability() == Applicability::Safe,
+                isolation_group: match fix.isolation() {
+                    IsolationLevel::NonOverlapping => None,
+                    IsolationLevel::Group(group) => Some(group),
+                },
+            }),
+            fix_title: diagnostic.fix_title.map(str::to_string),
+        })
+    }
+
+    /// The diagnostic as reported when it was cached.
+    #[must_use]
+    pub fn into_diagnostic(self) -> LintDiagnostic {
+        LintDiagnostic {
+            code: self.rule.into(),
+            message: self.message.into(),
+            span: span(self.offset, self.len),
+            help: self.help.map(Into::into),
+            fix: self.fix.and_then(CachedFix::into_fix),
+            fix_title: self.fix_title.as_deref().map(intern),
+        }
+    }
+}
+
+impl CachedFix {
+    fn into_fix(self) -> Option<Fix> {
+        let mut edits = self.edits.into_iter().map(|edit| {
+            let range = span(edit.start, edit.end - edit.start);
+            edit.content.map_or_else(
+                || Edit::deletion(range),
+                |content| Edit::replacement(content, range),
+            )
+        });
+        let first = edits.next()?;
+        let fix = if self.safe {
+            Fix::safe_edits(first, edits)
+        } else {
+            Fix::unsafe_edits(first, edits)
+        };
+        let isolation = self
+            .isolation_group
+            .map_or(IsolationLevel::NonOverlapping, IsolationLevel::Group);
+        Some(fix.isolate(isolation))
+    }
+}
+
+/// The `'static` string for a cached fix title.
+///
+/// Fix titles are string literals of the rules, so only a handful of distinct titles are ever
+/// leaked, once each.
+fn intern(title: &str) -> &'static str {
+    static TITLES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+    let mut titles = TITLES.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(title) = titles.get(title) {
+        return title;
+    }
+    let title: &'static str = Box::leak(title.to_string().into_boxed_str());
+    titles.insert(title);
+    title
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn cache_key_is_the_stable_hash_of_the_value() {
+        let mut hasher = StableHasher::default();
+        hasher.write(b"<p></p>\n\xff");
+        hasher.write(&7_u64.to_le_bytes());
+        assert_eq!(cache_key(&("<p></p>\n", 7_usize)), hasher.finish());
+    }
+
+    #[test]
+    fn entries_are_used_while_the_file_and_settings_match() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("cache");
+        let file = fs::canonicalize(dir.path()).unwrap().join("a.html");
+        fs::write(&file, "<p></p>\n").unwrap();
+
+        let cache = Cache::<u32>::load(&root, "test");
+        let cached = cache.file(&file, 1).unwrap();
+        assert_eq!(cached.get(), None);
+        cached.set("<p></p>\n", 42);
+        cache.persist().unwrap();
+        assert!(root.join("CACHEDIR.TAG").exists());
+
+        let cache = Cache::<u32>::load(&root, "test");
+        assert_eq!(cache.file(&file, 1).unwrap().get(), Some(42));
+        // Other settings invalidate the entry.
+        assert_eq!(cache.file(&file, 2).unwrap().get(), None);
+
+        // Same content, new metadata: found by content only.
+        fs::write(&file, "<p></p>\n").unwrap();
+        let later = FileKey {
+            modified_secs: u64::MAX,
+            ..FileKey::from_path(&file).unwrap()
+        };
+        let cached = CachedFile {
+            key: later,
+            ..cache.file(&file, 1).unwrap()
+        };
+        assert_eq!(cached.get(), None);
+        assert_eq!(cached.get_by_content("<p></p>\n"), Some(42));
+        assert_eq!(cached.get_by_content("<div></div>\n"), None);
+    }
+
+    #[test]
+    fn deleted_files_are_dropped() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("cache");
+        let file = fs::canonicalize(dir.path()).unwrap().join("a.html");
+        fs::write(&file, "<p></p>\n").unwrap();
+
+        let cache = Cache::<()>::load(&root, "test");
+        cache.file(&file, 1).unwrap().set("<p></p>\n", ());
+        cache.persist().unwrap();
+
+        fs::remove_file(&file).unwrap();
+        Cache::<()>::load(&root, "test").persist().unwrap();
+        assert!(Cache::<()>::load(&root, "test").entries.is_empty());
+    }
+
+    #[test]
+    fn diagnostics_round_trip() {
+        let diagnostic = LintDiagnostic {
+            code: "uppercase-form-method",
+            message: "Uppercase form method.".into(),
+            span: span(6, 13),
+            help: None,
+            fix: Some(
+                Fix::unsafe_edits(
+                    Edit::replacement("post", span(14, 4)),
+                    [Edit::deletion(span(19, 1))],
+                )
+                .isolate(IsolationLevel::Group(3)),
+            ),
+            fix_title: Some("Lowercase `method` value"),
+        };
+        let cached = CachedDiagnostic::new(&diagnostic).unwrap();
+        let json = serde_json::to_string(&cached).unwrap();
+        let replayed = serde_json::from_str::<CachedDiagnostic>(&json)
+            .unwrap()
+            .into_diagnostic();
+        assert_eq!(CachedDiagnostic::new(&replayed), Some(cached));
+        assert_eq!(replayed.code, "uppercase-form-method");
+        assert_eq!(replayed.fix_title, Some("Lowercase `method` value"));
+    }
+}
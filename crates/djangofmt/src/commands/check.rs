@@ -1,26 +1,43 @@
+use djangofmt_lint::suppression::add_suppressions;
 use djangofmt_lint::{
-    Applicability, FileDiagnostics, FixerError, RuleFixSummary, Settings, lint_fix, lint_source,
+    Applicability, FileDiagnostics, FixerError, LintDiagnostic, Rule, RuleCategory, RuleFixSummary,
+    Settings, lint_fix, lint_source,
 };
 use markup_fmt::FormatError;
-use miette::{SourceCode, SpanContents};
 use rayon::iter::Either::{Left, Right};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use rustc_hash::FxHashMap;
-use std::fmt::Write;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
 use std::fs;
+use std::io::{self, Write, stdout};
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, PoisonError};
 use std::time::Instant;
 use tracing::{debug, error, info, warn};
 
 use crate::ExitStatus;
 use crate::args::{CheckCommand, OutputFormat, Profile};
+use crate::baseline::Baseline;
+use crate::cache::{Cache, CachedDiagnostic, CachedFile, cache_dir, cache_key};
 use crate::config::{resolve_bool_arg, resolve_profile, resolve_rule_selection};
-use crate::error::{CommandError, ParseError, Result};
+use crate::emitters::github::write_github;
+use crate::emitters::gitlab::write_gitlab;
+use crate::emitters::json::write_json;
+use crate::emitters::sarif::write_sarif;
+use crate::emitters::{LocatedDiagnostic, Location, located_diagnostics};
+use crate::error::{CommandError, Error, ParseError, Result};
 use crate::fs::relativize_path;
 use crate::per_file_ignores::PerFileIgnores;
-use crate::pyproject::LintSettings;
+use crate::pyproject::{LintSettings, NestedProjects, PyprojectSettings};
 
+use super::ResolvedCommand;
 use super::format::merge_custom_blocks;
+#[cfg(not(target_arch = "wasm32"))]
+use super::watch::watch;
 
 /// Resolved fix-related configuration after merging CLI args with pyproject settings.
 #[derive(Debug, PartialEq, Eq)]
@@ -57,6 +74,59 @@ impl CheckConfig {
     }
 }
 
+/// The lint settings of a `pyproject.toml`, merged with the CLI arguments.
+pub(crate) struct LintProject {
+    pub(crate) settings: Settings,
+    /// Anchored at the directory of the `pyproject.toml`.
+    per_file_ignores: Option<PerFileIgnores>,
+    /// Same custom blocks as `format`, so both commands lint/format the same AST.
+    custom_blocks: Vec<String>,
+    profile: Option<Profile>,
+}
+
+impl LintProject {
+    /// Merge `args` with `pyproject`, found in the directory `root`.
+    pub(crate) fn new(
+        args: &CheckCommand,
+        pyproject: &PyprojectSettings,
+        root: &Path,
+    ) -> Result<Self> {
+        let lint = pyproject.lint.as_ref();
+        let (settings, warnings) =
+            resolve_rule_selection(&args.rule_selection, lint).into_settings();
+        for warning in &warnings {
+            warn!("{warning}");
+        }
+        let per_file_ignores = lint
+            .and_then(|l| l.per_file_ignores.as_ref())
+            .map(|patterns| PerFileIgnores::new(patterns, root))
+            .transpose()?;
+        let custom_blocks = merge_custom_blocks(
+            args.template.custom_blocks.clone(),
+            pyproject.custom_blocks.clone(),
+        )
+        .unwrap_or_default();
+        Ok(Self {
+            settings,
+            per_file_ignores,
+            custom_blocks,
+            profile: pyproject.profile,
+        })
+    }
+
+    /// The settings for `path`: the project's, unless per-file-ignores narrow them.
+    pub(crate) fn settings_for(&self, path: &Path) -> Cow<'_, Settings> {
+        self.per_file_ignores
+            .as_ref()
+            .map_or(Cow::Borrowed(&self.settings), |pfi| {
+                Cow::Owned(Settings {
+                    rules: pfi.rules_for(path, &self.settings.rules),
+                    ..self.settings.clone()
+                })
+            })
+    }
+}
+
 /// Per-file outcome of `check_path`.
 struct CheckResult {
     /// Owning path for display.
@@ -67,23 +137,62 @@ struct CheckResult {
     applied_count: usize,
     /// Per-rule applied summaries, for `--show-fixes`.
     fixes_by_rule: FxHashMap<&'static str, RuleFixSummary>,
+    /// Suppression comments written to this file (0 when `--add-suppressions` is off).
+    suppressions_added: usize,
 }
 
-/// Check the given source code for linting errors.
-pub fn check(args: &CheckCommand) -> Result<ExitStatus> {
-    let resolved = super::resolve_command(&args.files, &args.file_selection)?;
-    let lint = resolved.pyproject.lint.as_ref();
-    let config = CheckConfig::from_args(args, lint);
+impl CheckResult {
+    /// The result of linting `source`, the content of the file at `path`, without fixing it.
+    fn unfixed(path: &Path, source: String, diagnostics: Vec<LintDiagnostic>) -> Self {
+        let file_diagnostics = if diagnostics.is_empty() {
+            FileDiagnostics::empty()
+        } else {
+            FileDiagnostics::new(relativize_path(path), source, diagnostics)
+        };
+        Self {
+            path: path.to_path_buf(),
+            file_diagnostics,
+            applied_count: 0,
+            fixes_by_rule: FxHashMap::default(),
+            suppressions_added: 0,
+        }
+    }
+}
 
-    let (settings, warnings) = resolve_rule_selection(&args.rule_selection, lint).into_settings();
-    for warning in &warnings {
-        warn!("{warning}");
+/// Check the given source code for linting errors.
+pub fn check(args: &CheckCommand, config: Option<&Path>) -> Result<ExitStatus> {
+    if args.file_selection.show_files {
+        return super::show_files(&args.files, &args.file_selection, config);
     }
+    #[cfg(not(target_arch = "wasm32"))]
+    if args.watch {
+        return watch(
+            &args.files,
+            &args.file_selection,
+            config,
+            |resolved, files| check_files(args, resolved, files),
+        );
+    }
+    let resolved = super::resolve_command(&args.files, &args.file_selection, config)?;
+    check_files(args, &resolved, &resolved.files)
+}
 
-    let per_file_ignores = lint
-        .and_then(|l| l.per_file_ignores.as_ref())
-        .map(|patterns| PerFileIgnores::new(patterns, &resolved.project_root))
-        .transpose()?;
+/// Check `files`, selected by `resolved`.
+fn check_files(
+    args: &CheckCommand,
+    resolved: &ResolvedCommand,
+    files: &[PathBuf],
+) -> Result<ExitStatus> {
+    // Fix and output options apply to the whole run, so only the project root's are used.
+    let config = CheckConfig::from_args(args, resolved.pyproject.lint.as_ref());
+    let project = LintProject::new(args, &resolved.pyproject, &resolved.project_root)?;
+    let nested = if resolved.fixed_config {
+        NestedProjects::default()
+    } else {
+        NestedProjects::discover(files, &resolved.project_root, |pyproject, root| {
+            LintProject::new(args, &pyproject, root)
+        })?
+    };
 
     let threshold = if config.unsafe_fixes {
         Applicability::Unsafe
@@ -91,37 +200,54 @@ pub fn check(args: &CheckCommand) -> Result<ExitStatus> {
         Applicability::Safe
     };
 
-    // Same custom blocks as `format`, so both commands lint/format the same AST.
-    let custom_blocks = merge_custom_blocks(
-        args.template.custom_blocks.clone(),
-        resolved.pyproject.custom_blocks.clone(),
-    )
-    .unwrap_or_default();
+    // Cached diagnostics can be replayed, but not fixed or suppressed.
+    let cache = (!args.no_cache && !config.fix && !args.add_suppressions).then(|| {
+        Cache::load(
+            &cache_dir(&resolved.project_root, &resolved.pyproject),
+            "check",
+        )
+    });
+
+    let baseline = args
+        .baseline
+        .as_deref()
+        .map(|path| BaselineFilter::load(path, args.update_baseline))
+        .transpose()?;
+    // `json-lines` records are written as each file is checked, file errors once all are.
+    let stream = config.output_format == OutputFormat::JsonLines && !args.statistics;
+    let stream_error = OnceLock::new();
 
     let start = Instant::now();
-    let (results, parse_errors): (Vec<_>, Vec<_>) = resolved
-        .files
+    let (results, mut parse_errors): (Vec<_>, Vec<_>) = files
         .par_iter()
         .map(|path| {
-            // Reuse the global settings unless per-file-ignores narrow them for this path.
-            let file_settings = per_file_ignores.as_ref().map(|pfi| Settings {
-                rules: pfi.rules_for(path, &settings.rules),
-                ..settings.clone()
-            });
-            let settings = file_settings.as_ref().unwrap_or(&settings);
-            let profile = resolve_profile(
-                args.template.profile,
-                resolved.pyproject.profile,
-                Some(path),
-            );
-            check_path(
-                path,
-                profile,
-                settings,
-                &custom_blocks,
-                config.fix,
-                threshold,
-            )
+            let project = nested.get(path).unwrap_or(&project);
+            let settings = project.settings_for(path);
+            let profile = resolve_profile(args.template.profile, project.profile, Some(path));
+            let mut result = cache.as_ref().map_or_else(
+                || {
+                    check_path(
+                        path,
+                        profile,
+                        &settings,
+                        &project.custom_blocks,
+                        config.fix,
+                        threshold,
+                        args.add_suppressions,
+                    )
+                },
+                |cache| check_path_cached(path, profile, &settings, &project.custom_blocks, cache),
+            )?;
+            if let Some(baseline) = &baseline {
+                baseline.apply(&mut result);
+            }
+            if stream
+                && let Err(err) =
+                    write_json(&mut stdout().lock(), &[&result.file_diagnostics], &[], true)
+            {
+                let _ = stream_error.set(err);
+            }
+            Ok::<_, Box<CommandError>>(result)
         })
         .partition_map(|result| match result {
             Ok(r) => Left(r),
@@ -129,17 +255,56 @@ pub fn check(args: &CheckCommand) -> Result<ExitStatus> {
         });
 
     let duration = start.elapsed();
-    debug!("Checked {} files in {:.2?}", resolved.files.len(), duration);
+    debug!("Checked {} files in {:.2?}", files.len(), duration);
 
-    let nb_parse_errors = super::report_parse_errors(parse_errors, "check", config.output_format);
+    // Statistics have no room for file errors, so they are logged whatever the output format.
+    let errors_format = if args.statistics && config.output_format.is_machine_readable() {
+        OutputFormat::Concise
+    } else {
+        config.output_format
+    };
+    let nb_parse_errors = super::report_parse_errors(&mut parse_errors, "check", errors_format);
+    if let Some(cache) = cache
+        && let Err(err) = cache.persist()
+    {
+        warn!("Failed to write the cache: {err}");
+    }
+
+    if let Some(err) = stream_error.into_inner() {
+        return Err(err.into());
+    }
+    if let Some(baseline) = baseline {
+        baseline.finish(resolved, files)?;
+    }
 
+    report_results(
+        args,
+        &config,
+        &results,
+        &parse_errors,
+        nb_parse_errors,
+        threshold,
+    )
+}
+
+/// Report the diagnostics and totals of `results`, returning the exit status of the run.
+fn report_results(
+    args: &CheckCommand,
+    config: &CheckConfig,
+    results: &[CheckResult],
+    parse_errors: &[CommandError],
+    nb_parse_errors: usize,
+    threshold: Applicability,
+) -> Result<ExitStatus> {
     let mut total_diagnostics = 0usize;
     let mut total_applied = 0usize;
+    let mut total_suppressions = 0usize;
     let mut total_safe_fixable = 0usize;
     let mut total_unsafe_fixable = 0usize;
-    for result in &results {
+    for result in results {
         total_diagnostics += result.file_diagnostics.len();
         total_applied += result.applied_count;
+        total_suppressions += result.suppressions_added;
         for diag in &result.file_diagnostics.diagnostics {
             let Some(fix) = diag.fix.as_ref() else {
                 continue;
@@ -155,9 +320,13 @@ pub fn check(args: &CheckCommand) -> Result<ExitStatus> {
         total_unsafe_fixable = 0;
     }
 
-    match config.output_format {
-        OutputFormat::Full => print_full(&results),
-        OutputFormat::Concise => print_concise(&results, threshold),
+    write_report(args, config, results, parse_errors, threshold)?;
+
+    if args.add_suppressions {
+        info!(
+            "Added {total_suppressions} suppression comment{}.",
+            if total_suppressions == 1 { "" } else { "s" }
+        );
     }
 
     print_summary(
@@ -171,7 +340,7 @@ pub fn check(args: &CheckCommand) -> Result<ExitStatus> {
     );
 
     if config.show_fixes && total_applied > 0 {
-        print_show_fixes(&results, total_applied);
+        print_show_fixes(results, total_applied);
     }
 
     // I/O and parse errors take precedence over lint violations in the exit code.
@@ -184,6 +353,135 @@ pub fn check(args: &CheckCommand) -> Result<ExitStatus> {
     Ok(ExitStatus::Success)
 }
 
+/// Write the diagnostics of `results` and `parse_errors` in the output format, or the statistics
+/// by rule instead when requested.
+fn write_report(
+    args: &CheckCommand,
+    config: &CheckConfig,
+    results: &[CheckResult],
+    parse_errors: &[CommandError],
+    threshold: Applicability,
+) -> Result<()> {
+    let files: Vec<_> = results.iter().map(|r| &r.file_diagnostics).collect();
+    if args.statistics {
+        let statistics = rule_statistics(&files, threshold);
+        let mut stdout = stdout().lock();
+        match config.output_format {
+            OutputFormat::Json => {
+                serde_json::to_writer_pretty(&mut stdout, &statistics).map_err(io::Error::from)?;
+                writeln!(stdout)?;
+            }
+            OutputFormat::JsonLines => {
+                for rule in &statistics {
+                    serde_json::to_writer(&mut stdout, rule).map_err(io::Error::from)?;
+                    writeln!(stdout)?;
+                }
+            }
+            _ => stdout.write_all(render_statistics(&statistics).as_bytes())?,
+        }
+    } else {
+        match config.output_format {
+            OutputFormat::Full => print_full(results),
+            OutputFormat::Concise => print_concise(&files, threshold),
+            OutputFormat::Json => {
+                write_json(&mut stdout().lock(), &files, parse_errors, false)?;
+            }
+            OutputFormat::JsonLines => {
+                // The diagnostics were streamed already.
+                write_json(&mut stdout().lock(), &[], parse_errors, true)?;
+            }
+            OutputFormat::Sarif => write_sarif(&mut stdout().lock(), &files, parse_errors)?,
+            OutputFormat::Github => write_github(&mut stdout().lock(), &files, parse_errors)?,
+            OutputFormat::Gitlab => write_gitlab(&mut stdout().lock(), &files, parse_errors)?,
+        }
+    }
+    Ok(())
+}
+
+/// The baseline file of a run, applied to the diagnostics of each file as soon as it is checked.
+struct BaselineFilter<'a> {
+    path: &'a Path,
+    /// Whether the file is (re)written from the diagnostics instead of filtering them.
+    update: bool,
+    baseline: Mutex<Baseline>,
+}
+
+impl<'a> BaselineFilter<'a> {
+    /// Load the baseline file at `path`, which must exist unless `update` is set.
+    ///
+    /// With `update`, it is (re)written from every diagnostic instead, so they are all accepted.
+    /// An update only replaces the entries of the files checked in this run: those of files left
+    /// out or failing to parse are kept, as long as they still exist and are selected.
+    fn load(path: &'a Path, update: bool) -> Result<Self> {
+        let baseline = if path.exists() {
+            Baseline::load(path)?
+        } else if update {
+            Baseline::new(path)
+        } else {
+            return Err(Error::MissingBaseline(path.to_path_buf()));
+        };
+        Ok(Self {
+            path,
+            update,
+            baseline: Mutex::new(baseline),
+        })
+    }
+
+    /// Drop the diagnostics of `result` recorded in the baseline, or record them when updating.
+    fn apply(&self, result: &mut CheckResult) {
+        let file_diagnostics = &mut result.file_diagnostics;
+        let source = file_diagnostics.source_code.inner();
+        if self.update {
+            let diagnostics = std::mem::take(&mut file_diagnostics.diagnostics);
+            self.baseline
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .replace(&result.path, source, &diagnostics);
+        } else {
+            let mut baseline = self.baseline.lock().unwrap_or_else(PoisonError::into_inner);
+            file_diagnostics
+                .diagnostics
+                .retain(|diagnostic| !baseline.take(&result.path, source, diagnostic));
+        }
+    }
+
+    /// Write the updated baseline, or report the entries no diagnostic matched.
+    ///
+    /// An update drops the entries of the files neither checked in this run (`files`) nor
+    /// selected by `resolved` in its project.
+    fn finish(self, resolved: &ResolvedCommand, files: &[PathBuf]) -> Result<()> {
+        let mut baseline = self
+            .baseline
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        if self.update {
+            let selected: FxHashSet<PathBuf> = resolved
+                .discovery
+                .selected_files(&resolved.project_root)?
+                .iter()
+                .chain(files)
+                .filter_map(|file| fs::canonicalize(file).ok())
+                .collect();
+            baseline.retain_files(|file| {
+                fs::canonicalize(file).is_ok_and(|file| selected.contains(&file))
+            });
+            baseline.write(self.path)?;
+            let count = baseline.len();
+            info!(
+                "Wrote {count} diagnostic{} to the baseline {}.",
+                if count == 1 { "" } else { "s" },
+                relativize_path(self.path)
+            );
+        } else if !baseline.is_empty() {
+            debug!(
+                "{} baseline entries no longer match, drop them with --update-baseline",
+                baseline.len()
+            );
+        }
+        Ok(())
+    }
+}
+
 /// Render each diagnostic as its own block, with source snippet and help text.
 fn print_full(results: &[CheckResult]) {
     for result in results {
@@ -201,27 +499,97 @@ fn print_full(results: &[CheckResult]) {
 }
 
 /// Render one `path:line:col: rule [*] message` line per diagnostic.
-fn print_concise(results: &[CheckResult], threshold: Applicability) {
-    for result in results {
-        let source = &result.file_diagnostics.source_code;
-        let path = relativize_path(&result.path);
-        for diag in &result.file_diagnostics.diagnostics {
-            let (line, column) = source
-                .read_span(&diag.span, 0, 0)
-                .map_or((0, 0), |contents| {
-                    (contents.line() + 1, contents.column() + 1)
-                });
-            let fixable = if diag.fix.as_ref().is_some_and(|fix| fix.applies(threshold)) {
-                " [*]"
-            } else {
-                ""
-            };
-            error!(
-                "{path}:{line}:{column}: {}{fixable} {}",
-                diag.code, diag.message
-            );
+fn print_concise(files: &[&FileDiagnostics], threshold: Applicability) {
+    for LocatedDiagnostic {
+        path,
+        location: Location { line, column },
+        diagnostic: diag,
+        ..
+    } in located_diagnostics(files)
+    {
+        let fixable = if diag.fix.as_ref().is_some_and(|fix| fix.applies(threshold)) {
+            " [*]"
+        } else {
+            ""
+        };
+        error!(
+            "{path}:{line}:{column}: {}{fixable} {}",
+            diag.code, diag.message
+        );
+    }
+}
+
+/// Per-rule tally of the remaining diagnostics, for `--statistics`.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct RuleStatistics {
+    code: &'static str,
+    count: usize,
+    /// Diagnostics with a fix applicable at the current threshold.
+    fixable: usize,
+    category: Option<RuleCategory>,
+}
+
+/// Tally the diagnostics of `files` per rule, most frequent first.
+fn rule_statistics(files: &[&FileDiagnostics], threshold: Applicability) -> Vec<RuleStatistics> {
+    let mut by_rule: BTreeMap<&'static str, RuleStatistics> = BTreeMap::new();
+    for diag in files.iter().flat_map(|file| &file.diagnostics) {
+        let statistics = by_rule.entry(diag.code).or_insert_with(|| RuleStatistics {
+            code: diag.code,
+            count: 0,
+            fixable: 0,
+            category: diag.code.parse::<Rule>().ok().map(|rule| rule.category()),
+        });
+        statistics.count += 1;
+        if diag.fix.as_ref().is_some_and(|fix| fix.applies(threshold)) {
+            statistics.fixable += 1;
         }
     }
+    let mut statistics: Vec<_> = by_rule.into_values().collect();
+    // Stable sort: ties stay in rule code order.
+    statistics.sort_by_key(|rule| Reverse(rule.count));
+    statistics
+}
+
+/// Render `statistics` as a table with aligned columns.
+fn render_statistics(statistics: &[RuleStatistics]) -> String {
+    const HEADER: [&str; 4] = ["Count", "Fixable", "Rule", "Category"];
+    if statistics.is_empty() {
+        return String::new();
+    }
+    let rows: Vec<[String; 4]> = statistics
+        .iter()
+        .map(|row| {
+            [
+                row.count.to_string(),
+                row.fixable.to_string(),
+                row.code.to_string(),
+                row.category
+                    .map(|category| category.to_string())
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+    let widths: [usize; 4] = std::array::from_fn(|i| {
+        rows.iter()
+            .map(|row| row[i].len())
+            .chain([HEADER[i].len()])
+            .max()
+            .unwrap_or_default()
+    });
+
+    let mut output = String::new();
+    let header = HEADER.map(str::to_string);
+    for [count, fixable, code, category] in std::iter::once(&header).chain(&rows) {
+        let line = format!(
+            "{count:>w0$}  {fixable:>w1$}  {code:<w2$}  {category}",
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+        );
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+    output
 }
 
 fn print_summary(
@@ -309,10 +677,14 @@ fn check_path(
     custom_blocks: &[String],
     fix: bool,
     threshold: Applicability,
+    add_suppressions: bool,
 ) -> std::result::Result<CheckResult, Box<CommandError>> {
-    let source = fs::read_to_string(path)
+    let mut source = fs::read_to_string(path)
         .map_err(|err| CommandError::Read(Some(path.to_path_buf()), err))?;
 
+    let mut applied_count = 0;
+    let mut fixes_by_rule = FxHashMap::default();
+    let mut remaining_diagnostics = None;
     if fix {
         match lint_fix(
             &source,
@@ -327,23 +699,10 @@ fn check_path(
                     fs::write(path, &result.source)
                         .map_err(|err| CommandError::Write(Some(path.to_path_buf()), err))?;
                 }
-
-                let file_diagnostics = if result.remaining_diagnostics.is_empty() {
-                    FileDiagnostics::empty()
-                } else {
-                    FileDiagnostics::new(
-                        relativize_path(path),
-                        result.source,
-                        result.remaining_diagnostics,
-                    )
-                };
-
-                return Ok(CheckResult {
-                    path: path.to_path_buf(),
-                    file_diagnostics,
-                    applied_count: result.applied_count,
-                    fixes_by_rule: result.applied_by_rule,
-                });
+                source = result.source;
+                applied_count = result.applied_count;
+                fixes_by_rule = result.applied_by_rule;
+                remaining_diagnostics = Some(result.remaining_diagnostics);
             }
             Err(FixerError::InitialParse(err)) => {
                 return Err(Box::new(CommandError::Parse(ParseError::new(
@@ -365,8 +724,9 @@ fn check_path(
         }
     }
 
-    let diagnostics =
-        match lint_source(&source, profile.into(), custom_blocks, settings, Some(path)) {
+    let mut diagnostics = match remaining_diagnostics {
+        Some(diagnostics) => diagnostics,
+        None => match lint_source(&source, profile.into(), custom_blocks, settings, Some(path)) {
             Ok(diagnostics) => diagnostics,
             Err(err) => {
                 return Err(Box::new(CommandError::Parse(ParseError::new(
@@ -375,7 +735,26 @@ fn check_path(
                     &FormatError::Syntax(err),
                 ))));
             }
-        };
+        },
+    };
+
+    let suppressions_added = if add_suppressions
+        && !diagnostics.is_empty()
+        && let Some(suppressed) = suppress_diagnostics(
+            path,
+            &source,
+            &diagnostics,
+            profile,
+            settings,
+            custom_blocks,
+        )? {
+        source = suppressed.source;
+        diagnostics = suppressed.remaining_diagnostics;
+        suppressed.added_count
+    } else {
+        0
+    };
+
     let file_diagnostics = if diagnostics.is_empty() {
         FileDiagnostics::empty()
     } else {
@@ -385,14 +764,123 @@ fn check_path(
     Ok(CheckResult {
         path: path.to_path_buf(),
         file_diagnostics,
-        applied_count: 0,
-        fixes_by_rule: FxHashMap::default(),
+        applied_count,
+        fixes_by_rule,
+        suppressions_added,
     })
 }
 
+/// Check the file at the given [`Path`], replaying its diagnostics from `cache` when it didn't
+/// change since they were recorded with the same settings.
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(path = %path.display())
+)]
+fn check_path_cached(
+    path: &Path,
+    profile: Profile,
+    settings: &Settings,
+    custom_blocks: &[String],
+    cache: &Cache<Vec<CachedDiagnostic>>,
+) -> std::result::Result<CheckResult, Box<CommandError>> {
+    let cached = cache.file(path, cache_key(&(settings, profile, custom_blocks)));
+    let hit = cached.as_ref().and_then(CachedFile::get);
+    // Clean files don't even need to be read.
+    if hit.as_ref().is_some_and(Vec::is_empty) {
+        return Ok(CheckResult::unfixed(path, String::new(), Vec::new()));
+    }
+    let source = fs::read_to_string(path)
+        .map_err(|err| CommandError::Read(Some(path.to_path_buf()), err))?;
+    let hit = hit.or_else(|| cached.as_ref()?.get_by_content(&source));
+    if let Some(hit) = hit {
+        let diagnostics = hit.into_iter().map(CachedDiagnostic::into_diagnostic);
+        return Ok(CheckResult::unfixed(path, source, diagnostics.collect()));
+    }
+
+    let diagnostics =
+        match lint_source(&source, profile.into(), custom_blocks, settings, Some(path)) {
+            Ok(diagnostics) => diagnostics,
+            Err(err) => {
+                return Err(Box::new(CommandError::Parse(ParseError::new(
+                    Some(path.to_path_buf()),
+                    source,
+                    &FormatError::Syntax(err),
+                ))));
+            }
+        };
+    // Diagnostics that can't be stored leave the file uncached, to be linted again next time.
+    if let Some(cached) = cached
+        && let Some(data) = diagnostics
+            .iter()
+            .map(CachedDiagnostic::new)
+            .collect::<Option<Vec<_>>>()
+    {
+        cached.set(&source, data);
+    }
+    Ok(CheckResult::unfixed(path, source, diagnostics))
+}
+
+/// Outcome of `suppress_diagnostics` for a file it rewrote.
+struct Suppressed {
+    /// The source with the suppression comments inserted.
+    source: String,
+    /// Diagnostics left in the new source: those whose comment conflicted with another one.
+    remaining_diagnostics: Vec<LintDiagnostic>,
+    /// Suppression comments inserted or extended.
+    added_count: usize,
+}
+
+/// Write the comments suppressing `diagnostics` to the file at `path`.
+///
+/// Returns `None` when nothing was written.
+fn suppress_diagnostics(
+    path: &Path,
+    source: &str,
+    diagnostics: &[LintDiagnostic],
+    profile: Profile,
+    settings: &Settings,
+    custom_blocks: &[String],
+) -> std::result::Result<Option<Suppressed>, Box<CommandError>> {
+    // The source was just linted, so it parses.
+    let Ok(root) = djangofmt_lint::parse(source, profile.into(), custom_blocks) else {
+        return Ok(None);
+    };
+    let result = add_suppressions(source, &root, diagnostics);
+    if result.applied_count == 0 {
+        return Ok(None);
+    }
+
+    let Ok(remaining) = lint_source(
+        &result.output,
+        profile.into(),
+        custom_blocks,
+        settings,
+        Some(path),
+    ) else {
+        error!(
+            "Adding suppressions introduced a syntax error in {}, leaving file unchanged",
+            path.display()
+        );
+        return Ok(None);
+    };
+
+    fs::write(path, &result.output)
+        .map_err(|err| CommandError::Write(Some(path.to_path_buf()), err))?;
+    Ok(Some(Suppressed {
+        source: result.output,
+        remaining_diagnostics: remaining,
+        added_count: result.applied_count,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{CheckConfig, print_summary};
+    use djangofmt_lint::{
+        Applicability, Edit, FileDiagnostics, Fix, LintDiagnostic, Rule, RuleCategory, span,
+    };
+
+    use super::{CheckConfig, RuleStatistics, print_summary, render_statistics, rule_statistics};
     use crate::args::{CheckCommand, OutputFormat};
     use crate::pyproject::LintSettings;
     use tracing_test::traced_test;
@@ -560,4 +1048,80 @@ mod tests {
         assert!(!logs_contain("fixable with"));
         assert!(!logs_contain("hidden"));
     }
+
+    fn diagnostic(code: &'static str, fix: Option<Fix>) -> LintDiagnostic {
+        LintDiagnostic {
+            code,
+            message: "message".into(),
+            span: span(0, 1),
+            help: None,
+            fix,
+            fix_title: None,
+        }
+    }
+
+    #[test]
+    fn statistics_sorted_by_count_with_fixable_at_threshold() {
+        let unsafe_fix = || Some(Fix::unsafe_edit(Edit::deletion(span(0, 1))));
+        let file = FileDiagnostics::new(
+            "test.html",
+            "<p></p>\n",
+            vec![
+                diagnostic("missing-img-alt", None),
+                diagnostic("invalid-attr-value", unsafe_fix()),
+                diagnostic("invalid-attr-value", None),
+            ],
+        );
+        let other = FileDiagnostics::new(
+            "other.html",
+            "<p></p>\n",
+            vec![diagnostic("invalid-attr-value", unsafe_fix())],
+        );
+
+        let statistics = rule_statistics(&[&file, &other], Applicability::Safe);
+        assert_eq!(
+            statistics,
+            vec![
+                RuleStatistics {
+                    code: "invalid-attr-value",
+                    count: 3,
+                    fixable: 0,
+                    category: Some(RuleCategory::Correctness),
+                },
+                RuleStatistics {
+                    code: "missing-img-alt",
+                    count: 1,
+                    fixable: 0,
+                    category: Some(Rule::MissingImgAlt.category()),
+                },
+            ]
+        );
+        let statistics = rule_statistics(&[&file, &other], Applicability::Unsafe);
+        assert_eq!(statistics[0].fixable, 2);
+    }
+
+    #[test]
+    fn statistics_table_is_aligned() {
+        let statistics = [
+            RuleStatistics {
+                code: "invalid-attr-value",
+                count: 12,
+                fixable: 3,
+                category: Some(RuleCategory::Correctness),
+            },
+            RuleStatistics {
+                code: "unknown",
+                count: 1,
+                fixable: 0,
+                category: None,
+            },
+        ];
+        assert_eq!(
+            render_statistics(&statistics),
+            "Count  Fixable  Rule                Category\n   \
+                12        3  invalid-attr-value  correctness\n    \
+                 1        0  unknown\n"
+        );
+        assert_eq!(render_statistics(&[]), "");
+    }
 }
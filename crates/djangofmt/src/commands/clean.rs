@@ -0,0 +1,25 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use tracing::info;
+
+use crate::ExitStatus;
+use crate::cache::cache_dir;
+use crate::error::Result;
+use crate::fs::relativize_path;
+use crate::pyproject::load_config;
+
+/// Remove the cache directory of the project in the current directory, or of `config`.
+pub fn clean(config: Option<&Path>) -> Result<ExitStatus> {
+    let (pyproject, project_root) = load_config(config)?;
+    let cache_dir = cache_dir(&project_root, &pyproject);
+    match fs::remove_dir_all(&cache_dir) {
+        Ok(()) => info!("Removed cache at: {}", relativize_path(&cache_dir)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            info!("No cache found at: {}", relativize_path(&cache_dir));
+        }
+        Err(err) => return Err(err.into()),
+    }
+    Ok(ExitStatus::Success)
+}
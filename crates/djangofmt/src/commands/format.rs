@@ -2,20 +2,32 @@ use rayon::iter::Either::{Left, Right};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use std::borrow::Cow;
 use std::fs::File;
-use std::io::Write;
+use std::io::{IsTerminal, Write, stdout};
 use std::path::{Path, PathBuf};
 use std::time::Instant;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use crate::ExitStatus;
 use crate::args::{FormatCommand, OutputFormat, Profile};
-use crate::config::{resolve_bool_arg, resolve_profile};
+use crate::attribute_order::{AttributeOrder, AttributeSorter, order_attributes};
+use crate::cache::{Cache, cache_dir, cache_key};
+use crate::config::{FormatSettings, resolve_format_settings, resolve_profile};
+use crate::diff::unified_diff;
 use crate::editorconfig::{self, EditorconfigSettings};
 use crate::error::{CommandError, ParseError, Result};
-use crate::line_width::{IndentWidth, LineLength, SelfClosing};
-use crate::pyproject::PyprojectSettings;
+use crate::fs::relativize_path;
+use crate::git::ChangedFiles;
+use crate::line_width::{IndentWidth, LineLength, SelfClosing, StyleAttrLayout};
+use crate::pyproject::{NestedProjects, PyprojectSettings};
+use crate::range::{format_lines, parse};
+use crate::template_tags::wrap_template_tags;
+use crate::template_whitespace::normalize_template_whitespace;
 use editorconfig_parser::EditorConfig;
 
+use super::ResolvedCommand;
+#[cfg(not(target_arch = "wasm32"))]
+use super::watch::watch;
+
 /// Pre-built configuration for all formatters.
 #[derive(Clone)]
 pub struct FormatterConfig {
@@ -25,17 +37,48 @@ pub struct FormatterConfig {
     pub malva: malva::config::FormatOptions,
     /// Config for JSON formatter
     pub json: dprint_plugin_json::configuration::Configuration,
+    /// Config for JavaScript/TypeScript formatter
+    pub typescript: dprint_plugin_typescript::configuration::Configuration,
+    /// Layout of `style` attribute declarations
+    pub style_attr_layout: StyleAttrLayout,
+    /// Whether to normalize the whitespace inside template tags, variables and comments
+    pub normalize_template_whitespace: bool,
+    /// Whether to wrap template tags exceeding the line length, where the profile allows it
+    pub wrap_template_tags: bool,
+    /// Order to put the attributes of elements in, `None` to keep the authored order
+    pub attribute_order: Option<AttributeSorter>,
+    /// [`cache_key`] of the arguments the configs were built from.
+    key: u64,
 }
 
 impl FormatterConfig {
     #[must_use]
+    #[expect(
+        clippy::too_many_arguments,
+        reason = "one argument per formatting setting"
+    )]
     pub fn new(
         print_width: LineLength,
         indent_width: IndentWidth,
         custom_blocks: Option<Vec<String>>,
         html_void_self_closing: SelfClosing,
+        style_attr_layout: StyleAttrLayout,
         preserve_unquoted_attrs: bool,
+        normalize_template_whitespace: bool,
+        wrap_template_tags: bool,
+        attribute_order: Option<&AttributeOrder>,
     ) -> Self {
+        let key = cache_key(&(
+            print_width,
+            indent_width,
+            &custom_blocks,
+            html_void_self_closing,
+            style_attr_layout,
+            preserve_unquoted_attrs,
+            normalize_template_whitespace,
+            wrap_template_tags,
+            attribute_order,
+        ));
         Self {
             markup: build_markup_options(
                 print_width,
@@ -46,9 +89,21 @@ impl FormatterConfig {
             ),
             malva: build_malva_config(print_width, indent_width),
             json: build_json_config(print_width, indent_width),
+            typescript: build_typescript_config(print_width, indent_width),
+            style_attr_layout,
+            normalize_template_whitespace,
+            wrap_template_tags,
+            attribute_order: attribute_order.map(AttributeOrder::sorter),
+            key,
         }
     }
 
+    /// A hash of this configuration, to tell whether a cached result was formatted with it.
+    #[must_use]
+    pub const fn cache_key(&self) -> u64 {
+        self.key
+    }
+
     /// Build a [`FormatterConfig`] by merging CLI arguments with `pyproject.toml` and `.editorconfig` settings.
     ///
     /// Precedence is `cli` > `pyproject` > `editorconfig` > `default`
@@ -58,37 +113,26 @@ impl FormatterConfig {
         pyproject: &PyprojectSettings,
         editorconfig: &EditorconfigSettings,
     ) -> Self {
-        let line_length = args
-            .line_length
-            .or(pyproject.line_length)
-            .or(editorconfig.line_length)
-            .unwrap_or_default();
-        let indent_width = args
-            .indent_width
-            .or(pyproject.indent_width)
-            .or(editorconfig.indent_width)
-            .unwrap_or_default();
-        let custom_blocks = merge_custom_blocks(
-            args.template.custom_blocks.clone(),
-            pyproject.custom_blocks.clone(),
-        );
-        let html_void_self_closing = args
-            .html_void_self_closing
-            .or(pyproject.html_void_self_closing)
-            .unwrap_or_default();
-        let preserve_unquoted_attrs = resolve_bool_arg(
-            args.preserve_unquoted_attrs,
-            args.no_preserve_unquoted_attrs,
-        )
-        .or(pyproject.preserve_unquoted_attrs)
-        .unwrap_or_default();
+        Self::from_settings(&resolve_format_settings(
+            &args.formatting,
+            pyproject,
+            editorconfig,
+        ))
+    }
 
+    /// Build a [`FormatterConfig`] from resolved settings.
+    #[must_use]
+    pub fn from_settings(settings: &FormatSettings) -> Self {
         Self::new(
-            line_length,
-            indent_width,
-            custom_blocks,
-            html_void_self_closing,
-            preserve_unquoted_attrs,
+            settings.line_length.value,
+            settings.indent_width.value,
+            settings.custom_blocks.clone(),
+            settings.html_void_self_closing.value,
+            settings.style_attr_layout.value,
+            settings.preserve_unquoted_attrs.value,
+            settings.normalize_template_whitespace.value,
+            settings.wrap_template_tags.value,
+            settings.attribute_order.value.as_ref(),
         )
     }
 }
@@ -114,7 +158,7 @@ macro_rules! ignore_directive {
         "djangofmt:ignore"
     };
 }
-const DJANGOFMT_IGNORE_COMMENT_DIRECTIVE: &str = ignore_directive!();
+pub(crate) const DJANGOFMT_IGNORE_COMMENT_DIRECTIVE: &str = ignore_directive!();
 const DJANGOFMT_IGNORE_COMMENT: &str = concat!("<!-- ", ignore_directive!(), " -->");
 const DJANGOFMT_IGNORE_COMMENT_JINJA: &str = concat!("{# ", ignore_directive!(), " #}");
 
@@ -225,90 +269,270 @@ fn build_json_config(
         .build()
 }
 
+/// Build default `dprint-plugin-typescript` options for JavaScript/TypeScript formatting.
+fn build_typescript_config(
+    print_width: LineLength,
+    indent_width: IndentWidth,
+) -> dprint_plugin_typescript::configuration::Configuration {
+    dprint_plugin_typescript::configuration::ConfigurationBuilder::new()
+        .line_width(print_width.value().into())
+        .indent_width(indent_width.value())
+        .ignore_node_comment_text(DJANGOFMT_IGNORE_COMMENT_DIRECTIVE)
+        .ignore_file_comment_text(DJANGOFMT_IGNORE_COMMENT_DIRECTIVE)
+        .build()
+}
+
+/// What to do with a file whose formatted output differs from its content.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FormatMode {
+    /// Write the formatted output back to the file.
+    #[default]
+    Write,
+    /// Leave the file untouched and report it as one that would be reformatted.
+    Check,
+    /// Leave the file untouched and print a unified diff of the changes.
+    Diff,
+}
+
+impl FormatMode {
+    #[must_use]
+    pub const fn from_args(args: &FormatCommand) -> Self {
+        if args.diff {
+            Self::Diff
+        } else if args.check {
+            Self::Check
+        } else {
+            Self::Write
+        }
+    }
+
+    /// Whether files are left untouched in this mode.
+    #[must_use]
+    pub const fn is_dry_run(self) -> bool {
+        matches!(self, Self::Check | Self::Diff)
+    }
+}
+
+/// The settings of a nested `pyproject.toml`, for the files closest to it.
+struct NestedProject {
+    pyproject: PyprojectSettings,
+    /// Built once when `.editorconfig` can't vary per file, like [`FormatContext::config`].
+    config: Option<FormatterConfig>,
+}
+
 /// Per-run inputs used to derive a per-file [`FormatterConfig`] and [`Profile`].
-struct FormatContext<'a> {
+pub(crate) struct FormatContext<'a> {
     args: &'a FormatCommand,
     pyproject: &'a PyprojectSettings,
     editorconfig: Option<&'a EditorConfig>,
     /// Built once when `.editorconfig` can't vary per file (no config, or only `[*]`).
     config: Option<FormatterConfig>,
+    /// The `pyproject.toml` files nearer to some files than the project root's.
+    nested: Option<&'a NestedProjects<NestedProject>>,
+    mode: FormatMode,
+    /// Whether diffs are rendered with ANSI colors, i.e. `stdout` is a terminal.
+    colored: bool,
+    /// With `--lines-changed-only`, the changed lines to restrict formatting to.
+    changed_lines: Option<&'a ChangedFiles>,
+    /// The files known to be formatted already, unless `--no-cache` is set.
+    cache: Option<&'a Cache<()>>,
 }
 
 impl<'a> FormatContext<'a> {
-    fn new(
+    pub(crate) fn new(
         args: &'a FormatCommand,
         pyproject: &'a PyprojectSettings,
         editorconfig: Option<&'a EditorConfig>,
     ) -> Self {
-        let mut context = Self {
+        Self {
             args,
             pyproject,
             editorconfig,
-            config: None,
-        };
-        if !editorconfig::has_per_file_sections(editorconfig) {
-            // Any filename resolves the same settings here, so build the config once.
-            context.config = Some(context.build_config(Path::new("any")));
+            config: shared_config(args, pyproject, editorconfig),
+            nested: None,
+            mode: FormatMode::from_args(args),
+            colored: stdout().is_terminal(),
+            changed_lines: None,
+            cache: None,
         }
-        context
     }
 
-    fn profile_for(&self, path: &Path) -> Profile {
+    /// The nested `pyproject.toml` settings of the files under `project_root`.
+    fn nested_projects(
+        &self,
+        files: &[PathBuf],
+        project_root: &Path,
+    ) -> Result<NestedProjects<NestedProject>> {
+        NestedProjects::discover(files, project_root, |pyproject, _| {
+            Ok(NestedProject {
+                config: shared_config(self.args, &pyproject, self.editorconfig),
+                pyproject,
+            })
+        })
+    }
+
+    /// The settings of the nearest `pyproject.toml` of `path`, with the config shared by its
+    /// files if any.
+    fn project_for(&self, path: &Path) -> (&PyprojectSettings, Option<&FormatterConfig>) {
+        self.nested
+            .and_then(|nested| nested.get(path))
+            .map_or((self.pyproject, self.config.as_ref()), |project| {
+                (&project.pyproject, project.config.as_ref())
+            })
+    }
+
+    pub(crate) fn profile_for(&self, path: &Path) -> Profile {
+        let (pyproject, _) = self.project_for(path);
         resolve_profile(
-            self.args.template.profile,
-            self.pyproject.profile,
+            self.args.formatting.template.profile,
+            pyproject.profile,
             Some(path),
         )
     }
 
     /// The config for `path`: the shared one when set, otherwise built for this file.
-    fn config_for(&self, path: &Path) -> Cow<'_, FormatterConfig> {
-        self.config
-            .as_ref()
-            .map_or_else(|| Cow::Owned(self.build_config(path)), Cow::Borrowed)
+    pub(crate) fn config_for(&self, path: &Path) -> Cow<'_, FormatterConfig> {
+        let (pyproject, config) = self.project_for(path);
+        file_config(self.args, pyproject, self.editorconfig, config, path)
     }
+}
+
+/// The config of every file following `pyproject`, when `.editorconfig` can't vary per file (no
+/// config, or only `[*]`).
+pub(crate) fn shared_config(
+    args: &FormatCommand,
+    pyproject: &PyprojectSettings,
+    editorconfig: Option<&EditorConfig>,
+) -> Option<FormatterConfig> {
+    // Any filename resolves the same settings here, so build the config once.
+    (!editorconfig::has_per_file_sections(editorconfig)).then(|| {
+        let editorconfig = editorconfig::resolve_editorconfig(editorconfig, Path::new("any"));
+        FormatterConfig::from_args(args, pyproject, &editorconfig)
+    })
+}
+
+/// The config of the file at `path`: `shared` when set, otherwise built for this file.
+pub(crate) fn file_config<'c>(
+    args: &FormatCommand,
+    pyproject: &PyprojectSettings,
+    editorconfig: Option<&EditorConfig>,
+    shared: Option<&'c FormatterConfig>,
+    path: &Path,
+) -> Cow<'c, FormatterConfig> {
+    shared.map_or_else(
+        || {
+            let editorconfig = editorconfig::resolve_editorconfig(editorconfig, path);
+            Cow::Owned(FormatterConfig::from_args(args, pyproject, &editorconfig))
+        },
+        Cow::Borrowed,
+    )
+}
 
-    fn build_config(&self, path: &Path) -> FormatterConfig {
-        let editorconfig = editorconfig::resolve_editorconfig(self.editorconfig, path);
-        FormatterConfig::from_args(self.args, self.pyproject, &editorconfig)
+pub fn format(args: &FormatCommand, config: Option<&Path>) -> Result<ExitStatus> {
+    if args.file_selection.show_files {
+        return super::show_files(&args.files, &args.file_selection, config);
     }
+    #[cfg(not(target_arch = "wasm32"))]
+    if args.watch {
+        return watch(
+            &args.files,
+            &args.file_selection,
+            config,
+            |resolved, files| format_files(args, resolved, files),
+        );
+    }
+    let resolved = super::resolve_command(&args.files, &args.file_selection, config)?;
+    format_files(args, &resolved, &resolved.files)
 }
 
-pub fn format(args: &FormatCommand) -> Result<ExitStatus> {
-    let resolved = super::resolve_command(&args.files, &args.file_selection)?;
+/// Format `files`, selected by `resolved`.
+fn format_files(
+    args: &FormatCommand,
+    resolved: &ResolvedCommand,
+    files: &[PathBuf],
+) -> Result<ExitStatus> {
     let editorconfig = editorconfig::load_editorconfig_from_cwd();
-    let context = FormatContext::new(args, &resolved.pyproject, editorconfig.as_ref());
+    let cache = (!args.no_cache).then(|| {
+        Cache::load(
+            &cache_dir(&resolved.project_root, &resolved.pyproject),
+            "format",
+        )
+    });
+    let mut context = FormatContext::new(args, &resolved.pyproject, editorconfig.as_ref());
+    let nested = if resolved.fixed_config {
+        None
+    } else {
+        Some(context.nested_projects(files, &resolved.project_root)?)
+    };
+    context.nested = nested.as_ref();
+    context.cache = cache.as_ref();
+    if args.lines_changed_only {
+        context.changed_lines = resolved.discovery.changed.as_ref();
+    }
 
     // Format files in parallel
     let start = Instant::now();
-    let (results, parse_errors): (Vec<_>, Vec<_>) = resolved
-        .files
+    let (outcomes, mut parse_errors): (Vec<_>, Vec<_>) = files
         .par_iter()
-        .map(|entry| format_path(entry, &context))
+        .map(|entry| format_path(entry, &context).map(|fmt_res| (entry, fmt_res)))
         .partition_map(|result| match result {
-            Ok(fmt_res) => Left(fmt_res),
+            Ok(outcome) => Left(outcome),
             Err(err) => Right(*err),
         });
+    let (paths, results): (Vec<&PathBuf>, Vec<FormatResult>) = outcomes.into_iter().unzip();
 
-    debug!(
-        "Formatted {} files in {:.2?}",
-        resolved.files.len(),
-        start.elapsed()
-    );
+    debug!("Formatted {} files in {:.2?}", files.len(), start.elapsed());
 
-    let nb_parse_errors = super::report_parse_errors(parse_errors, "format", OutputFormat::Full);
+    let nb_parse_errors =
+        super::report_parse_errors(&mut parse_errors, "format", OutputFormat::Full);
+    // Ends the borrow of the cache by the context, so it can be written.
+    let mode = context.mode;
+    if let Some(cache) = cache
+        && let Err(err) = cache.persist()
+    {
+        warn!("Failed to write the cache: {err}");
+    }
+
+    // `files` is sorted and `partition_map` keeps the order, so these lists are too.
+    match mode {
+        FormatMode::Write => {}
+        FormatMode::Check => {
+            for (path, result) in paths.iter().zip(&results) {
+                if *result == FormatResult::Formatted {
+                    info!("Would reformat: {}", relativize_path(path));
+                }
+            }
+        }
+        FormatMode::Diff => {
+            let mut stdout = stdout().lock();
+            for result in &results {
+                if let FormatResult::Diff(diff) = result {
+                    stdout.write_all(diff.as_bytes())?;
+                }
+            }
+        }
+    }
 
     // Report on the formatting changes.
-    let summary = build_summary(results.as_ref());
+    let summary = build_summary(results.as_ref(), mode);
     if !summary.is_empty() {
         info!("{} !", summary);
     }
 
-    if nb_parse_errors == 0 {
-        Ok(ExitStatus::Success)
-    } else {
-        Ok(ExitStatus::Error)
+    if nb_parse_errors > 0 {
+        return Ok(ExitStatus::Error);
+    }
+    if mode.is_dry_run() && results.iter().any(FormatResult::is_changed) {
+        return Ok(ExitStatus::Failure);
     }
+    Ok(ExitStatus::Success)
+}
+
+/// Whether `source` opts out of formatting with a leading ignore comment.
+#[must_use]
+pub fn has_ignore_file_comment(source: &str) -> bool {
+    source.starts_with(DJANGOFMT_IGNORE_COMMENT)
+        || source.starts_with(DJANGOFMT_IGNORE_COMMENT_JINJA)
 }
 
 /// Format the given source code.
@@ -317,12 +541,25 @@ pub fn format_text(
     config: &FormatterConfig,
     profile: Profile,
 ) -> std::result::Result<Option<String>, markup_fmt::FormatError> {
-    if source.starts_with(DJANGOFMT_IGNORE_COMMENT)
-        || source.starts_with(DJANGOFMT_IGNORE_COMMENT_JINJA)
-    {
+    if has_ignore_file_comment(source) {
         return Ok(None);
     }
-    markup_fmt::format_text(
+    let normalized;
+    let source = if config.normalize_template_whitespace {
+        // A source that doesn't parse is left for the formatter to report.
+        normalized = parse(source, config, profile).map_or(Cow::Borrowed(source), |root| {
+            normalize_template_whitespace(source, &root, profile)
+        });
+        &normalized
+    } else {
+        source
+    };
+    let ordered = config.attribute_order.as_ref().and_then(|sorter| {
+        let root = parse(source, config, profile).ok()?;
+        Some(order_attributes(source, &root, sorter))
+    });
+    let source = ordered.as_deref().unwrap_or(source);
+    let formatted = markup_fmt::format_text(
         source,
         markup_fmt::Language::from(profile),
         &config.markup,
@@ -366,19 +603,132 @@ pub fn format_text(
                     if code.contains('{') {
                         Ok(formatted_css)
                     } else {
-                        Ok(formatted_css
-                            .lines()
-                            .map(str::trim)
-                            .collect::<Vec<_>>()
-                            .join(" ")
-                            .into())
+                        Ok(layout_style_attr(
+                            code,
+                            &formatted_css,
+                            hints.print_width,
+                            config.style_attr_layout,
+                        )
+                        .into())
                     }
                 }
+                "js" | "mjs" | "cjs" | "jsx" | "ts" | "mts" | "cts" | "tsx" => {
+                    Ok(format_script(code, &hints, &config.typescript))
+                }
                 _ => Ok(code.into()),
             }
         },
-    )
-    .map(Some)
+    )?;
+
+    if config.wrap_template_tags && profile.supports_multiline_tags() {
+        let layout = &config.markup.layout;
+        if let Ok(root) = parse(&formatted, config, profile) {
+            let wrapped =
+                wrap_template_tags(&formatted, &root, layout.print_width, layout.indent_width);
+            return Ok(Some(wrapped.into_owned()));
+        }
+    }
+    Ok(Some(formatted))
+}
+
+/// Lay out the declarations of a formatted `style` attribute according to `layout`.
+///
+/// Multi-line declarations are aligned under the first one:
+/// ```html
+/// style="top: 60%;
+///        transform: translate(0, -50%)"
+/// ```
+fn layout_style_attr(
+    code: &str,
+    formatted_css: &str,
+    print_width: usize,
+    layout: StyleAttrLayout,
+) -> String {
+    let mut declarations = vec![];
+    let mut declaration = String::new();
+    for line in formatted_css.lines().map(str::trim) {
+        if !declaration.is_empty() {
+            declaration.push(' ');
+        }
+        declaration.push_str(line);
+        if line.ends_with(';') {
+            declarations.push(std::mem::take(&mut declaration));
+        }
+    }
+    if !declaration.is_empty() {
+        declarations.push(declaration);
+    }
+
+    let single_line = declarations.join(" ");
+    let one_per_line = declarations.len() > 1
+        && match layout {
+            StyleAttrLayout::SingleLine => false,
+            StyleAttrLayout::Auto => r#"style="""#.len() + single_line.len() > print_width,
+            StyleAttrLayout::PreserveBreaks => code.trim().contains('\n'),
+        };
+    if one_per_line {
+        let alignment = format!("\n{}", " ".repeat(r#"style=""#.len()));
+        declarations.join(&alignment)
+    } else {
+        single_line
+    }
+}
+
+/// Format the JavaScript/TypeScript of a `<script>` tag or an event handler attribute.
+///
+/// Falls back to the original code whenever it can't be formatted safely.
+fn format_script<'a>(
+    code: &'a str,
+    hints: &markup_fmt::Hints,
+    config: &dprint_plugin_typescript::configuration::Configuration,
+) -> Cow<'a, str> {
+    // Template interpolation often parses as valid JS (`{{ x }}` is two nested blocks),
+    // formatting it would mangle the template, so leave it untouched.
+    if code.contains("{{") || code.contains("{%") || code.contains("{#") {
+        debug!("Skipping script containing template syntax");
+        return code.into();
+    }
+
+    let fake_filename = PathBuf::from(format!("djangofmt_fmt_stdin.{}", hints.ext));
+    let mut ts_config = config.clone();
+    ts_config.line_width = u32::try_from(hints.print_width).unwrap_or(u32::MAX);
+    if hints.attr {
+        // Because markup_fmt uses DoubleQuotes
+        ts_config.quote_style = dprint_plugin_typescript::configuration::QuoteStyle::AlwaysSingle;
+        // onclick="submit()" rather than onclick="submit();"
+        ts_config.semi_colons = dprint_plugin_typescript::configuration::SemiColons::Asi;
+    }
+
+    let options = dprint_plugin_typescript::FormatTextOptions {
+        path: &fake_filename,
+        extension: None,
+        text: code.to_owned(),
+        config: &ts_config,
+        external_formatter: None,
+    };
+    let formatted = match dprint_plugin_typescript::format_text(options) {
+        Ok(Some(formatted)) => formatted,
+        Ok(None) => return code.into(),
+        Err(error) => {
+            debug!(
+                "Failed to format JavaScript, falling back to original code. Error: {:?}",
+                error
+            );
+            return code.into();
+        }
+    };
+
+    if hints.attr {
+        // Attribute values stay on a single line, keep handlers that don't fit one as written.
+        let formatted = formatted.trim();
+        if formatted.contains('\n') {
+            code.into()
+        } else {
+            formatted.to_owned().into()
+        }
+    } else {
+        formatted.into()
+    }
 }
 
 /// Format the file at the given [`Path`].
@@ -389,10 +739,33 @@ fn format_path(
 ) -> std::result::Result<FormatResult, Box<CommandError>> {
     let profile = context.profile_for(path);
     let config = context.config_for(path);
+    // Only whole files are recorded as formatted.
+    let settings = cache_key(&(config.cache_key(), profile));
+    let cached = context
+        .cache
+        .filter(|_| context.changed_lines.is_none())
+        .and_then(|cache| cache.file(path, settings));
+    if cached.as_ref().is_some_and(|cached| cached.get().is_some()) {
+        return Ok(FormatResult::Unchanged);
+    }
     let unformatted = std::fs::read_to_string(path)
         .map_err(|err| CommandError::Read(Some(path.to_path_buf()), err))?;
+    if cached
+        .as_ref()
+        .is_some_and(|cached| cached.get_by_content(&unformatted).is_some())
+    {
+        return Ok(FormatResult::Unchanged);
+    }
 
-    let formatted = match format_text(&unformatted, &config, profile) {
+    // Files that are entirely new have no changed lines to restrict formatting to.
+    let formatted = context
+        .changed_lines
+        .and_then(|changed| changed.lines(path))
+        .map_or_else(
+            || format_text(&unformatted, &config, profile),
+            |lines| format_lines(&unformatted, lines, &config, profile),
+        );
+    let formatted = match formatted {
         Ok(f) => f,
         Err(err) => {
             return Err(Box::new(CommandError::Parse(ParseError::new(
@@ -409,7 +782,19 @@ fn format_path(
 
     // Checked if something changed and write to file if necessary
     if formatted == unformatted {
+        if let Some(cached) = cached {
+            cached.set(&unformatted, ());
+        }
         Ok(FormatResult::Unchanged)
+    } else if context.mode == FormatMode::Check {
+        Ok(FormatResult::Formatted)
+    } else if context.mode == FormatMode::Diff {
+        Ok(FormatResult::Diff(unified_diff(
+            &relativize_path(path),
+            &unformatted,
+            &formatted,
+            context.colored,
+        )))
     } else {
         let mut writer =
             File::create(path).map_err(|err| CommandError::Write(Some(path.to_path_buf()), err))?;
@@ -417,6 +802,11 @@ fn format_path(
         writer
             .write_all(formatted.as_bytes())
             .map_err(|err| CommandError::Write(Some(path.to_path_buf()), err))?;
+        drop(writer);
+        // Look the file up again, for the metadata of the content just written.
+        if let Some(cached) = cached.and_then(|_| context.cache?.file(path, settings)) {
+            cached.set(&formatted, ());
+        }
 
         Ok(FormatResult::Formatted)
     }
@@ -425,9 +815,12 @@ fn format_path(
 /// The result of an individual formatting operation.
 #[derive(Eq, PartialEq, Debug)]
 pub enum FormatResult {
-    /// The file was formatted.
+    /// The file was formatted (or, in [`FormatMode::Check`], would have been).
     Formatted,
 
+    /// The file would have been formatted, as shown by this unified diff ([`FormatMode::Diff`]).
+    Diff(String),
+
     /// The file was unchanged, as the formatted contents matched the existing contents.
     Unchanged,
 
@@ -435,20 +828,32 @@ pub enum FormatResult {
     Skipped,
 }
 
+impl FormatResult {
+    /// Whether the file was (or would have been) reformatted.
+    #[must_use]
+    pub const fn is_changed(&self) -> bool {
+        matches!(self, Self::Formatted | Self::Diff(_))
+    }
+}
+
 /// Write a summary of the formatting results to stdout.
 #[must_use]
-pub fn build_summary(results: &[FormatResult]) -> String {
+pub fn build_summary(results: &[FormatResult], mode: FormatMode) -> String {
     let (mut changed, mut unchanged, mut skipped) = (0usize, 0usize, 0usize);
     for result in results {
         match result {
-            FormatResult::Formatted => changed += 1,
+            FormatResult::Formatted | FormatResult::Diff(_) => changed += 1,
             FormatResult::Unchanged => unchanged += 1,
             FormatResult::Skipped => skipped += 1,
         }
     }
 
+    let changed_label = match mode {
+        FormatMode::Write => "reformatted",
+        FormatMode::Check | FormatMode::Diff => "would be reformatted",
+    };
     let parts: Vec<String> = [
-        (changed, "reformatted"),
+        (changed, changed_label),
         (unchanged, "left unchanged"),
         (skipped, "skipped"),
     ]
@@ -470,6 +875,7 @@ pub fn build_summary(results: &[FormatResult]) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::args::FormattingArgs;
     use std::io;
 
     use rstest::rstest;
@@ -554,9 +960,12 @@ mod tests {
     #[test]
     fn formatter_config_from_args_cli_overrides_pyproject() {
         let args = FormatCommand {
-            line_length: Some(LineLength::try_from(80u16).unwrap()),
-            indent_width: Some(IndentWidth::try_from(2u8).unwrap()),
-            html_void_self_closing: Some(SelfClosing::Always),
+            formatting: FormattingArgs {
+                line_length: Some(LineLength::try_from(80u16).unwrap()),
+                indent_width: Some(IndentWidth::try_from(2u8).unwrap()),
+                html_void_self_closing: Some(SelfClosing::Always),
+                ..Default::default()
+            },
             ..Default::default()
         };
         let pyproject = PyprojectSettings {
@@ -600,7 +1009,10 @@ mod tests {
     #[test]
     fn formatter_config_preserve_unquoted_attrs_from_cli() {
         let args = FormatCommand {
-            preserve_unquoted_attrs: true,
+            formatting: FormattingArgs {
+                preserve_unquoted_attrs: true,
+                ..Default::default()
+            },
             ..Default::default()
         };
         let pyproject = PyprojectSettings::default();
@@ -624,7 +1036,10 @@ mod tests {
     #[test]
     fn formatter_config_no_preserve_unquoted_attrs_cli_overrides_pyproject() {
         let args = FormatCommand {
-            no_preserve_unquoted_attrs: true,
+            formatting: FormattingArgs {
+                no_preserve_unquoted_attrs: true,
+                ..Default::default()
+            },
             ..Default::default()
         };
         let pyproject = PyprojectSettings {
@@ -636,6 +1051,29 @@ mod tests {
         assert!(!config.markup.language.preserve_unquoted_attrs);
     }
 
+    #[rstest]
+    #[case(StyleAttrLayout::SingleLine, "top:0;left:0", 20, "top: 0; left: 0")]
+    #[case(StyleAttrLayout::Auto, "top:0;left:0", 30, "top: 0; left: 0")]
+    #[case(StyleAttrLayout::Auto, "top:0;left:0", 20, "top: 0;\n       left: 0")]
+    #[case(StyleAttrLayout::PreserveBreaks, "top:0;left:0", 20, "top: 0; left: 0")]
+    #[case(
+        StyleAttrLayout::PreserveBreaks,
+        "top:0;\nleft:0",
+        80,
+        "top: 0;\n       left: 0"
+    )]
+    fn style_attr_layout(
+        #[case] layout: StyleAttrLayout,
+        #[case] code: &str,
+        #[case] print_width: usize,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            layout_style_attr(code, "top: 0;\nleft: 0", print_width, layout),
+            expected
+        );
+    }
+
     #[rstest]
     #[case(vec![], "")]
     #[case(vec![FormatResult::Formatted], "1 file reformatted")]
@@ -660,6 +1098,27 @@ mod tests {
         FormatResult::Skipped,
     ], "2 files reformatted, 1 file left unchanged, 3 files skipped")]
     fn test_write_summary(#[case] results: Vec<FormatResult>, #[case] expected: &str) {
-        assert_eq!(build_summary(&results), expected);
+        assert_eq!(build_summary(&results, FormatMode::Write), expected);
+    }
+
+    #[rstest]
+    #[case(vec![FormatResult::Formatted], "1 file would be reformatted")]
+    #[case(vec![FormatResult::Formatted, FormatResult::Formatted, FormatResult::Unchanged], "2 files would be reformatted, 1 file left unchanged")]
+    #[case(vec![FormatResult::Unchanged, FormatResult::Skipped], "1 file left unchanged, 1 file skipped")]
+    fn test_check_summary(#[case] results: Vec<FormatResult>, #[case] expected: &str) {
+        assert_eq!(build_summary(&results, FormatMode::Check), expected);
+    }
+
+    #[test]
+    fn test_diff_summary() {
+        let results = vec![
+            FormatResult::Diff(String::new()),
+            FormatResult::Diff(String::new()),
+            FormatResult::Unchanged,
+        ];
+        assert_eq!(
+            build_summary(&results, FormatMode::Diff),
+            "2 files would be reformatted, 1 file left unchanged"
+        );
     }
 }
@@ -1,42 +1,72 @@
-use std::io::{Read, Write, stdin, stdout};
+use std::io::{IsTerminal, Read, Write, stdin, stdout};
 use std::path::Path;
 
-use tracing::error;
+use tracing::{error, info};
 
 use crate::ExitStatus;
 use crate::args::{FormatCommand, Profile};
-use crate::commands::format::{FormatterConfig, format_text};
+use crate::commands::format::{FormatMode, FormatResult, FormatterConfig, format_text};
 use crate::config::resolve_profile;
+use crate::diff::unified_diff;
 use crate::editorconfig;
 use crate::error::{CommandError, ParseError, Result};
-use crate::pyproject::load_pyproject_from_cwd;
+use crate::fs::{get_cwd, relativize_path};
+use crate::pyproject::{NestedProjects, PyprojectSettings, load_config};
+use crate::range::{FormatRange, format_range};
 use crate::resolver::{ResolvedDiscoveryConfig, is_force_excluded};
 
+/// How stdin is named in output when no `--stdin-filename` is given.
+const STDIN_DISPLAY: &str = "-";
+
 /// Run the formatter over a single file, read from `stdin`.
-pub fn format_stdin(cli: &FormatCommand) -> Result<ExitStatus> {
+pub fn format_stdin(cli: &FormatCommand, config: Option<&Path>) -> Result<ExitStatus> {
     let stdin_filename = cli.stdin_filename.as_deref();
-    let (pyproject, _) = load_pyproject_from_cwd()?;
+    let (pyproject, project_root) = load_config(config)?;
     let discovery_config = ResolvedDiscoveryConfig::new(&cli.file_selection, &pyproject);
 
+    let mode = FormatMode::from_args(cli);
+
     // If force-exclude matches the (virtual) stdin filename, parrot stdin to
     // stdout unchanged so editors don't trip on excluded files.
+    // In check and diff modes an excluded file has nothing to report.
     if let Some(filename) = stdin_filename
         && is_force_excluded(filename, &discovery_config)?
     {
-        std::io::copy(&mut stdin().lock(), &mut stdout().lock())?;
+        if mode == FormatMode::Write {
+            std::io::copy(&mut stdin().lock(), &mut stdout().lock())?;
+        }
         return Ok(ExitStatus::Success);
     }
 
-    let profile = resolve_profile(cli.template.profile, pyproject.profile, stdin_filename);
+    // Like files on disk, the stdin filename follows its nearest configuration file.
+    let nested = match stdin_filename {
+        Some(filename) if config.is_none() => nested_project(filename, &project_root)?,
+        _ => None,
+    };
+    let pyproject = nested.as_ref().unwrap_or(&pyproject);
+
+    let profile = resolve_profile(
+        cli.formatting.template.profile,
+        pyproject.profile,
+        stdin_filename,
+    );
     let editorconfig = editorconfig::load_editorconfig_from_cwd();
     let settings = editorconfig::resolve_editorconfig(
         editorconfig.as_ref(),
         stdin_filename.unwrap_or_else(|| Path::new("")),
     );
-    let config = FormatterConfig::from_args(cli, &pyproject, &settings);
+    let config = FormatterConfig::from_args(cli, pyproject, &settings);
 
-    match format_source_code(stdin_filename, &config, profile) {
-        Ok(()) => Ok(ExitStatus::Success),
+    match format_source_code(stdin_filename, cli.range.as_ref(), &config, profile, mode) {
+        Ok(FormatResult::Formatted) if mode == FormatMode::Check => {
+            info!("Would reformat: {}", display_name(stdin_filename));
+            Ok(ExitStatus::Failure)
+        }
+        Ok(FormatResult::Diff(diff)) => {
+            stdout().lock().write_all(diff.as_bytes())?;
+            Ok(ExitStatus::Failure)
+        }
+        Ok(_) => Ok(ExitStatus::Success),
         Err(err) => {
             error!("{:?}", miette::Report::new(*err));
             Ok(ExitStatus::Error)
@@ -44,18 +74,53 @@ pub fn format_stdin(cli: &FormatCommand) -> Result<ExitStatus> {
     }
 }
 
+/// The settings of the nearest configuration file of `filename` below `project_root`, `None` when
+/// it is the project root's. The file doesn't have to exist, only its directory.
+fn nested_project(filename: &Path, project_root: &Path) -> Result<Option<PyprojectSettings>> {
+    let path = get_cwd().join(filename);
+    let (Some(directory), Some(name)) = (path.parent(), path.file_name()) else {
+        return Ok(None);
+    };
+    let Ok(directory) = directory.canonicalize() else {
+        return Ok(None);
+    };
+    let mut nearest = None;
+    // Only the directory of `filename` is looked up, so at most one project is built.
+    NestedProjects::discover(&[directory.join(name)], project_root, |pyproject, _| {
+        nearest = Some(pyproject);
+        Ok(())
+    })?;
+    Ok(nearest)
+}
+
+/// How the file read from `stdin` is displayed in reports.
+fn display_name(path: Option<&Path>) -> String {
+    path.map_or_else(|| STDIN_DISPLAY.to_string(), relativize_path)
+}
+
+/// Format the source read from `stdin`, writing it to `stdout` unless `mode` is a dry run.
+/// With a `range`, only the nodes covering it are formatted.
 fn format_source_code(
     path: Option<&Path>,
+    range: Option<&FormatRange>,
     config: &FormatterConfig,
     profile: Profile,
-) -> std::result::Result<(), Box<CommandError>> {
+    mode: FormatMode,
+) -> std::result::Result<FormatResult, Box<CommandError>> {
     let mut source = String::new();
     stdin()
         .lock()
         .read_to_string(&mut source)
         .map_err(|err| Box::new(CommandError::Read(path.map(Path::to_path_buf), err)))?;
 
-    let formatted = match format_text(&source, config, profile) {
+    let formatted = range.map_or_else(
+        || format_text(&source, config, profile),
+        |range| {
+            format_range(&source, &range.to_byte_range(&source), config, profile)
+                .map(|formatted| formatted.map(|formatted| formatted.apply(&source)))
+        },
+    );
+    let formatted = match formatted {
         Ok(f) => f,
         Err(err) => {
             return Err(Box::new(CommandError::Parse(ParseError::new(
@@ -66,10 +131,25 @@ fn format_source_code(
         }
     };
 
+    let result = match formatted.as_deref() {
+        None => FormatResult::Skipped,
+        Some(formatted) if formatted == source => FormatResult::Unchanged,
+        Some(formatted) if mode == FormatMode::Diff => FormatResult::Diff(unified_diff(
+            &display_name(path),
+            &source,
+            formatted,
+            stdout().is_terminal(),
+        )),
+        Some(_) => FormatResult::Formatted,
+    };
+    if mode.is_dry_run() {
+        return Ok(result);
+    }
+
     let output = formatted.as_deref().unwrap_or(&source);
     stdout()
         .lock()
         .write_all(output.as_bytes())
         .map_err(|err| Box::new(CommandError::Write(path.map(Path::to_path_buf), err)))?;
-    Ok(())
+    Ok(result)
 }
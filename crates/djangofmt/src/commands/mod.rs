@@ -1,15 +1,24 @@
-use std::path::PathBuf;
+use std::io::{Write, stdout};
+use std::path::{Path, PathBuf};
 
-use tracing::error;
+use tracing::{Level, debug, error};
 
+use crate::ExitStatus;
 use crate::args::{FileSelectionArgs, OutputFormat};
 use crate::error::{CommandError, Result};
-use crate::pyproject::{PyprojectSettings, load_pyproject_from_cwd};
-use crate::resolver::{ResolvedDiscoveryConfig, resolve_files};
+use crate::fs::{get_cwd, relativize_path};
+use crate::git::ChangedFiles;
+use crate::pyproject::{PyprojectSettings, load_config};
+use crate::resolver::{Exclusion, ResolvedDiscoveryConfig, resolve_files_explained};
 
 pub mod check;
+pub mod clean;
 pub mod format;
 pub mod format_stdin;
+pub mod rule;
+pub mod show_settings;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod watch;
 
 /// Shared preamble for all commands: loads pyproject settings and discovers files.
 pub(crate) struct ResolvedCommand {
@@ -17,35 +26,75 @@ pub(crate) struct ResolvedCommand {
     pub files: Vec<PathBuf>,
     /// Directory of the nearest `pyproject.toml` (or the cwd), anchoring path-relative config.
     pub project_root: PathBuf,
+    /// How `files` were selected, including the `--changed-since` files they are limited to.
+    pub discovery: ResolvedDiscoveryConfig,
+    /// Whether `--config` gave the settings of every file, instead of their nearest
+    /// configuration file.
+    pub fixed_config: bool,
+    /// With `--show-files --verbose`, the candidates left out of `files` and why.
+    pub exclusions: Vec<(PathBuf, Exclusion)>,
 }
 
 pub(crate) fn resolve_command(
     files: &[PathBuf],
     file_selection: &FileSelectionArgs,
+    config: Option<&Path>,
 ) -> Result<ResolvedCommand> {
-    let (pyproject, project_root) = load_pyproject_from_cwd()?;
-    let discovery_config = ResolvedDiscoveryConfig::new(file_selection, &pyproject);
-    let resolved_files = resolve_files(files, &discovery_config)?;
+    let (pyproject, project_root) = load_config(config)?;
+    let mut discovery_config = ResolvedDiscoveryConfig::new(file_selection, &pyproject);
+    if let Some(rev) = &file_selection.changed_since {
+        discovery_config.changed = Some(ChangedFiles::since(rev, get_cwd())?);
+    }
+    // Only `--show-files` explains the exclusions, and only when it is shown.
+    let explain = file_selection.show_files && tracing::enabled!(Level::DEBUG);
+    let (resolved_files, exclusions) = resolve_files_explained(files, &discovery_config, explain)?;
     Ok(ResolvedCommand {
         pyproject,
         files: resolved_files,
         project_root,
+        discovery: discovery_config,
+        fixed_config: config.is_some(),
+        exclusions,
     })
 }
 
+/// `--show-files`: print the files selected by `files` and `file_selection`, without processing
+/// them. With `--verbose`, also log why each candidate left out was excluded.
+pub(crate) fn show_files(
+    files: &[PathBuf],
+    file_selection: &FileSelectionArgs,
+    config: Option<&Path>,
+) -> Result<ExitStatus> {
+    let resolved = resolve_command(files, file_selection, config)?;
+    let mut stdout = stdout().lock();
+    for file in &resolved.files {
+        writeln!(stdout, "{}", relativize_path(file))?;
+    }
+    for (path, exclusion) in &resolved.exclusions {
+        debug!("Excluded {}: {exclusion}", relativize_path(path));
+    }
+    Ok(ExitStatus::Success)
+}
+
 /// Sort parse errors by path, log each as a report, and return the count.
 /// `verb` fills the summary line, e.g. "Couldn't format N files!".
+///
+/// Logged errors are drained; machine-readable formats only log the summary and leave the errors
+/// in `parse_errors`, to be written along with the diagnostics.
 pub(crate) fn report_parse_errors(
-    mut parse_errors: Vec<CommandError>,
+    parse_errors: &mut Vec<CommandError>,
     verb: &str,
     output_format: OutputFormat,
 ) -> usize {
     parse_errors.sort_unstable_by(|a, b| a.path().cmp(&b.path()));
     let count = parse_errors.len();
-    for err in parse_errors {
-        match output_format {
-            OutputFormat::Full => error!("{:?}", miette::Report::new(err)),
-            OutputFormat::Concise => error!("{}", err.concise()),
+    if !output_format.is_machine_readable() {
+        for err in parse_errors.drain(..) {
+            if output_format == OutputFormat::Concise {
+                error!("{}", err.concise());
+            } else {
+                error!("{:?}", miette::Report::new(err));
+            }
         }
     }
     if count > 0 {
@@ -0,0 +1,123 @@
+use std::fmt::Write as _;
+use std::io::{Write, stdout};
+
+use djangofmt_lint::{FixAvailability, Rule, RuleSelector};
+use serde::Serialize;
+
+use crate::ExitStatus;
+use crate::args::{RuleCommand, RuleOutputFormat};
+use crate::error::Result;
+
+/// A rule's metadata, as printed by `--output-format json`.
+#[derive(Serialize)]
+struct RuleInfo {
+    name: String,
+    category: String,
+    status: &'static str,
+    since: &'static str,
+    fix_availability: &'static str,
+    message_formats: &'static [&'static str],
+    explanation: Option<&'static str>,
+}
+
+impl RuleInfo {
+    fn new(rule: Rule) -> Self {
+        let group = rule.group();
+        Self {
+            name: rule.to_string(),
+            category: rule.category().to_string(),
+            status: group.status(),
+            since: group.since(),
+            fix_availability: match rule.fix_availability() {
+                FixAvailability::Always => "always",
+                FixAvailability::Sometimes => "sometimes",
+                FixAvailability::None => "none",
+            },
+            message_formats: rule.message_formats(),
+            explanation: rule.explanation(),
+        }
+    }
+}
+
+/// Print the documentation of the selected rule, or of every rule with `--all`.
+pub fn rule(args: &RuleCommand) -> Result<ExitStatus> {
+    let rules: Vec<Rule> = if args.all {
+        RuleSelector::All.all_rules().collect()
+    } else {
+        args.rule.into_iter().collect()
+    };
+
+    let output = match args.output_format.unwrap_or_default() {
+        RuleOutputFormat::Text => rules
+            .into_iter()
+            .map(render_text)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        RuleOutputFormat::Json => {
+            let infos: Vec<RuleInfo> = rules.into_iter().map(RuleInfo::new).collect();
+            let mut json = if args.all {
+                serde_json::to_string_pretty(&infos)
+            } else {
+                serde_json::to_string_pretty(&infos[0])
+            }
+            .map_err(std::io::Error::from)?;
+            json.push('\n');
+            json
+        }
+    };
+
+    stdout().lock().write_all(output.as_bytes())?;
+    Ok(ExitStatus::Success)
+}
+
+fn render_text(rule: Rule) -> String {
+    let group = rule.group();
+    let mut output = String::new();
+    let _ = writeln!(output, "# {rule}\n");
+    let _ = writeln!(output, "Category: {}", rule.category());
+    let _ = writeln!(
+        output,
+        "Status: {} (since {})",
+        group.status(),
+        group.since()
+    );
+    let _ = writeln!(output, "{}", rule.fix_availability());
+
+    let formats = rule.message_formats();
+    if !formats.is_empty() {
+        let _ = writeln!(output, "\nMessage formats:");
+        for format in formats {
+            let _ = writeln!(output, "* {format}");
+        }
+    }
+
+    if let Some(explanation) = rule.explanation() {
+        let _ = write!(output, "\n{explanation}");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_lists_metadata_then_explanation() {
+        let text = render_text(Rule::InvalidAttrValue);
+        assert!(
+            text.starts_with(
+                "# invalid-attr-value\n\nCategory: correctness\nStatus: stable (since "
+            )
+        );
+        assert!(text.contains("\nMessage formats:\n* "));
+        assert!(text.contains("\n## What it does\n"));
+    }
+
+    #[test]
+    fn json_info() {
+        let info = RuleInfo::new(Rule::InvalidAttrValue);
+        assert_eq!(info.name, "invalid-attr-value");
+        assert_eq!(info.category, "correctness");
+        assert_eq!(info.fix_availability, "none");
+    }
+}
@@ -0,0 +1,598 @@
+//! `show-settings`: print the settings a file resolves to, and where each one comes from.
+
+use std::fmt;
+use std::fmt::Write as _;
+use std::io::{Write, stdout};
+use std::path::{Path, PathBuf};
+
+use djangofmt_lint::RuleSelector;
+use serde::{Serialize, Serializer};
+
+use crate::ExitStatus;
+use crate::args::{CheckCommand, Profile, SettingsOutputFormat, ShowSettingsCommand};
+use crate::attribute_order::AttributeOrder;
+use crate::config::{FormatSettings, Layer, Layered, resolve_bool_arg, resolve_format_settings};
+use crate::editorconfig::{
+    EditorconfigSections, load_editorconfig_from_cwd, resolve_editorconfig,
+    resolve_editorconfig_sections,
+};
+use crate::error::Result;
+use crate::fs::{
+    find_nearest_ancestor_file, find_nearest_ancestor_file_of, get_cwd, relativize_path,
+};
+use crate::line_width::{IndentWidth, LineLength, SelfClosing, StyleAttrLayout};
+use crate::pyproject::{
+    CONFIG_FILES, LintSettings, NestedProjects, PyprojectSettings, load_config_chain,
+    load_config_file,
+};
+
+use super::check::LintProject;
+
+/// Where a setting comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+    /// A command-line argument.
+    Cli,
+    /// A configuration file.
+    Config(PathBuf),
+    /// A section of an `.editorconfig` file.
+    Editorconfig(PathBuf, Option<String>),
+    /// The extension of the file, for the profile.
+    FileExtension,
+    /// Several of the above, for settings merging them.
+    Merged(Vec<Self>),
+    /// The default value.
+    Default,
+}
+
+impl Source {
+    /// A single source for all of `sources`, the default when there are none.
+    fn merged(mut sources: Vec<Self>) -> Self {
+        match sources.len() {
+            0 => Self::Default,
+            1 => sources.remove(0),
+            _ => Self::Merged(sources),
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cli => f.write_str("cli"),
+            Self::Config(path) | Self::Editorconfig(path, None) => {
+                f.write_str(&relativize_path(path))
+            }
+            Self::Editorconfig(path, Some(section)) => {
+                write!(f, "{} [{section}]", relativize_path(path))
+            }
+            Self::FileExtension => f.write_str("file extension"),
+            Self::Merged(sources) => {
+                let sources: Vec<String> = sources.iter().map(ToString::to_string).collect();
+                f.write_str(&sources.join(", "))
+            }
+            Self::Default => f.write_str("default"),
+        }
+    }
+}
+
+impl Serialize for Source {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// The resolved value of a setting and its source.
+#[derive(Debug, Serialize)]
+struct Setting<T> {
+    value: T,
+    source: Source,
+}
+
+impl<T> Setting<T> {
+    /// The first of `candidates` that is set, or `default`.
+    fn resolve<const N: usize>(candidates: [(Option<T>, Source); N], default: T) -> Self {
+        candidates
+            .into_iter()
+            .find_map(|(value, source)| {
+                Some(Self {
+                    value: value?,
+                    source,
+                })
+            })
+            .unwrap_or(Self {
+                value: default,
+                source: Source::Default,
+            })
+    }
+
+    fn map<U>(self, f: impl FnOnce(T) -> U) -> Setting<U> {
+        Setting {
+            value: f(self.value),
+            source: self.source,
+        }
+    }
+}
+
+/// The inputs of the [`FormatterConfig`](super::format::FormatterConfig) of a file.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct FormatSection {
+    line_length: Setting<u16>,
+    indent_width: Setting<u8>,
+    custom_blocks: Setting<Vec<String>>,
+    html_void_self_closing: Setting<SelfClosing>,
+    style_attr_layout: Setting<StyleAttrLayout>,
+    preserve_unquoted_attrs: Setting<bool>,
+    normalize_template_whitespace: Setting<bool>,
+    wrap_template_tags: Setting<bool>,
+    attribute_order: Setting<Option<AttributeOrder>>,
+}
+
+/// The lint settings of a file.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct ResolvedLintSettings {
+    select: Setting<Vec<String>>,
+    ignore: Setting<Vec<String>>,
+    preview: Setting<bool>,
+    /// The selected rules that `per-file-ignores` turn off for the file.
+    per_file_ignores: Setting<Vec<String>>,
+    /// The rules that run on the file.
+    rules: Vec<String>,
+}
+
+/// Everything `show-settings` prints.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct ResolvedSettings {
+    path: String,
+    /// The configuration file of the file, which may extend others.
+    config_file: Option<String>,
+    format: FormatSection,
+    profile: Setting<Profile>,
+    lint: ResolvedLintSettings,
+}
+
+/// A configuration file and the files it `extend`s, in order.
+struct ConfigChain(Vec<(PathBuf, PyprojectSettings)>);
+
+impl ConfigChain {
+    /// The files of the chain setting what `is_set` looks for.
+    fn sources(&self, is_set: impl Fn(&PyprojectSettings) -> bool) -> Vec<Source> {
+        self.0
+            .iter()
+            .filter(|(_, settings)| is_set(settings))
+            .map(|(path, _)| Source::Config(path.clone()))
+            .collect()
+    }
+
+    /// The file of the chain setting what `is_set` looks for, which wins over the files it
+    /// extends.
+    fn source(&self, is_set: impl Fn(&PyprojectSettings) -> bool) -> Source {
+        self.sources(is_set)
+            .into_iter()
+            .next()
+            .unwrap_or(Source::Default)
+    }
+
+    /// `layered` with its source: the file of the chain setting what `is_set` looks for when
+    /// taken from the configuration, `editorconfig` when taken from `.editorconfig`.
+    fn setting<T>(
+        &self,
+        layered: Layered<T>,
+        is_set: impl Fn(&PyprojectSettings) -> bool,
+        editorconfig: impl FnOnce() -> Source,
+    ) -> Setting<T> {
+        let source = match layered.layer {
+            Layer::Cli => Source::Cli,
+            Layer::Config => self.source(is_set),
+            Layer::Editorconfig => editorconfig(),
+            Layer::Default => Source::Default,
+        };
+        Setting {
+            value: layered.value,
+            source,
+        }
+    }
+}
+
+/// Print the settings that `format` and `check` resolve for `args.path`.
+pub fn show_settings(args: &ShowSettingsCommand, config: Option<&Path>) -> Result<ExitStatus> {
+    let resolved = resolve_settings(args, config)?;
+    let output = match args.output_format.unwrap_or_default() {
+        SettingsOutputFormat::Text => render_text(&resolved),
+        SettingsOutputFormat::Json => {
+            let mut json = serde_json::to_string_pretty(&resolved).map_err(std::io::Error::from)?;
+            json.push('\n');
+            json
+        }
+    };
+    stdout().lock().write_all(output.as_bytes())?;
+    Ok(ExitStatus::Success)
+}
+
+fn resolve_settings(args: &ShowSettingsCommand, config: Option<&Path>) -> Result<ResolvedSettings> {
+    // Files are processed by their canonical path, which `.editorconfig` sections and
+    // `per-file-ignores` match against.
+    let cwd = get_cwd();
+    let path = args
+        .path
+        .canonicalize()
+        .unwrap_or_else(|_| cwd.join(&args.path));
+    let config_file = config_file_for(&path, config)?;
+    let (chain, pyproject) = match &config_file {
+        Some(file) => (
+            ConfigChain(load_config_chain(file)?),
+            load_config_file(file)?,
+        ),
+        None => (ConfigChain(Vec::new()), PyprojectSettings::default()),
+    };
+    let root = config_file.as_deref().and_then(Path::parent).unwrap_or(cwd);
+
+    // `format` reads the `.editorconfig` of the current directory.
+    let editorconfig_path = find_nearest_ancestor_file(cwd, ".editorconfig");
+    let editorconfig = resolve_editorconfig(load_editorconfig_from_cwd().as_ref(), &path);
+    let sections = editorconfig_path
+        .as_deref()
+        .map_or_else(EditorconfigSections::default, |editorconfig_path| {
+            resolve_editorconfig_sections(editorconfig_path, &path)
+        });
+    let editorconfig_source =
+        |section| Source::Editorconfig(editorconfig_path.clone().unwrap_or_default(), section);
+
+    let settings = resolve_format_settings(&args.formatting, &pyproject, &editorconfig);
+    let format = format_settings(
+        settings,
+        args.formatting.template.custom_blocks.is_some(),
+        &chain,
+        sections,
+        editorconfig_source,
+    );
+    let profile = Setting::resolve(
+        [
+            (args.formatting.template.profile, Source::Cli),
+            (pyproject.profile, chain.source(|s| s.profile.is_some())),
+            (Profile::from_path(&path), Source::FileExtension),
+        ],
+        Profile::default(),
+    );
+    let lint = lint_settings(args, &path, &pyproject, &chain, root)?;
+
+    Ok(ResolvedSettings {
+        path: relativize_path(&path),
+        config_file: config_file.map(relativize_path),
+        format,
+        profile,
+        lint,
+    })
+}
+
+/// The configuration file that `format` and `check` follow for the canonical `path`: `config`
+/// when given, otherwise its nearest one below the project root, or the project root's.
+fn config_file_for(path: &Path, config: Option<&Path>) -> Result<Option<PathBuf>> {
+    let cwd = get_cwd();
+    if let Some(config) = config {
+        return Ok(Some(cwd.join(config)));
+    }
+    let root_file = find_nearest_ancestor_file_of(cwd, &CONFIG_FILES);
+    let root = root_file.as_deref().and_then(Path::parent).unwrap_or(cwd);
+    let nested = NestedProjects::discover(&[path.to_path_buf()], root, |_, directory| {
+        Ok(directory.to_path_buf())
+    })?;
+    Ok(nested.get(path).map_or(root_file, |directory| {
+        find_nearest_ancestor_file_of(directory, &CONFIG_FILES)
+    }))
+}
+
+/// The sources of `settings`, resolved by [`resolve_format_settings`]: the file of `chain`
+/// setting each, or the section of `.editorconfig` found in `sections`.
+fn format_settings(
+    settings: FormatSettings,
+    cli_custom_blocks: bool,
+    chain: &ConfigChain,
+    sections: EditorconfigSections,
+    editorconfig_source: impl Fn(Option<String>) -> Source,
+) -> FormatSection {
+    let mut custom_blocks_sources = Vec::new();
+    if cli_custom_blocks {
+        custom_blocks_sources.push(Source::Cli);
+    }
+    if chain.0.iter().any(|(_, s)| s.custom_blocks.is_some()) {
+        custom_blocks_sources.push(chain.source(|s| s.custom_blocks.is_some()));
+    }
+    FormatSection {
+        line_length: chain
+            .setting(
+                settings.line_length,
+                |s| s.line_length.is_some(),
+                || editorconfig_source(sections.line_length),
+            )
+            .map(LineLength::value),
+        indent_width: chain
+            .setting(
+                settings.indent_width,
+                |s| s.indent_width.is_some(),
+                || editorconfig_source(sections.indent_width),
+            )
+            .map(IndentWidth::value),
+        custom_blocks: Setting {
+            value: settings.custom_blocks.unwrap_or_default(),
+            source: Source::merged(custom_blocks_sources),
+        },
+        html_void_self_closing: chain.setting(
+            settings.html_void_self_closing,
+            |s| s.html_void_self_closing.is_some(),
+            || editorconfig_source(None),
+        ),
+        style_attr_layout: chain.setting(
+            settings.style_attr_layout,
+            |s| s.style_attr_layout.is_some(),
+            || editorconfig_source(None),
+        ),
+        preserve_unquoted_attrs: chain.setting(
+            settings.preserve_unquoted_attrs,
+            |s| s.preserve_unquoted_attrs.is_some(),
+            || editorconfig_source(None),
+        ),
+        normalize_template_whitespace: chain.setting(
+            settings.normalize_template_whitespace,
+            |s| s.normalize_template_whitespace.is_some(),
+            || editorconfig_source(None),
+        ),
+        wrap_template_tags: chain.setting(
+            settings.wrap_template_tags,
+            |s| s.wrap_template_tags.is_some(),
+            || editorconfig_source(None),
+        ),
+        attribute_order: chain.setting(
+            settings.attribute_order,
+            |s| s.attribute_order.is_some(),
+            || editorconfig_source(None),
+        ),
+    }
+}
+
+/// The lint settings of the canonical `path`, following `pyproject` found in `root`.
+fn lint_settings(
+    args: &ShowSettingsCommand,
+    path: &Path,
+    pyproject: &PyprojectSettings,
+    chain: &ConfigChain,
+    root: &Path,
+) -> Result<ResolvedLintSettings> {
+    let cli = &args.rule_selection;
+    let lint = pyproject.lint.as_ref();
+    let selectors =
+        |list: Vec<RuleSelector>| -> Vec<String> { list.iter().map(ToString::to_string).collect() };
+    let lint_source =
+        |is_set: fn(&LintSettings) -> bool| chain.source(|s| s.lint.as_ref().is_some_and(is_set));
+
+    // The rules themselves are resolved as `check` does.
+    let check_args = CheckCommand {
+        template: args.formatting.template.clone(),
+        rule_selection: cli.clone(),
+        ..CheckCommand::default()
+    };
+    let project = LintProject::new(&check_args, pyproject, root)?;
+    let rules = project.settings_for(path).rules;
+
+    Ok(ResolvedLintSettings {
+        select: Setting::resolve(
+            [
+                (cli.select.clone(), Source::Cli),
+                (
+                    lint.and_then(|l| l.select.clone()),
+                    lint_source(|l| l.select.is_some()),
+                ),
+            ],
+            vec![RuleSelector::All],
+        )
+        .map(selectors),
+        ignore: Setting::resolve(
+            [
+                (cli.ignore.clone(), Source::Cli),
+                (
+                    lint.and_then(|l| l.ignore.clone()),
+                    lint_source(|l| l.ignore.is_some()),
+                ),
+            ],
+            Vec::new(),
+        )
+        .map(selectors),
+        preview: Setting::resolve(
+            [
+                (resolve_bool_arg(cli.preview, cli.no_preview), Source::Cli),
+                (
+                    lint.and_then(|l| l.preview),
+                    lint_source(|l| l.preview.is_some()),
+                ),
+            ],
+            false,
+        ),
+        per_file_ignores: Setting {
+            value: project
+                .settings
+                .rules
+                .iter()
+                .filter(|rule| !rules.contains(*rule))
+                .map(|rule| rule.to_string())
+                .collect(),
+            source: Source::merged(chain.sources(|s| {
+                s.lint
+                    .as_ref()
+                    .is_some_and(|l| l.per_file_ignores.is_some())
+            })),
+        },
+        rules: rules.iter().map(|rule| rule.to_string()).collect(),
+    })
+}
+
+/// Render `settings` as TOML-like `name = value  # source` lines.
+fn render_text(settings: &ResolvedSettings) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "Settings for {}", settings.path);
+    let _ = writeln!(
+        output,
+        "Configuration file: {}",
+        settings.config_file.as_deref().unwrap_or("none")
+    );
+
+    let format = &settings.format;
+    let lines = [
+        line("line-length", &format.line_length),
+        line("indent-width", &format.indent_width),
+        line("custom-blocks", &format.custom_blocks),
+        line("html-void-self-closing", &format.html_void_self_closing),
+        line("style-attr-layout", &format.style_attr_layout),
+        line("preserve-unquoted-attrs", &format.preserve_unquoted_attrs),
+        line(
+            "normalize-template-whitespace",
+            &format.normalize_template_whitespace,
+        ),
+        line("wrap-template-tags", &format.wrap_template_tags),
+        line("attribute-order", &format.attribute_order),
+        line("profile", &settings.profile),
+    ];
+    let _ = writeln!(output);
+    write_lines(&mut output, &lines);
+
+    let lint = &settings.lint;
+    let lines = [
+        line("select", &lint.select),
+        line("ignore", &lint.ignore),
+        line("preview", &lint.preview),
+        line("per-file-ignores", &lint.per_file_ignores),
+    ];
+    let _ = writeln!(output, "\n[lint]");
+    write_lines(&mut output, &lines);
+    let _ = writeln!(output, "rules = {}", value_text(&lint.rules));
+    output
+}
+
+/// The `name = value` of a setting, and its source.
+fn line<T: Serialize>(name: &str, setting: &Setting<T>) -> (String, String) {
+    (
+        format!("{name} = {}", value_text(&setting.value)),
+        setting.source.to_string(),
+    )
+}
+
+/// Write `lines`, their sources aligned in a column.
+fn write_lines(output: &mut String, lines: &[(String, String)]) {
+    let width = lines.iter().map(|(line, _)| line.len()).max().unwrap_or(0);
+    for (line, source) in lines {
+        let _ = writeln!(output, "{line:width$}  # {source}");
+    }
+}
+
+/// A value written the way TOML does.
+fn value_text<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::Array(items)) => {
+            let items: Vec<String> = items.iter().map(ToString::to_string).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Ok(value) => value.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::{FormatCommand, FormattingArgs};
+    use crate::commands::format::FormatterConfig;
+    use crate::editorconfig::EditorconfigSettings;
+
+    #[test]
+    fn resolve_takes_the_first_set_candidate() {
+        let setting = Setting::resolve(
+            [
+                (None, Source::Cli),
+                (Some(2), Source::Config(PathBuf::from("djangofmt.toml"))),
+                (Some(3), Source::FileExtension),
+            ],
+            1,
+        );
+        assert_eq!(setting.value, 2);
+        assert_eq!(setting.source.to_string(), "djangofmt.toml");
+
+        let setting = Setting::resolve([(None, Source::Cli)], 1);
+        assert_eq!((setting.value, setting.source), (1, Source::Default));
+    }
+
+    #[test]
+    fn sources_display() {
+        assert_eq!(
+            Source::Editorconfig(PathBuf::from(".editorconfig"), Some("*.html".into())).to_string(),
+            ".editorconfig [*.html]"
+        );
+        assert_eq!(
+            Source::merged(vec![
+                Source::Cli,
+                Source::Config(PathBuf::from("pyproject.toml"))
+            ])
+            .to_string(),
+            "cli, pyproject.toml"
+        );
+        assert_eq!(Source::merged(Vec::new()), Source::Default);
+    }
+
+    #[test]
+    fn format_settings_build_the_formatter_config_of_format() {
+        let args = FormattingArgs {
+            line_length: Some(LineLength::try_from(100_u16).unwrap()),
+            no_wrap_template_tags: true,
+            ..FormattingArgs::default()
+        };
+        let pyproject: PyprojectSettings = toml::from_str(
+            "indent-width = 2\ncustom-blocks = [\"cache\"]\npreserve-unquoted-attrs = true\n",
+        )
+        .unwrap();
+        let editorconfig = EditorconfigSettings {
+            line_length: Some(LineLength::try_from(80_u16).unwrap()),
+            indent_width: Some(IndentWidth::try_from(8_u8).unwrap()),
+        };
+        let chain = ConfigChain(vec![(PathBuf::from("djangofmt.toml"), pyproject)]);
+        let pyproject = &chain.0[0].1;
+
+        let shown = format_settings(
+            resolve_format_settings(&args, pyproject, &editorconfig),
+            false,
+            &chain,
+            EditorconfigSections::default(),
+            |section| Source::Editorconfig(PathBuf::from(".editorconfig"), section),
+        );
+        assert_eq!(shown.line_length.source, Source::Cli);
+        assert_eq!(shown.indent_width.source.to_string(), "djangofmt.toml");
+        assert_eq!(shown.style_attr_layout.source, Source::Default);
+
+        let format = FormatCommand {
+            formatting: args,
+            ..FormatCommand::default()
+        };
+        let rebuilt = FormatterConfig::new(
+            LineLength::try_from(shown.line_length.value).unwrap(),
+            IndentWidth::try_from(shown.indent_width.value).unwrap(),
+            Some(shown.custom_blocks.value),
+            shown.html_void_self_closing.value,
+            shown.style_attr_layout.value,
+            shown.preserve_unquoted_attrs.value,
+            shown.normalize_template_whitespace.value,
+            shown.wrap_template_tags.value,
+            shown.attribute_order.value.as_ref(),
+        );
+        assert_eq!(
+            rebuilt.cache_key(),
+            FormatterConfig::from_args(&format, pyproject, &editorconfig).cache_key()
+        );
+    }
+
+    #[test]
+    fn values_are_written_as_toml() {
+        assert_eq!(value_text(&vec!["a", "b"]), r#"["a", "b"]"#);
+        assert_eq!(value_text(&Profile::Jinja), r#""jinja""#);
+        assert_eq!(value_text(&120), "120");
+    }
+}
@@ -0,0 +1,188 @@
+//! `--watch`: run `format` or `check` again whenever the files they process change.
+
+use std::collections::BTreeSet;
+use std::io::{IsTerminal, Write, stderr};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use tracing::{error, info, warn};
+
+use crate::ExitStatus;
+use crate::args::FileSelectionArgs;
+use crate::error::Result;
+use crate::pyproject::CONFIG_FILES;
+use crate::resolver::resolve_files;
+
+use super::{ResolvedCommand, resolve_command};
+
+/// How long to wait for more events after a change, so that a save touching several files (or
+/// the same file several times) triggers a single run.
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Run `run` on the files selected by `files` and `file_selection`, then again on those that
+/// change, until interrupted.
+pub(crate) fn watch(
+    files: &[PathBuf],
+    file_selection: &FileSelectionArgs,
+    config: Option<&Path>,
+    run: impl Fn(&ResolvedCommand, &[PathBuf]) -> Result<ExitStatus>,
+) -> Result<ExitStatus> {
+    let mut resolved = resolve_command(files, file_selection, config)?;
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    for path in files {
+        watcher.watch(path, RecursiveMode::Recursive)?;
+    }
+    watch_project_root(&mut watcher, files, &resolved.project_root)?;
+    if let Err(err) = run(&resolved, &resolved.files) {
+        error!("{err}");
+    }
+
+    loop {
+        info!("Watching for file changes.");
+        let Some(changes) = next_changes(&receiver) else {
+            return Ok(ExitStatus::Success);
+        };
+
+        let to_run = if changes.iter().any(|path| is_config_file(path)) {
+            match resolve_command(files, file_selection, config) {
+                Ok(reloaded) => resolved = reloaded,
+                Err(err) => {
+                    error!("{err}");
+                    continue;
+                }
+            }
+            resolved.files.clone()
+        } else {
+            let mut modified: Vec<PathBuf> = changes
+                .iter()
+                .filter_map(|path| std::fs::canonicalize(path).ok())
+                .filter(|path| path.is_file())
+                .collect();
+            // New files may or may not be selected: walk the watched paths again to find out.
+            if modified
+                .iter()
+                .any(|path| resolved.files.binary_search(path).is_err())
+            {
+                match resolve_files(files, &resolved.discovery) {
+                    Ok(selected) => resolved.files = selected,
+                    Err(err) => {
+                        error!("{err}");
+                        continue;
+                    }
+                }
+            }
+            modified.retain(|path| resolved.files.binary_search(path).is_ok());
+            modified.sort();
+            modified.dedup();
+            modified
+        };
+        if to_run.is_empty() {
+            continue;
+        }
+
+        clear_screen();
+        info!("File change detected...");
+        if let Err(err) = run(&resolved, &to_run) {
+            error!("{err}");
+        }
+        // Drop the events caused by the run itself, e.g. for the files it wrote.
+        while receiver.try_recv().is_ok() {}
+    }
+}
+
+/// Also watch the project root, whose `pyproject.toml` may live above the watched paths.
+fn watch_project_root(
+    watcher: &mut RecommendedWatcher,
+    files: &[PathBuf],
+    project_root: &Path,
+) -> Result<()> {
+    let covered = files
+        .iter()
+        .any(|path| std::fs::canonicalize(path).is_ok_and(|path| project_root.starts_with(path)));
+    if !covered {
+        watcher.watch(project_root, RecursiveMode::NonRecursive)?;
+    }
+    Ok(())
+}
+
+/// Wait for the next batch of changes, returning the changed paths.
+///
+/// `None` once the watcher stopped.
+fn next_changes(receiver: &Receiver<notify::Result<Event>>) -> Option<BTreeSet<PathBuf>> {
+    let mut changes = BTreeSet::new();
+    let mut event = receiver.recv().ok()?;
+    loop {
+        match event {
+            // Reading files, as the command itself does, is not a change.
+            Ok(event) if event.kind.is_access() => {}
+            Ok(event) => changes.extend(event.paths),
+            Err(err) => warn!("Failed to watch for file changes: {err}"),
+        }
+        event = match receiver.recv_timeout(DEBOUNCE) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) if changes.is_empty() => receiver.recv().ok()?,
+            Err(RecvTimeoutError::Timeout) => return Some(changes),
+            Err(RecvTimeoutError::Disconnected) => return None,
+        };
+    }
+}
+
+/// Whether `path` configures templates, so that changing it reloads the settings and runs the
+/// command on all files again.
+fn is_config_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name == ".editorconfig" || CONFIG_FILES.contains(&name))
+}
+
+/// Clear the terminal, so that only the output of the latest run is shown.
+fn clear_screen() {
+    let mut stderr = stderr();
+    if stderr.is_terminal() {
+        // Ignore failures: the previous output then merely stays on screen.
+        let _ = stderr.write_all(b"\x1B[2J\x1B[1;1H");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use notify::EventKind;
+    use notify::event::{AccessKind, ModifyKind};
+
+    use super::*;
+
+    #[test]
+    fn changes_are_batched_without_accesses() {
+        let (sender, receiver) = mpsc::channel();
+        let event = |kind, path: &str| Ok(Event::new(kind).add_path(PathBuf::from(path)));
+        sender
+            .send(event(EventKind::Access(AccessKind::Any), "read.html"))
+            .unwrap();
+        sender
+            .send(event(EventKind::Modify(ModifyKind::Any), "b.html"))
+            .unwrap();
+        sender
+            .send(event(EventKind::Modify(ModifyKind::Any), "a.html"))
+            .unwrap();
+        assert_eq!(
+            next_changes(&receiver),
+            Some(BTreeSet::from(["a.html".into(), "b.html".into()]))
+        );
+
+        drop(sender);
+        assert_eq!(next_changes(&receiver), None);
+    }
+
+    #[test]
+    fn config_files() {
+        assert!(is_config_file(Path::new("app/pyproject.toml")));
+        assert!(is_config_file(Path::new(".editorconfig")));
+        assert!(is_config_file(Path::new("djangofmt.toml")));
+        assert!(!is_config_file(Path::new("templates/pyproject.html")));
+    }
+}
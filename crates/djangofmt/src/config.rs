@@ -1,15 +1,20 @@
 //! Option resolution: merging CLI arguments, `pyproject.toml` settings and defaults.
 //!
 //! Precedence is always CLI > pyproject > default; [`resolve_profile`] extends
-//! it with file-extension inference. Every command resolves through here so the
+//! it with file-extension inference, and [`resolve_format_settings`] with
+//! `.editorconfig`. Every command resolves through here so the
 //! file and stdin paths cannot disagree.
 
 use std::path::Path;
 
 use djangofmt_lint::LintConfiguration;
 
-use crate::args::{Profile, RuleSelectionArgs};
-use crate::pyproject::{LintSettings, UnsortedTailwindClassesOptions};
+use crate::args::{FormattingArgs, Profile, RuleSelectionArgs};
+use crate::attribute_order::AttributeOrder;
+use crate::commands::format::merge_custom_blocks;
+use crate::editorconfig::EditorconfigSettings;
+use crate::line_width::{IndentWidth, LineLength, SelfClosing, StyleAttrLayout};
+use crate::pyproject::{LintSettings, PyprojectSettings, UnsortedTailwindClassesOptions};
 
 /// Collapse a `--flag` / `--no-flag` pair into an optional bool.
 #[must_use]
@@ -68,6 +73,127 @@ pub fn resolve_rule_selection(
     }
 }
 
+/// The layer of configuration a setting is taken from, by decreasing precedence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layer {
+    /// A command-line argument.
+    Cli,
+    /// The configuration file.
+    Config,
+    /// The `.editorconfig` settings of the file.
+    Editorconfig,
+    /// The default value.
+    Default,
+}
+
+/// A resolved setting and the layer it is taken from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layered<T> {
+    pub value: T,
+    pub layer: Layer,
+}
+
+impl<T: Default> Layered<T> {
+    /// The first of `cli`, `config` and `editorconfig` that is set, else the default.
+    fn resolve(cli: Option<T>, config: Option<T>, editorconfig: Option<T>) -> Self {
+        [
+            (cli, Layer::Cli),
+            (config, Layer::Config),
+            (editorconfig, Layer::Editorconfig),
+        ]
+        .into_iter()
+        .find_map(|(value, layer)| {
+            Some(Self {
+                value: value?,
+                layer,
+            })
+        })
+        .unwrap_or_else(|| Self {
+            value: T::default(),
+            layer: Layer::Default,
+        })
+    }
+}
+
+/// The settings a [`FormatterConfig`](crate::commands::format::FormatterConfig) is built from.
+#[derive(Clone, Debug)]
+pub struct FormatSettings {
+    pub line_length: Layered<LineLength>,
+    pub indent_width: Layered<IndentWidth>,
+    /// The custom blocks of the command line and of the configuration file, merged.
+    pub custom_blocks: Option<Vec<String>>,
+    pub html_void_self_closing: Layered<SelfClosing>,
+    pub style_attr_layout: Layered<StyleAttrLayout>,
+    pub preserve_unquoted_attrs: Layered<bool>,
+    pub normalize_template_whitespace: Layered<bool>,
+    pub wrap_template_tags: Layered<bool>,
+    pub attribute_order: Layered<Option<AttributeOrder>>,
+}
+
+/// Merge the formatting arguments with `pyproject.toml` and `.editorconfig` settings.
+///
+/// Precedence is `cli` > `pyproject` > `editorconfig` > `default`; `.editorconfig` only sets the
+/// line length and the indent width.
+#[must_use]
+pub fn resolve_format_settings(
+    args: &FormattingArgs,
+    pyproject: &PyprojectSettings,
+    editorconfig: &EditorconfigSettings,
+) -> FormatSettings {
+    FormatSettings {
+        line_length: Layered::resolve(
+            args.line_length,
+            pyproject.line_length,
+            editorconfig.line_length,
+        ),
+        indent_width: Layered::resolve(
+            args.indent_width,
+            pyproject.indent_width,
+            editorconfig.indent_width,
+        ),
+        custom_blocks: merge_custom_blocks(
+            args.template.custom_blocks.clone(),
+            pyproject.custom_blocks.clone(),
+        ),
+        html_void_self_closing: Layered::resolve(
+            args.html_void_self_closing,
+            pyproject.html_void_self_closing,
+            None,
+        ),
+        style_attr_layout: Layered::resolve(
+            args.style_attr_layout,
+            pyproject.style_attr_layout,
+            None,
+        ),
+        preserve_unquoted_attrs: Layered::resolve(
+            resolve_bool_arg(
+                args.preserve_unquoted_attrs,
+                args.no_preserve_unquoted_attrs,
+            ),
+            pyproject.preserve_unquoted_attrs,
+            None,
+        ),
+        normalize_template_whitespace: Layered::resolve(
+            resolve_bool_arg(
+                args.normalize_template_whitespace,
+                args.no_normalize_template_whitespace,
+            ),
+            pyproject.normalize_template_whitespace,
+            None,
+        ),
+        wrap_template_tags: Layered::resolve(
+            resolve_bool_arg(args.wrap_template_tags, args.no_wrap_template_tags),
+            pyproject.wrap_template_tags,
+            None,
+        ),
+        attribute_order: Layered::resolve(
+            args.attribute_order.clone().map(Some),
+            pyproject.attribute_order.clone().map(Some),
+            None,
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
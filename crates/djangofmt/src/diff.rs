@@ -0,0 +1,79 @@
+use std::fmt::Write as _;
+
+use clap::builder::styling::{AnsiColor, Effects, Style};
+use similar::TextDiff;
+
+const HEADER: Style = Style::new().effects(Effects::BOLD);
+const HUNK: Style = AnsiColor::Cyan.on_default();
+const DELETION: Style = AnsiColor::Red.on_default();
+const INSERTION: Style = AnsiColor::Green.on_default();
+
+/// Render a unified diff between the `unformatted` and `formatted` contents of the file displayed as `path`.
+///
+/// When `colored` is set, headers, hunk ranges, deletions and insertions are wrapped in ANSI styles.
+#[must_use]
+pub fn unified_diff(path: &str, unformatted: &str, formatted: &str, colored: bool) -> String {
+    let diff = TextDiff::from_lines(unformatted, formatted)
+        .unified_diff()
+        .header(path, path)
+        .to_string();
+
+    if !colored {
+        return diff;
+    }
+
+    let mut output = String::with_capacity(diff.len());
+    for line in diff.split_inclusive('\n') {
+        let style = if line.starts_with("---") || line.starts_with("+++") {
+            HEADER
+        } else if line.starts_with("@@") {
+            HUNK
+        } else if line.starts_with('-') {
+            DELETION
+        } else if line.starts_with('+') {
+            INSERTION
+        } else {
+            output.push_str(line);
+            continue;
+        };
+        let (content, newline) = line
+            .strip_suffix('\n')
+            .map_or((line, ""), |content| (content, "\n"));
+        let _ = write!(output, "{style}{content}{style:#}{newline}");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let diff = unified_diff(
+            "test.html",
+            "<div   class=\"foo\"  >\n</div>\n",
+            "<div class=\"foo\"></div>\n",
+            false,
+        );
+        assert_eq!(
+            diff,
+            "--- test.html\n+++ test.html\n@@ -1,2 +1 @@\n-<div   class=\"foo\"  >\n-</div>\n+<div class=\"foo\"></div>\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_identical() {
+        assert_eq!(
+            unified_diff("test.html", "<p></p>\n", "<p></p>\n", true),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_colored() {
+        let diff = unified_diff("test.html", "<p>a</p>\n", "<p>b</p>\n", true);
+        assert!(diff.contains("\u{1b}[31m-<p>a</p>\u{1b}[0m\n"));
+        assert!(diff.contains("\u{1b}[32m+<p>b</p>\u{1b}[0m\n"));
+    }
+}
@@ -69,6 +69,60 @@ pub fn resolve_editorconfig(
     settings
 }
 
+/// The names of the `.editorconfig` sections setting each of the [`EditorconfigSettings`] of a
+/// file.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct EditorconfigSections {
+    pub line_length: Option<String>,
+    pub indent_width: Option<String>,
+}
+
+/// Find the sections of the `.editorconfig` at `editorconfig_path` setting the indent/line
+/// settings of `path`: the last matching one, which wins over the earlier ones.
+#[must_use]
+pub fn resolve_editorconfig_sections(
+    editorconfig_path: &Path,
+    path: &Path,
+) -> EditorconfigSections {
+    let mut sections = EditorconfigSections::default();
+    let Ok(content) = fs::read_to_string(editorconfig_path) else {
+        return sections;
+    };
+    let dir = editorconfig_path.parent().unwrap_or_else(|| Path::new("."));
+    for (name, body) in split_sections(&content) {
+        // Resolve each section on its own, to tell whether it matches `path`.
+        let properties = EditorConfig::parse(&format!("[{name}]\n{body}"))
+            .with_cwd(dir)
+            .resolve(path);
+        if matches!(properties.indent_size, EditorConfigProperty::Value(_)) {
+            sections.indent_width = Some(name.to_string());
+        }
+        if matches!(
+            properties.max_line_length,
+            EditorConfigProperty::Value(MaxLineLength::Number(_))
+        ) {
+            sections.line_length = Some(name.to_string());
+        }
+    }
+    sections
+}
+
+/// Split the content of an `.editorconfig` into the name and body of each section, dropping the
+/// preamble before the first one.
+fn split_sections(content: &str) -> Vec<(&str, String)> {
+    let mut sections: Vec<(&str, String)> = Vec::new();
+    for line in content.lines() {
+        let header = line.trim();
+        if let Some(name) = header.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+            sections.push((name, String::new()));
+        } else if let Some((_, body)) = sections.last_mut() {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+    sections
+}
+
 /// Whether `.editorconfig` settings can differ between files.
 ///
 /// `false` when there is no config or only a root `[*]` section, letting callers
@@ -196,6 +250,33 @@ indent_size = 3
         );
     }
 
+    #[test]
+    fn finds_the_sections_setting_each_value() {
+        let project = Project::new().file(
+            ".editorconfig",
+            "
+root = true
+
+[*]
+indent_size = 4
+max_line_length = 100
+
+[*.html]
+indent_size = 2
+
+[*.txt]
+max_line_length = 80
+",
+        );
+        assert_eq!(
+            resolve_editorconfig_sections(&project.join(".editorconfig"), &project.join("x.html")),
+            EditorconfigSections {
+                line_length: Some("*".to_string()),
+                indent_width: Some("*.html".to_string()),
+            }
+        );
+    }
+
     #[rstest]
     #[case("[*]\nindent_size = 0")]
     #[case("[*]\nindent_size = 17")]
@@ -0,0 +1,104 @@
+//! `--output-format github`: [workflow commands] that GitHub Actions shows as annotations.
+//!
+//! [workflow commands]: https://docs.github.com/en/actions/reference/workflow-commands-for-github-actions#setting-an-error-message
+
+use std::io::{self, Write};
+
+use djangofmt_lint::FileDiagnostics;
+
+use super::{LocatedDiagnostic, located_diagnostics};
+use crate::error::CommandError;
+use crate::fs::relativize_path;
+
+/// Write one `::error` command per diagnostic of `files`, then per file error of `errors`.
+pub fn write_github(
+    writer: &mut impl Write,
+    files: &[&FileDiagnostics],
+    errors: &[CommandError],
+) -> io::Result<()> {
+    for LocatedDiagnostic {
+        path,
+        location,
+        end_location,
+        diagnostic,
+    } in located_diagnostics(files)
+    {
+        writeln!(
+            writer,
+            "::error file={},line={},col={},endLine={},endColumn={},title={}::{}",
+            escape_property(path),
+            location.line,
+            location.column,
+            end_location.line,
+            end_location.column,
+            escape_property(diagnostic.code),
+            escape_data(&diagnostic.message),
+        )?;
+    }
+
+    for error in errors {
+        let mut properties = Vec::new();
+        if let Some(path) = error.path() {
+            properties.push(format!("file={}", escape_property(&relativize_path(path))));
+        }
+        let (title, message) = match error {
+            CommandError::Parse(err) => {
+                let (line, column) = err.location();
+                properties.push(format!("line={line},col={column}"));
+                ("parse-error", err.message.clone())
+            }
+            CommandError::Read(..) | CommandError::Write(..) => ("io-error", error.to_string()),
+        };
+        properties.push(format!("title={title}"));
+        writeln!(
+            writer,
+            "::error {}::{}",
+            properties.join(","),
+            escape_data(&message)
+        )?;
+    }
+    Ok(())
+}
+
+/// Escape a command message, which runs to the end of the line.
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a command property value, which also ends at `,` and `::`.
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use djangofmt_lint::{LintDiagnostic, span};
+
+    use super::*;
+
+    #[test]
+    fn error_command() {
+        let file = FileDiagnostics::new(
+            "templates/a,b.html",
+            "<p>\n<form method=\"put\"></form>\n",
+            vec![LintDiagnostic {
+                code: "invalid-attr-value",
+                message: "Invalid value 'put' for attribute 'method'.\n100% sure".into(),
+                span: span(18, 3),
+                help: None,
+                fix: None,
+                fix_title: None,
+            }],
+        );
+        let mut output = Vec::new();
+        write_github(&mut output, &[&file], &[]).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "::error file=templates/a%2Cb.html,line=2,col=15,endLine=2,endColumn=18,\
+             title=invalid-attr-value::Invalid value 'put' for attribute 'method'.%0A100%25 sure\n"
+        );
+    }
+}
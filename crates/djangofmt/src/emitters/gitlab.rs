@@ -0,0 +1,182 @@
+//! `--output-format gitlab`: a [GitLab Code Quality] report.
+//!
+//! Fingerprints reuse the baseline ones, so an issue keeps its identity across line shifts and
+//! GitLab can tell new issues from fixed ones between pipelines.
+//!
+//! [GitLab Code Quality]: https://docs.gitlab.com/ci/testing/code_quality/#code-quality-report-format
+
+use std::hash::Hasher as _;
+use std::io::{self, Write};
+
+use djangofmt_lint::FileDiagnostics;
+use rustc_hash::FxHashMap;
+use serde::Serialize;
+
+use super::{Location, located_diagnostics};
+use crate::baseline::{StableHasher, fingerprint};
+use crate::error::CommandError;
+use crate::fs::relativize_path;
+
+#[derive(Serialize)]
+struct Issue {
+    description: String,
+    check_name: &'static str,
+    fingerprint: String,
+    severity: &'static str,
+    location: IssueLocation,
+}
+
+#[derive(Serialize)]
+struct IssueLocation {
+    path: String,
+    positions: Positions,
+}
+
+#[derive(Serialize)]
+struct Positions {
+    begin: Location,
+    end: Location,
+}
+
+/// Write a Code Quality report of the diagnostics of `files` and of `errors`.
+pub fn write_gitlab(
+    writer: &mut impl Write,
+    files: &[&FileDiagnostics],
+    errors: &[CommandError],
+) -> io::Result<()> {
+    let mut fingerprints = Fingerprints::default();
+    let mut issues = Vec::new();
+    for file in files {
+        let source = file.source_code.inner();
+        for located in located_diagnostics(std::slice::from_ref(file)) {
+            let content = fingerprint(source, located.diagnostic);
+            issues.push(Issue {
+                description: located.diagnostic.message.to_string(),
+                check_name: located.diagnostic.code,
+                fingerprint: fingerprints.unique(located.path, &content),
+                severity: "major",
+                location: IssueLocation {
+                    path: located.path.to_string(),
+                    positions: Positions {
+                        begin: located.location,
+                        end: located.end_location,
+                    },
+                },
+            });
+        }
+    }
+
+    for error in errors {
+        let path = error.path().map(relativize_path).unwrap_or_default();
+        let (check_name, description, location) = match error {
+            CommandError::Parse(err) => {
+                let (line, column) = err.location();
+                (
+                    "parse-error",
+                    err.message.clone(),
+                    Location { line, column },
+                )
+            }
+            CommandError::Read(..) | CommandError::Write(..) => (
+                "io-error",
+                error.to_string(),
+                Location { line: 1, column: 1 },
+            ),
+        };
+        issues.push(Issue {
+            fingerprint: fingerprints.unique(&path, check_name),
+            description,
+            check_name,
+            severity: "critical",
+            location: IssueLocation {
+                path,
+                positions: Positions {
+                    begin: location,
+                    end: location,
+                },
+            },
+        });
+    }
+
+    serde_json::to_writer_pretty(&mut *writer, &issues)?;
+    writeln!(writer)
+}
+
+/// Hands out the issue fingerprints, which GitLab requires to be unique: identical issues of a
+/// file are told apart by their rank.
+#[derive(Default)]
+struct Fingerprints {
+    seen: FxHashMap<u64, usize>,
+}
+
+impl Fingerprints {
+    fn unique(&mut self, path: &str, content: &str) -> String {
+        let mut hasher = StableHasher::default();
+        hasher.write(path.as_bytes());
+        // Never valid UTF-8, so the path can't run into the content.
+        hasher.write(&[0xff]);
+        hasher.write(content.as_bytes());
+        let base = hasher.finish();
+
+        let rank = self.seen.entry(base).or_default();
+        let mut hasher = StableHasher::default();
+        hasher.write(&base.to_le_bytes());
+        hasher.write(&u64::try_from(*rank).unwrap_or(u64::MAX).to_le_bytes());
+        *rank += 1;
+        format!("{:016x}", hasher.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use djangofmt_lint::{LintDiagnostic, span};
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    fn report(source: &str) -> Value {
+        let diagnostic = |offset| LintDiagnostic {
+            code: "missing-img-alt",
+            message: "Image is missing an `alt` attribute.".into(),
+            span: span(offset, 5),
+            help: None,
+            fix: None,
+            fix_title: None,
+        };
+        let file = FileDiagnostics::new(
+            "test.html",
+            source,
+            source
+                .match_indices("<img>")
+                .map(|(offset, _)| diagnostic(offset))
+                .collect(),
+        );
+        let mut output = Vec::new();
+        write_gitlab(&mut output, &[&file], &[]).unwrap();
+        serde_json::from_slice(&output).unwrap()
+    }
+
+    #[test]
+    fn issue() {
+        let issues = report("<p>\n<img>\n");
+        assert_eq!(issues[0]["check_name"], "missing-img-alt");
+        assert_eq!(issues[0]["severity"], "major");
+        assert_eq!(
+            issues[0]["location"],
+            json!({
+                "path": "test.html",
+                "positions": {"begin": {"line": 2, "column": 1}, "end": {"line": 2, "column": 6}},
+            })
+        );
+    }
+
+    #[test]
+    fn fingerprints_are_unique_and_stable() {
+        let issues = report("<img>\n<img>\n");
+        assert_ne!(issues[0]["fingerprint"], issues[1]["fingerprint"]);
+
+        let shifted = report("\n\n<img>\n    <img>\n");
+        assert_eq!(issues[0]["fingerprint"], shifted[0]["fingerprint"]);
+        assert_eq!(issues[1]["fingerprint"], shifted[1]["fingerprint"]);
+    }
+}
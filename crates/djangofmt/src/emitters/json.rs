@@ -0,0 +1,210 @@
+//! `--output-format json` and `json-lines`: one record per diagnostic or file error.
+//!
+//! `json` writes a single array once every file is checked; `json-lines` writes one object per
+//! line, the records of each file as soon as it is checked, so consumers can process them as
+//! they are read.
+
+use std::io::{self, Write};
+
+use djangofmt_lint::{Applicability, Edit, FileDiagnostics, LintDiagnostic};
+use serde::Serialize;
+
+use super::Location;
+use crate::error::CommandError;
+use crate::fs::relativize_path;
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum Record<'a> {
+    Diagnostic(DiagnosticRecord<'a>),
+    ParseError(ErrorRecord),
+    IoError(ErrorRecord),
+}
+
+#[derive(Serialize)]
+struct DiagnosticRecord<'a> {
+    filename: &'a str,
+    code: &'a str,
+    message: &'a str,
+    help: Option<&'a str>,
+    location: Location,
+    end_location: Location,
+    span: ByteSpan,
+    fix: Option<FixRecord<'a>>,
+}
+
+#[derive(Serialize)]
+struct FixRecord<'a> {
+    title: Option<&'a str>,
+    applicability: &'static str,
+    edits: Vec<EditRecord<'a>>,
+}
+
+#[derive(Serialize)]
+struct EditRecord<'a> {
+    /// Replacement text, empty for a deletion.
+    content: &'a str,
+    location: Location,
+    end_location: Location,
+    span: ByteSpan,
+}
+
+/// Half-open byte range in the file.
+#[derive(Serialize)]
+struct ByteSpan {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Serialize)]
+struct ErrorRecord {
+    filename: Option<String>,
+    message: String,
+    help: Option<String>,
+    location: Option<Location>,
+}
+
+/// Write the diagnostics of `files` followed by `errors` to `writer`.
+///
+/// With `lines`, each record is a JSON object on its own line instead of an array element.
+pub fn write_json(
+    writer: &mut impl Write,
+    files: &[&FileDiagnostics],
+    errors: &[CommandError],
+    lines: bool,
+) -> io::Result<()> {
+    let records = files
+        .iter()
+        .flat_map(|&file| {
+            file.diagnostics
+                .iter()
+                .map(move |diagnostic| Record::Diagnostic(diagnostic_record(file, diagnostic)))
+        })
+        .chain(errors.iter().map(error_record));
+
+    if lines {
+        for record in records {
+            serde_json::to_writer(&mut *writer, &record)?;
+            writeln!(writer)?;
+        }
+    } else {
+        serde_json::to_writer_pretty(&mut *writer, &records.collect::<Vec<_>>())?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+fn diagnostic_record<'a>(
+    file: &'a FileDiagnostics,
+    diagnostic: &'a LintDiagnostic,
+) -> DiagnosticRecord<'a> {
+    let source = &file.source_code;
+    let start = diagnostic.span.offset() as usize;
+    let end = start + diagnostic.span.len() as usize;
+    DiagnosticRecord {
+        filename: source.name(),
+        code: diagnostic.code,
+        message: &diagnostic.message,
+        help: diagnostic.help.as_deref(),
+        location: Location::of(source, start),
+        end_location: Location::of(source, end),
+        span: ByteSpan { start, end },
+        fix: diagnostic.fix.as_ref().map(|fix| FixRecord {
+            title: diagnostic.fix_title,
+            applicability: match fix.applicability() {
+                Applicability::Safe => "safe",
+                Applicability::Unsafe => "unsafe",
+            },
+            edits: fix
+                .edits()
+                .iter()
+                .map(|edit| edit_record(file, edit))
+                .collect(),
+        }),
+    }
+}
+
+fn edit_record<'a>(file: &FileDiagnostics, edit: &'a Edit) -> EditRecord<'a> {
+    EditRecord {
+        content: edit.content().unwrap_or_default(),
+        location: Location::of(&file.source_code, edit.start()),
+        end_location: Location::of(&file.source_code, edit.end()),
+        span: ByteSpan {
+            start: edit.start(),
+            end: edit.end(),
+        },
+    }
+}
+
+fn error_record<'a>(error: &CommandError) -> Record<'a> {
+    let filename = error.path().map(relativize_path);
+    match error {
+        CommandError::Parse(err) => {
+            let (line, column) = err.location();
+            Record::ParseError(ErrorRecord {
+                filename,
+                message: err.message.clone(),
+                help: err.hint().map(str::to_string),
+                location: Some(Location { line, column }),
+            })
+        }
+        CommandError::Read(..) | CommandError::Write(..) => Record::IoError(ErrorRecord {
+            filename,
+            message: error.to_string(),
+            help: None,
+            location: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use djangofmt_lint::{Fix, span};
+
+    use super::*;
+
+    fn render(files: &[&FileDiagnostics], lines: bool) -> String {
+        let mut output = Vec::new();
+        write_json(&mut output, files, &[], lines).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn file() -> FileDiagnostics {
+        let source = "<p>\n<form method=\"put\"></form>\n";
+        FileDiagnostics::new(
+            "test.html",
+            source,
+            vec![LintDiagnostic {
+                code: "invalid-attr-value",
+                message: "Invalid value 'put' for attribute 'method'.".into(),
+                span: span(18, 3),
+                help: Some("Use one of: get, post, dialog".into()),
+                fix: Some(Fix::unsafe_edit(Edit::replacement("post", span(18, 3)))),
+                fix_title: Some("Replace with `post`"),
+            }],
+        )
+    }
+
+    #[test]
+    fn json_lines_record() {
+        let file = file();
+        assert_eq!(
+            render(&[&file], true),
+            concat!(
+                r#"{"kind":"diagnostic","filename":"test.html","code":"invalid-attr-value","#,
+                r#""message":"Invalid value 'put' for attribute 'method'.","#,
+                r#""help":"Use one of: get, post, dialog","location":{"line":2,"column":15},"#,
+                r#""end_location":{"line":2,"column":18},"span":{"start":18,"end":21},"#,
+                r#""fix":{"title":"Replace with `post`","applicability":"unsafe","edits":"#,
+                r#"[{"content":"post","location":{"line":2,"column":15},"#,
+                r#""end_location":{"line":2,"column":18},"span":{"start":18,"end":21}}]}}"#,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn json_without_diagnostics_is_an_empty_array() {
+        assert_eq!(render(&[&FileDiagnostics::empty()], false), "[]\n");
+    }
+}
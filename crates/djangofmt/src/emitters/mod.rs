@@ -0,0 +1,60 @@
+//! Machine-readable renderings of `check` results, written to stdout.
+
+use djangofmt_lint::{FileDiagnostics, LintDiagnostic, span};
+use miette::{SourceCode, SpanContents};
+use serde::Serialize;
+
+pub mod github;
+pub mod gitlab;
+pub mod json;
+pub mod sarif;
+
+/// Where the documentation of each rule lives, under `{RULES_URL}/{rule}/`.
+pub const RULES_URL: &str = "https://unknownplatypus.github.io/djangofmt/docs/rules";
+
+/// A 1-based line and column, counted as in `--output-format concise`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    /// The location of byte `offset` in `source`.
+    #[must_use]
+    pub fn of(source: &impl SourceCode, offset: usize) -> Self {
+        source
+            .read_span(&span(offset, 0), 0, 0)
+            .map_or(Self { line: 0, column: 0 }, |contents| Self {
+                line: contents.line() + 1,
+                column: contents.column() + 1,
+            })
+    }
+}
+
+/// A diagnostic with its file and location resolved, as line-oriented formats print it.
+pub struct LocatedDiagnostic<'a> {
+    /// The file, as displayed in diagnostics.
+    pub path: &'a str,
+    pub location: Location,
+    pub end_location: Location,
+    pub diagnostic: &'a LintDiagnostic,
+}
+
+/// Every diagnostic of `files`, in order.
+pub fn located_diagnostics<'a>(
+    files: &'a [&'a FileDiagnostics],
+) -> impl Iterator<Item = LocatedDiagnostic<'a>> {
+    files.iter().flat_map(|file| {
+        let source = &file.source_code;
+        file.diagnostics.iter().map(move |diagnostic| {
+            let start = diagnostic.span.offset() as usize;
+            LocatedDiagnostic {
+                path: source.name(),
+                location: Location::of(source, start),
+                end_location: Location::of(source, start + diagnostic.span.len() as usize),
+                diagnostic,
+            }
+        })
+    })
+}
@@ -0,0 +1,433 @@
+//! `--output-format sarif`: a [SARIF 2.1.0] log, as uploaded to GitHub code scanning.
+//!
+//! Every rule of the registry is described under `tool.driver.rules`, so results can be
+//! explained even for rules that didn't fire. File errors become tool execution notifications.
+//! Region columns are counted in UTF-16 code units, the SARIF default GitHub expects.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use std::io::{self, Write};
+
+use djangofmt_lint::{FileDiagnostics, LintDiagnostic, Rule, RuleSelector};
+use serde::Serialize;
+
+use super::RULES_URL;
+use crate::error::CommandError;
+use crate::fs::relativize_path;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+#[derive(Serialize)]
+struct Log<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run<'a>; 1],
+}
+
+#[derive(Serialize)]
+struct Run<'a> {
+    tool: Tool,
+    results: Vec<SarifResult<'a>>,
+    invocations: [Invocation; 1],
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    information_uri: &'static str,
+    version: &'static str,
+    rules: Vec<ReportingDescriptor>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportingDescriptor {
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short_description: Option<Message<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_description: Option<MultiformatMessage>,
+    help_uri: String,
+    properties: RuleProperties,
+}
+
+#[derive(Serialize)]
+struct RuleProperties {
+    category: String,
+    status: &'static str,
+    since: &'static str,
+}
+
+#[derive(Serialize)]
+struct Message<T> {
+    text: T,
+}
+
+#[derive(Serialize)]
+struct MultiformatMessage {
+    text: &'static str,
+    markdown: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'a> {
+    rule_id: &'a str,
+    level: &'static str,
+    message: Message<&'a str>,
+    locations: [ResultLocation; 1],
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<SarifFix<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResultLocation {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Region>,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+impl ArtifactLocation {
+    /// The location of the file displayed as `name`, with `/` separators as URIs require.
+    fn new(name: &str) -> Self {
+        Self {
+            uri: name.replace('\\', "/"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+impl Region {
+    /// The region of the bytes `start..end` of `source`.
+    fn new(source: &str, start: usize, end: usize) -> Self {
+        let (start_line, start_column) = utf16_position(source, start);
+        let (end_line, end_column) = utf16_position(source, end);
+        Self {
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }
+    }
+}
+
+/// The 1-based line and column of byte `offset` of `source`, the column counted in UTF-16 code
+/// units.
+fn utf16_position(source: &str, offset: usize) -> (usize, usize) {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].encode_utf16().count() + 1,
+    )
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifFix<'a> {
+    description: Message<&'a str>,
+    artifact_changes: [ArtifactChange<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange<'a> {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement<'a> {
+    deleted_region: Region,
+    inserted_content: Message<&'a str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Invocation {
+    execution_successful: bool,
+    tool_execution_notifications: Vec<Notification>,
+}
+
+#[derive(Serialize)]
+struct Notification {
+    level: &'static str,
+    message: Message<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<ResultLocation>,
+}
+
+/// Write a SARIF log of the diagnostics of `files`, with `errors` as notifications.
+pub fn write_sarif(
+    writer: &mut impl Write,
+    files: &[&FileDiagnostics],
+    errors: &[CommandError],
+) -> io::Result<()> {
+    let log = Log {
+        schema: SCHEMA,
+        version: "2.1.0",
+        runs: [Run {
+            tool: Tool {
+                driver: Driver {
+                    name: "djangofmt",
+                    information_uri: env!("CARGO_PKG_REPOSITORY"),
+                    version: env!("CARGO_PKG_VERSION"),
+                    rules: RuleSelector::All.all_rules().map(rule_descriptor).collect(),
+                },
+            },
+            results: files
+                .iter()
+                .flat_map(|file| {
+                    file.diagnostics
+                        .iter()
+                        .map(|diagnostic| sarif_result(file, diagnostic))
+                })
+                .collect(),
+            invocations: [Invocation {
+                execution_successful: errors.is_empty(),
+                tool_execution_notifications: errors.iter().map(notification).collect(),
+            }],
+        }],
+    };
+    serde_json::to_writer_pretty(&mut *writer, &log)?;
+    writeln!(writer)
+}
+
+fn rule_descriptor(rule: Rule) -> ReportingDescriptor {
+    let explanation = rule.explanation();
+    let group = rule.group();
+    ReportingDescriptor {
+        id: rule.to_string(),
+        short_description: explanation.and_then(summary).map(|text| Message { text }),
+        full_description: explanation.map(|text| MultiformatMessage {
+            text,
+            markdown: text,
+        }),
+        help_uri: format!("{RULES_URL}/{rule}/"),
+        properties: RuleProperties {
+            category: rule.category().to_string(),
+            status: group.status(),
+            since: group.since(),
+        },
+    }
+}
+
+/// The "What it does" paragraph of a rule explanation, on one line.
+fn summary(explanation: &str) -> Option<String> {
+    let (_, section) = explanation.split_once("## What it does\n")?;
+    let paragraph: Vec<&str> = section
+        .lines()
+        .take_while(|line| !line.trim().is_empty())
+        .map(str::trim)
+        .collect();
+    (!paragraph.is_empty()).then(|| paragraph.join(" "))
+}
+
+fn sarif_result<'a>(file: &'a FileDiagnostics, diagnostic: &'a LintDiagnostic) -> SarifResult<'a> {
+    let uri = file.source_code.name();
+    let source = file.source_code.inner();
+    let fix_title: Option<&'a str> = diagnostic.fix_title;
+    let start = diagnostic.span.offset() as usize;
+    let end = start + diagnostic.span.len() as usize;
+    SarifResult {
+        rule_id: diagnostic.code,
+        level: "error",
+        message: Message {
+            text: &diagnostic.message,
+        },
+        locations: [ResultLocation {
+            physical_location: PhysicalLocation {
+                artifact_location: ArtifactLocation::new(uri),
+                region: Some(Region::new(source, start, end)),
+            },
+        }],
+        fixes: diagnostic
+            .fix
+            .iter()
+            .map(|fix| SarifFix {
+                description: Message {
+                    text: fix_title.unwrap_or(&diagnostic.message),
+                },
+                artifact_changes: [ArtifactChange {
+                    artifact_location: ArtifactLocation::new(uri),
+                    replacements: fix
+                        .edits()
+                        .iter()
+                        .map(|edit| Replacement {
+                            deleted_region: Region::new(source, edit.start(), edit.end()),
+                            inserted_content: Message {
+                                text: edit.content().unwrap_or_default(),
+                            },
+                        })
+                        .collect(),
+                }],
+            })
+            .collect(),
+    }
+}
+
+fn notification(error: &CommandError) -> Notification {
+    let (message, location) = match error {
+        CommandError::Parse(err) => {
+            let offset = err.span().offset() as usize;
+            (
+                err.message.clone(),
+                Some(Region::new(err.source_text(), offset, offset)),
+            )
+        }
+        CommandError::Read(..) | CommandError::Write(..) => (error.to_string(), None),
+    };
+    Notification {
+        level: "error",
+        message: Message { text: message },
+        locations: error
+            .path()
+            .map(|path| ResultLocation {
+                physical_location: PhysicalLocation {
+                    artifact_location: ArtifactLocation::new(&relativize_path(path)),
+                    region: location,
+                },
+            })
+            .into_iter()
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use djangofmt_lint::{Edit, Fix, span};
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    fn render(files: &[&FileDiagnostics]) -> Value {
+        let mut output = Vec::new();
+        write_sarif(&mut output, files, &[]).unwrap();
+        serde_json::from_slice(&output).unwrap()
+    }
+
+    #[test]
+    fn summary_is_the_what_it_does_paragraph() {
+        let explanation = "## What it does\nChecks for things\nthat are wrong.\n\n## Why is this bad?\nBecause.\n";
+        assert_eq!(
+            summary(explanation).as_deref(),
+            Some("Checks for things that are wrong.")
+        );
+        assert_eq!(summary("No sections."), None);
+    }
+
+    #[test]
+    fn describes_every_rule() {
+        let log = render(&[]);
+        let rules = log["runs"][0]["tool"]["driver"]["rules"]
+            .as_array()
+            .unwrap();
+        assert_eq!(rules.len(), RuleSelector::All.all_rules().count());
+        let rule = rules
+            .iter()
+            .find(|rule| rule["id"] == "invalid-attr-value")
+            .unwrap();
+        assert_eq!(rule["properties"]["category"], "correctness");
+        assert!(rule["shortDescription"]["text"].is_string());
+        assert_eq!(log["runs"][0]["results"], json!([]));
+        assert_eq!(
+            log["runs"][0]["invocations"][0]["executionSuccessful"],
+            true
+        );
+    }
+
+    #[test]
+    fn result_with_fix() {
+        let file = FileDiagnostics::new(
+            "templates/test.html",
+            "<p>\n<form method=\"put\"></form>\n",
+            vec![LintDiagnostic {
+                code: "invalid-attr-value",
+                message: "Invalid value 'put' for attribute 'method'.".into(),
+                span: span(18, 3),
+                help: None,
+                fix: Some(Fix::unsafe_edit(Edit::replacement("post", span(18, 3)))),
+                fix_title: Some("Replace with `post`"),
+            }],
+        );
+        let log = render(&[&file]);
+        let region = json!({"startLine": 2, "startColumn": 15, "endLine": 2, "endColumn": 18});
+        assert_eq!(
+            log["runs"][0]["results"][0],
+            json!({
+                "ruleId": "invalid-attr-value",
+                "level": "error",
+                "message": {"text": "Invalid value 'put' for attribute 'method'."},
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {"uri": "templates/test.html"},
+                        "region": region,
+                    }
+                }],
+                "fixes": [{
+                    "description": {"text": "Replace with `post`"},
+                    "artifactChanges": [{
+                        "artifactLocation": {"uri": "templates/test.html"},
+                        "replacements": [{
+                            "deletedRegion": region,
+                            "insertedContent": {"text": "post"},
+                        }],
+                    }],
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn columns_count_utf16_code_units() {
+        let file = FileDiagnostics::new(
+            "test.html",
+            "<p>\n😀é<img></p>\n",
+            vec![LintDiagnostic {
+                code: "missing-img-alt",
+                message: "Image is missing an `alt` attribute.".into(),
+                span: span(10, 5),
+                help: None,
+                fix: None,
+                fix_title: None,
+            }],
+        );
+        let log = render(&[&file]);
+        assert_eq!(
+            log["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"],
+            json!({"startLine": 2, "startColumn": 4, "endLine": 2, "endColumn": 9})
+        );
+    }
+}
@@ -17,6 +17,42 @@ pub enum Error {
     #[error("{0}")]
     #[diagnostic(code(djangofmt::resolve_error))]
     Resolve(String),
+
+    #[error("Invalid baseline file {path}: {err}", path = relativize_path(.0), err = .1)]
+    #[diagnostic(code(djangofmt::baseline_error))]
+    Baseline(PathBuf, #[source] serde_json::Error),
+
+    #[error(
+        "Baseline file {path} doesn't exist, create it with --update-baseline",
+        path = relativize_path(.0)
+    )]
+    #[diagnostic(code(djangofmt::baseline_error))]
+    MissingBaseline(PathBuf),
+
+    #[error(
+        "{config}: `extend` points to {extended}, which doesn't exist",
+        config = relativize_path(.0),
+        extended = relativize_path(.1)
+    )]
+    #[diagnostic(code(djangofmt::config_error))]
+    MissingExtend(PathBuf, PathBuf),
+
+    #[error(
+        "{config}: `extend` goes round in a cycle: {cycle}",
+        config = relativize_path(.0),
+        cycle = .1.iter().map(relativize_path).collect::<Vec<_>>().join(" -> ")
+    )]
+    #[diagnostic(code(djangofmt::config_error))]
+    ExtendCycle(PathBuf, Vec<PathBuf>),
+
+    #[error("Language server error: {0}")]
+    #[diagnostic(code(djangofmt::server_error))]
+    Server(String),
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("Failed to watch for file changes: {0}")]
+    #[diagnostic(code(djangofmt::watch_error))]
+    Watch(#[from] notify::Error),
 }
 
 #[must_use]
@@ -139,8 +175,27 @@ impl ParseError {
         }
     }
 
+    /// Help text suggesting how to fix the error, if any.
+    #[must_use]
+    pub fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+
+    /// The source the error was found in.
+    #[must_use]
+    pub fn source_text(&self) -> &str {
+        self.src.inner()
+    }
+
+    /// The source span the error points at.
+    #[must_use]
+    pub const fn span(&self) -> SourceSpan {
+        self.span
+    }
+
     /// 1-based line and column the error points at.
-    fn location(&self) -> (usize, usize) {
+    #[must_use]
+    pub fn location(&self) -> (usize, usize) {
         self.src
             .read_span(&self.span, 0, 0)
             .map_or((0, 0), |contents| {
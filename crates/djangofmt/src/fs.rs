@@ -26,11 +26,22 @@ pub fn find_nearest_ancestor_file<P: AsRef<Path>>(
     start_path: P,
     file_name: &str,
 ) -> Option<PathBuf> {
-    start_path
-        .as_ref()
-        .ancestors()
-        .map(|directory| directory.join(file_name))
-        .find(|candidate| candidate.is_file())
+    find_nearest_ancestor_file_of(start_path, &[file_name])
+}
+
+/// Finds the nearest of `file_names` by traversing directories upward from `start_path`.
+///
+/// In a directory holding several of them, the first one listed wins.
+pub fn find_nearest_ancestor_file_of<P: AsRef<Path>>(
+    start_path: P,
+    file_names: &[&str],
+) -> Option<PathBuf> {
+    start_path.as_ref().ancestors().find_map(|directory| {
+        file_names
+            .iter()
+            .map(|file_name| directory.join(file_name))
+            .find(|candidate| candidate.is_file())
+    })
 }
 
 #[cfg(test)]
@@ -56,6 +67,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn first_listed_file_wins_in_the_nearest_dir() {
+        let project = Project::new()
+            .file("first.toml", "")
+            .file("child/second.toml", "")
+            .file("child/third.toml", "");
+        assert_eq!(
+            find_nearest_ancestor_file_of(
+                project.join("child"),
+                &["first.toml", "second.toml", "third.toml"]
+            ),
+            Some(project.join("child/second.toml"))
+        );
+    }
+
     #[test]
     fn finds_file_in_ancestor_dir() {
         let project = Project::new().file("marker.toml", "").dir("child");
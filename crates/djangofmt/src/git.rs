@@ -0,0 +1,171 @@
+//! The files changed in the local git repository since a revision, for `--changed-since`.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+#[cfg(not(target_arch = "wasm32"))]
+use git2::{Delta, DiffOptions, Patch, Repository};
+
+use crate::error::Error;
+
+/// The files of the working tree that differ from a revision, staged or not, with their changed
+/// lines.
+#[derive(Debug, Default)]
+pub struct ChangedFiles {
+    /// Canonical paths to the 1-based line ranges changed in the current content, end exclusive.
+    /// `None` when the whole file is new.
+    files: BTreeMap<PathBuf, Option<Vec<Range<usize>>>>,
+}
+
+impl ChangedFiles {
+    /// Diff the working tree of the repository containing `cwd` against `rev`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn since(rev: &str, cwd: &Path) -> Result<Self, Error> {
+        let git_error = |err: git2::Error| {
+            Error::Resolve(format!("`--changed-since {rev}`: {}", err.message()))
+        };
+        let repo = Repository::discover(cwd).map_err(git_error)?;
+        let workdir = repo.workdir().ok_or_else(|| {
+            Error::Resolve(format!(
+                "`--changed-since {rev}`: the repository has no working tree"
+            ))
+        })?;
+        let workdir = std::fs::canonicalize(workdir)?;
+        let tree = repo
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_tree())
+            .map_err(git_error)?;
+
+        let mut options = DiffOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .context_lines(0);
+        let diff = repo
+            .diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))
+            .map_err(git_error)?;
+
+        let mut files = BTreeMap::new();
+        for (idx, delta) in diff.deltas().enumerate() {
+            let Some(path) = delta.new_file().path() else {
+                continue;
+            };
+            let lines = match delta.status() {
+                Delta::Modified | Delta::Renamed | Delta::Copied | Delta::Typechange => {
+                    // Binary files have no patch: treat them as entirely changed.
+                    Patch::from_diff(&diff, idx)
+                        .map_err(git_error)?
+                        .map(|patch| changed_lines(&patch))
+                        .transpose()
+                        .map_err(git_error)?
+                }
+                Delta::Added | Delta::Untracked => None,
+                _ => continue,
+            };
+            files.insert(workdir.join(path), lines);
+        }
+        Ok(Self { files })
+    }
+
+    /// Git isn't linked into the wasm build.
+    #[cfg(target_arch = "wasm32")]
+    pub fn since(rev: &str, _cwd: &Path) -> Result<Self, Error> {
+        Err(Error::Resolve(format!(
+            "`--changed-since {rev}`: git isn't available on this platform"
+        )))
+    }
+
+    /// Whether the file at the canonical `path` changed.
+    #[must_use]
+    pub fn contains(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    /// The changed line ranges of the file at the canonical `path`.
+    /// `None` when it is unchanged or entirely new.
+    #[must_use]
+    pub fn lines(&self, path: &Path) -> Option<&[Range<usize>]> {
+        self.files.get(path)?.as_deref()
+    }
+}
+
+/// The line ranges of the new side of each hunk of `patch`.
+#[cfg(not(target_arch = "wasm32"))]
+fn changed_lines(patch: &Patch<'_>) -> Result<Vec<Range<usize>>, git2::Error> {
+    (0..patch.num_hunks())
+        .map(|idx| {
+            let (hunk, _) = patch.hunk(idx)?;
+            let start = hunk.new_start() as usize;
+            // A pure deletion has no new lines: it starts at the line before the removed ones,
+            // which then stands for the change.
+            let len = (hunk.new_lines() as usize).max(1);
+            Ok(start.max(1)..start.max(1) + len)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::process::Command;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .args([
+                "-c",
+                "user.name=djangofmt",
+                "-c",
+                "user.email=djangofmt@example.com",
+            ])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+
+    #[test]
+    fn changed_files_since_revision() {
+        let dir = tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        git(&root, &["init", "-q"]);
+        fs::write(root.join("kept.html"), "<p></p>\n").unwrap();
+        fs::write(
+            root.join("edited.html"),
+            "<a></a>\n<b></b>\n<i></i>\n<u></u>\n",
+        )
+        .unwrap();
+        fs::write(root.join("removed.html"), "<p></p>\n").unwrap();
+        git(&root, &["add", "."]);
+        git(&root, &["commit", "-qm", "initial"]);
+
+        fs::write(root.join("edited.html"), "<a></a>\n<b   ></b>\n<i></i>\n").unwrap();
+        fs::remove_file(root.join("removed.html")).unwrap();
+        fs::create_dir(root.join("new")).unwrap();
+        fs::write(root.join("new/added.html"), "<p></p>\n").unwrap();
+
+        let changed = ChangedFiles::since("HEAD", &root).unwrap();
+        assert!(!changed.contains(&root.join("kept.html")));
+        assert!(!changed.contains(&root.join("removed.html")));
+        assert!(changed.contains(&root.join("new/added.html")));
+        assert_eq!(changed.lines(&root.join("new/added.html")), None);
+        // Line 2 was edited, and the deleted line 4 is marked on the line before it.
+        assert_eq!(
+            changed.lines(&root.join("edited.html")),
+            Some([2..3, 3..4].as_slice())
+        );
+    }
+
+    #[test]
+    fn unknown_revision_errors() {
+        let dir = tempdir().unwrap();
+        git(dir.path(), &["init", "-q"]);
+        let err = ChangedFiles::since("nope", dir.path()).unwrap_err();
+        assert!(err.to_string().starts_with("`--changed-since nope`: "));
+    }
+}
@@ -2,22 +2,33 @@ use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
 use clap::CommandFactory;
-use tracing::warn;
+use tracing::{error, warn};
 
 use crate::args::Args;
 use crate::logging::setup_tracing;
 pub mod args;
+pub mod attribute_order;
+pub mod baseline;
+pub mod cache;
 pub mod commands;
 pub mod config;
+pub mod diff;
 pub mod editorconfig;
+pub mod emitters;
 pub mod error;
 pub mod fs;
+pub mod git;
 pub mod line_width;
 mod logging;
 pub mod options_metadata;
 pub mod per_file_ignores;
 pub mod pyproject;
+pub mod range;
 pub mod resolver;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod server;
+pub mod template_tags;
+pub mod template_whitespace;
 #[cfg(test)]
 mod test_support;
 
@@ -55,18 +66,33 @@ pub fn run(
     }: Args,
 ) -> error::Result<ExitStatus> {
     setup_tracing(global_options.log_level());
+    let config = global_options.config.as_deref();
 
     match command {
-        Some(args::Commands::Check(ref check_args)) => commands::check::check(check_args),
+        Some(args::Commands::Check(ref check_args)) => commands::check::check(check_args, config),
+        Some(args::Commands::Rule(ref rule_args)) => commands::rule::rule(rule_args),
+        #[cfg(not(target_arch = "wasm32"))]
+        Some(args::Commands::Server) => server::run(),
+        #[cfg(target_arch = "wasm32")]
+        Some(args::Commands::Server) => Err(error::Error::Server(
+            "the language server isn't available on this platform".to_string(),
+        )),
+        Some(args::Commands::Clean) => commands::clean::clean(config),
+        Some(args::Commands::ShowSettings(ref show_settings_args)) => {
+            commands::show_settings::show_settings(show_settings_args, config)
+        }
         Some(args::Commands::Completions { shell }) => {
             shell.generate(&mut Args::command(), &mut std::io::stdout());
             Ok(ExitStatus::Success)
         }
         None => {
             if is_stdin(&fmt.files, fmt.stdin_filename.as_deref()) {
-                commands::format_stdin::format_stdin(&fmt)
+                commands::format_stdin::format_stdin(&fmt, config)
+            } else if fmt.range.is_some() {
+                error!("`--range` can only be used when formatting standard input");
+                Ok(ExitStatus::Error)
             } else {
-                commands::format::format(&fmt)
+                commands::format::format(&fmt, config)
             }
         }
     }
@@ -1,10 +1,10 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::num::{NonZeroU8, NonZeroU16};
 use std::str::FromStr;
 
 /// The length of a line of text that is considered too long.
 /// The allowed range of values is 1..=320.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct LineLength(NonZeroU16);
 
 impl LineLength {
@@ -83,7 +83,7 @@ impl std::fmt::Display for LineLength {
 
 /// The width of an indentation level.
 /// The allowed range of values is 1..=16.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct IndentWidth(NonZeroU8);
 
 impl IndentWidth {
@@ -170,7 +170,9 @@ impl std::fmt::Display for IndentWidth {
 ///     - <https://developer.mozilla.org/en-US/docs/Glossary/Void_element>
 ///     - <https://github.com/whatwg/html/issues/721>
 ///     - <https://github.com/whatwg/html/issues/9491>
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize, clap::ValueEnum,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum SelfClosing {
     #[default]
@@ -191,3 +193,18 @@ impl From<SelfClosing> for Option<bool> {
         }
     }
 }
+
+/// Controls how `style` attribute declarations are laid out.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleAttrLayout {
+    /// All declarations on a single line
+    #[default]
+    SingleLine,
+    /// One declaration per line when they exceed the line length
+    Auto,
+    /// One declaration per line when the source spans several lines
+    PreserveBreaks,
+}
@@ -10,13 +10,24 @@ use std::{
 use tracing::debug;
 
 use crate::args::{OutputFormat, Profile};
+use crate::attribute_order::AttributeOrder;
 use crate::error::{Error, Result};
-use crate::line_width::{IndentWidth, LineLength, SelfClosing};
+use crate::line_width::{IndentWidth, LineLength, SelfClosing, StyleAttrLayout};
 
 /// Options shared by the `format` and `check` commands.
 #[derive(Debug, Default, Deserialize, PartialEq, Eq, OptionsMetadata)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub struct PyprojectSettings {
+    /// A configuration file to inherit settings from, relative to this one. Settings set here
+    /// override the inherited ones, except that `extend-exclude`, `extend-include` and
+    /// `lint.per-file-ignores` add to them. Paths are still relative to this file's directory.
+    #[option(
+        default = "null",
+        value_type = "str",
+        example = r#"extend = "../shared/djangofmt.toml""#
+    )]
+    pub extend: Option<PathBuf>,
+
     /// The line length the formatter should fit code into when possible.
     #[option(default = "120", value_type = "int", example = "line-length = 88")]
     pub line_length: Option<LineLength>,
@@ -52,6 +63,16 @@ pub struct PyprojectSettings {
     )]
     pub html_void_self_closing: Option<SelfClosing>,
 
+    /// How declarations of `style` attributes are laid out: all on one line, one per line
+    /// when they exceed the line length (`auto`), or one per line when the source already
+    /// spans several lines (`preserve-breaks`).
+    #[option(
+        default = r#""single-line""#,
+        value_type = r#""single-line" | "auto" | "preserve-breaks""#,
+        example = r#"style-attr-layout = "auto""#
+    )]
+    pub style_attr_layout: Option<StyleAttrLayout>,
+
     /// Whether to leave unquoted attribute values (e.g. `prop=True`) as-is instead of quoting
     /// them. Useful for template syntaxes that assign non-string values through attributes.
     #[option(
@@ -61,6 +82,38 @@ pub struct PyprojectSettings {
     )]
     pub preserve_unquoted_attrs: Option<bool>,
 
+    /// Whether to normalize the whitespace inside template tags, variables and comments:
+    /// `{{x|lower}}` becomes `{{ x|lower }}`, `{%if a==b%}` becomes `{% if a == b %}`.
+    /// Jinja whitespace-control markers (`{%-`, `-%}`) and string literals are kept as written.
+    #[option(
+        default = "false",
+        value_type = "bool",
+        example = "normalize-template-whitespace = true"
+    )]
+    pub normalize_template_whitespace: Option<bool>,
+
+    /// Whether to wrap the template tags exceeding `line-length`, one keyword argument per
+    /// line with a hanging indent. Only applies to the `jinja` profile, as Django doesn't
+    /// parse tags spanning several lines.
+    #[option(
+        default = "false",
+        value_type = "bool",
+        example = "wrap-template-tags = true"
+    )]
+    pub wrap_template_tags: Option<bool>,
+
+    /// The order to put the attributes of elements in: a preset (`"code-guide"`, `"id-first"`
+    /// or `"alphabetical"`), or a list of attribute names and globs, each one a group.
+    /// Attributes matching no group go last, and attributes keep their order within a group.
+    /// Attributes are never moved across template tags, and elements with duplicate attributes
+    /// are left untouched.
+    #[option(
+        default = "null",
+        value_type = r#""code-guide" | "id-first" | "alphabetical" | list[str]"#,
+        example = r#"attribute-order = ["id", "class", "name", "data-*", "aria-*", "on*"]"#
+    )]
+    pub attribute_order: Option<AttributeOrder>,
+
     /// File and directory patterns to exclude from discovery, replacing the default excludes.
     #[option(
         default = r#"[".bzr", ".direnv", ".eggs", ".git", ".git-rewrite", ".hg", ".mypy_cache", ".nox", ".pants.d", ".pytype", ".ruff_cache", ".svn", ".tox", ".venv", "__pypackages__", "_build", "buck-out", "dist", "node_modules", "venv"]"#,
@@ -110,10 +163,65 @@ pub struct PyprojectSettings {
     )]
     pub force_exclude: Option<bool>,
 
+    /// The directory to cache formatting and lint results in, relative to the `pyproject.toml`
+    /// directory. Use `djangofmt clean` to remove it.
+    #[option(
+        default = r#"".djangofmt_cache""#,
+        value_type = "str",
+        example = r#"cache-dir = ".cache/djangofmt""#
+    )]
+    pub cache_dir: Option<PathBuf>,
+
     #[option_group]
     pub lint: Option<LintSettings>,
 }
 
+impl PyprojectSettings {
+    /// Merge `base`, the settings this file extends, into these.
+    #[must_use]
+    fn extend(self, base: Self) -> Self {
+        Self {
+            extend: self.extend,
+            line_length: self.line_length.or(base.line_length),
+            indent_width: self.indent_width.or(base.indent_width),
+            profile: self.profile.or(base.profile),
+            custom_blocks: self.custom_blocks.or(base.custom_blocks),
+            html_void_self_closing: self.html_void_self_closing.or(base.html_void_self_closing),
+            style_attr_layout: self.style_attr_layout.or(base.style_attr_layout),
+            preserve_unquoted_attrs: self
+                .preserve_unquoted_attrs
+                .or(base.preserve_unquoted_attrs),
+            normalize_template_whitespace: self
+                .normalize_template_whitespace
+                .or(base.normalize_template_whitespace),
+            wrap_template_tags: self.wrap_template_tags.or(base.wrap_template_tags),
+            attribute_order: self.attribute_order.or(base.attribute_order),
+            exclude: self.exclude.or(base.exclude),
+            extend_exclude: concat(base.extend_exclude, self.extend_exclude),
+            include: self.include.or(base.include),
+            extend_include: concat(base.extend_include, self.extend_include),
+            respect_gitignore: self.respect_gitignore.or(base.respect_gitignore),
+            force_exclude: self.force_exclude.or(base.force_exclude),
+            cache_dir: self.cache_dir.or(base.cache_dir),
+            lint: match (self.lint, base.lint) {
+                (Some(lint), Some(base)) => Some(lint.extend(base)),
+                (lint, base) => lint.or(base),
+            },
+        }
+    }
+}
+
+/// `base` followed by `extension`, `None` when both are.
+fn concat<T>(base: Option<Vec<T>>, extension: Option<Vec<T>>) -> Option<Vec<T>> {
+    match (base, extension) {
+        (Some(mut base), Some(extension)) => {
+            base.extend(extension);
+            Some(base)
+        }
+        (base, extension) => base.or(extension),
+    }
+}
+
 /// Options for the `check` command.
 #[derive(Debug, Default, Deserialize, PartialEq, Eq, OptionsMetadata)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
@@ -158,7 +266,7 @@ pub struct LintSettings {
     /// How diagnostics are rendered.
     #[option(
         default = r#""full""#,
-        value_type = r#""full" | "concise""#,
+        value_type = r#""full" | "concise" | "json" | "json-lines" | "sarif" | "github" | "gitlab""#,
         example = r#"output-format = "concise""#
     )]
     pub output_format: Option<OutputFormat>,
@@ -177,6 +285,33 @@ pub struct LintSettings {
     pub unsorted_tailwind_classes: Option<UnsortedTailwindClassesOptions>,
 }
 
+impl LintSettings {
+    /// Merge `base`, the settings this file extends, into these.
+    #[must_use]
+    fn extend(self, base: Self) -> Self {
+        Self {
+            select: self.select.or(base.select),
+            ignore: self.ignore.or(base.ignore),
+            preview: self.preview.or(base.preview),
+            fix: self.fix.or(base.fix),
+            unsafe_fixes: self.unsafe_fixes.or(base.unsafe_fixes),
+            show_fixes: self.show_fixes.or(base.show_fixes),
+            output_format: self.output_format.or(base.output_format),
+            // The globs of both apply, these winning for a glob set in both.
+            per_file_ignores: match (self.per_file_ignores, base.per_file_ignores) {
+                (Some(per_file_ignores), Some(mut base)) => {
+                    base.extend(per_file_ignores);
+                    Some(base)
+                }
+                (per_file_ignores, base) => per_file_ignores.or(base),
+            },
+            unsorted_tailwind_classes: self
+                .unsorted_tailwind_classes
+                .or(base.unsorted_tailwind_classes),
+        }
+    }
+}
+
 /// Options for the [`unsorted-tailwind-classes`](rules/unsorted-tailwind-classes.md) rule.
 #[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq, OptionsMetadata)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
@@ -211,6 +346,9 @@ struct Tool {
     djangofmt: Option<PyprojectSettings>,
 }
 
+/// The configuration files djangofmt reads, by precedence when a directory holds several.
+pub const CONFIG_FILES: [&str; 3] = [".djangofmt.toml", "djangofmt.toml", "pyproject.toml"];
+
 /// Loads `Options` from a given `pyproject.toml` file
 fn load_options_from_pyproject_toml(content: &str) -> Result<PyprojectSettings> {
     let pyproject = toml::from_str::<PyProject>(content)
@@ -218,22 +356,92 @@ fn load_options_from_pyproject_toml(content: &str) -> Result<PyprojectSettings>
     Ok(pyproject.tool.and_then(|t| t.djangofmt).unwrap_or_default())
 }
 
-/// Load `pyproject.toml` settings rooted at the current working directory,
-/// falling back to defaults if the cwd can't be determined.
-pub fn load_pyproject_from_cwd() -> Result<(PyprojectSettings, PathBuf)> {
-    load_options(crate::fs::get_cwd())
+/// Loads `Options` from a given `djangofmt.toml` file, holding them at the top level.
+fn load_options_from_djangofmt_toml(content: &str, file_name: &str) -> Result<PyprojectSettings> {
+    toml::from_str::<PyprojectSettings>(content)
+        .map_err(|err| Error::Resolve(format!("Failed to parse {file_name}: {err}")))
+}
+
+/// Load the settings of the current working directory, or those of `config` when given.
+///
+/// Also returns the directory anchoring path-relative config, as [`load_options`] does.
+pub fn load_config(config: Option<&Path>) -> Result<(PyprojectSettings, PathBuf)> {
+    let cwd = crate::fs::get_cwd();
+    let Some(config) = config else {
+        return load_options(cwd);
+    };
+    let config = cwd.join(config);
+    let root = config
+        .parent()
+        .map_or_else(|| cwd.to_path_buf(), Path::to_path_buf);
+    Ok((load_config_file(&config)?, root))
+}
+
+/// Load user configured options from the configuration file at `path`: the
+/// `[tool.djangofmt]` table of a `pyproject.toml`, or the whole of any other TOML file.
+///
+/// The settings it `extend`s are merged in.
+pub fn load_config_file(path: &Path) -> Result<PyprojectSettings> {
+    Ok(load_config_chain(path)?
+        .into_iter()
+        .map(|(_, settings)| settings)
+        .rev()
+        .reduce(|base, settings| settings.extend(base))
+        .unwrap_or_default())
 }
 
-/// Loads user configured options from the nearest `pyproject.toml` file from the given path.
+/// The settings of the configuration file at `path`, followed by those of the files it
+/// `extend`s, in order, unmerged.
+pub fn load_config_chain(path: &Path) -> Result<Vec<(PathBuf, PyprojectSettings)>> {
+    let mut chain = Vec::new();
+    // The canonical paths of the files in `chain`, to detect cycles.
+    let mut seen = Vec::new();
+    let mut path = path.to_path_buf();
+    loop {
+        let settings = read_config_file(&path)?;
+        let Some(extend) = &settings.extend else {
+            chain.push((path, settings));
+            return Ok(chain);
+        };
+        let base = path.parent().unwrap_or_else(|| Path::new(".")).join(extend);
+        if !base.is_file() {
+            return Err(Error::MissingExtend(path, base));
+        }
+        seen.push(path.canonicalize()?);
+        let canonical_base = base.canonicalize()?;
+        if let Some(start) = seen.iter().position(|link| *link == canonical_base) {
+            let mut cycle = seen.split_off(start);
+            cycle.push(canonical_base);
+            return Err(Error::ExtendCycle(path, cycle));
+        }
+        chain.push((path, settings));
+        path = base;
+    }
+}
+
+/// The settings of the configuration file at `path` alone.
+fn read_config_file(path: &Path) -> Result<PyprojectSettings> {
+    debug!("Loading options from {}", path.display());
+    let content = fs::read_to_string(path)
+        .map_err(|err| Error::Resolve(format!("Failed to read {}: {err}", path.display())))?;
+    match path.file_name().and_then(|name| name.to_str()) {
+        Some("pyproject.toml") => load_options_from_pyproject_toml(&content),
+        file_name => load_options_from_djangofmt_toml(&content, file_name.unwrap_or("config")),
+    }
+}
+
+/// Loads user configured options from the nearest configuration file from the given path:
+/// `.djangofmt.toml`, `djangofmt.toml` or `pyproject.toml`, in this order of precedence
+/// within a directory.
 ///
 /// Also returns the directory that file was found in (the search start when there is none),
 /// which anchors path-relative config such as `per-file-ignores`.
 pub fn load_options<P: AsRef<Path>>(start_path: P) -> Result<(PyprojectSettings, PathBuf)> {
-    let Some(pyproject_path) =
-        crate::fs::find_nearest_ancestor_file(start_path.as_ref(), "pyproject.toml")
+    let Some(config_path) =
+        crate::fs::find_nearest_ancestor_file_of(start_path.as_ref(), &CONFIG_FILES)
     else {
         debug!(
-            "No pyproject.toml found starting search from: {}",
+            "No configuration file found starting search from: {}",
             start_path.as_ref().display()
         );
         return Ok((
@@ -241,21 +449,122 @@ pub fn load_options<P: AsRef<Path>>(start_path: P) -> Result<(PyprojectSettings,
             start_path.as_ref().to_path_buf(),
         ));
     };
-    debug!(
-        "Loading options from pyproject.toml at: {}",
-        pyproject_path.display()
-    );
-
-    let content = fs::read_to_string(&pyproject_path).map_err(|err| {
-        Error::Resolve(format!(
-            "Failed to read {}: {err}",
-            pyproject_path.display()
-        ))
-    })?;
-    let root = pyproject_path
+    let root = config_path
         .parent()
         .map_or_else(|| start_path.as_ref().to_path_buf(), Path::to_path_buf);
-    Ok((load_options_from_pyproject_toml(&content)?, root))
+    Ok((load_config_file(&config_path)?, root))
+}
+
+/// Settings derived from the `pyproject.toml` files closer to some of the processed files than
+/// the project root's, e.g. those of the apps of a monorepo.
+///
+/// Each file follows its nearest `pyproject.toml` only: settings are not merged across files.
+#[derive(Debug)]
+pub struct NestedProjects<T> {
+    /// The settings derived from each nested `pyproject.toml`.
+    projects: Vec<T>,
+    /// The index in `projects` of the nearest `pyproject.toml` of each directory looked up,
+    /// `None` when it is the project root's.
+    directories: BTreeMap<PathBuf, Option<usize>>,
+}
+
+impl<T> Default for NestedProjects<T> {
+    fn default() -> Self {
+        Self {
+            projects: Vec::new(),
+            directories: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> NestedProjects<T> {
+    /// Find the nearest `pyproject.toml` of each of the canonical `files`, deriving settings with
+    /// `build` once for each one other than the `project_root`'s.
+    ///
+    /// Files without any `pyproject.toml` above them follow the project root's.
+    pub fn discover(
+        files: &[PathBuf],
+        project_root: &Path,
+        mut build: impl FnMut(PyprojectSettings, &Path) -> Result<T>,
+    ) -> Result<Self> {
+        let project_root = project_root
+            .canonicalize()
+            .unwrap_or_else(|_| project_root.to_path_buf());
+        let mut nested = Self::default();
+        for file in files {
+            nested.discover_file(file, &project_root, &mut build)?;
+        }
+        Ok(nested)
+    }
+
+    /// Find the nearest `pyproject.toml` of the canonical `file` as well, like
+    /// [`NestedProjects::discover`] does, unless its directory was looked up already.
+    ///
+    /// `project_root` has to be canonical too.
+    pub fn discover_file(
+        &mut self,
+        file: &Path,
+        project_root: &Path,
+        build: &mut impl FnMut(PyprojectSettings, &Path) -> Result<T>,
+    ) -> Result<()> {
+        if let Some(directory) = file.parent() {
+            self.lookup(directory, project_root, build)?;
+        }
+        Ok(())
+    }
+
+    fn lookup(
+        &mut self,
+        directory: &Path,
+        project_root: &Path,
+        build: &mut impl FnMut(PyprojectSettings, &Path) -> Result<T>,
+    ) -> Result<Option<usize>> {
+        if let Some(&project) = self.directories.get(directory) {
+            return Ok(project);
+        }
+        let project = if directory == project_root {
+            None
+        } else if has_project_config(directory)? {
+            let (pyproject, _) = load_options(directory)?;
+            self.projects.push(build(pyproject, directory)?);
+            Some(self.projects.len() - 1)
+        } else if let Some(parent) = directory.parent() {
+            self.lookup(parent, project_root, build)?
+        } else {
+            None
+        };
+        self.directories.insert(directory.to_path_buf(), project);
+        Ok(project)
+    }
+
+    /// The settings of the nearest `pyproject.toml` of the file at `path`, one of the files
+    /// discovered. `None` when it is the project root's.
+    #[must_use]
+    pub fn get(&self, path: &Path) -> Option<&T> {
+        let project = (*self.directories.get(path.parent()?)?)?;
+        self.projects.get(project)
+    }
+}
+
+/// Whether `directory` holds the settings of a project: a `djangofmt.toml` or `.djangofmt.toml`,
+/// or a `pyproject.toml` with a `[tool.djangofmt]` table. Other `pyproject.toml` files belong to
+/// Python packages that don't configure djangofmt.
+fn has_project_config(directory: &Path) -> Result<bool> {
+    let pyproject = directory.join("pyproject.toml");
+    if CONFIG_FILES
+        .iter()
+        .any(|file_name| *file_name != "pyproject.toml" && directory.join(file_name).is_file())
+    {
+        return Ok(true);
+    }
+    if !pyproject.is_file() {
+        return Ok(false);
+    }
+    let content = fs::read_to_string(&pyproject)
+        .map_err(|err| Error::Resolve(format!("Failed to read {}: {err}", pyproject.display())))?;
+    let pyproject = toml::from_str::<PyProject>(&content)
+        .map_err(|err| Error::Resolve(format!("Failed to parse pyproject.toml: {err}")))?;
+    Ok(pyproject.tool.is_some_and(|tool| tool.djangofmt.is_some()))
 }
 
 #[cfg(test)]
@@ -310,6 +619,156 @@ mod tests {
         );
     }
 
+    #[test]
+    fn nested_projects_follow_the_nearest_pyproject_toml() {
+        let project = Project::new()
+            .file("pyproject.toml", "[tool.djangofmt]\nline-length = 100\n")
+            .file("base.html", "")
+            .file(
+                "apps/blog/pyproject.toml",
+                "[tool.djangofmt]\nline-length = 80\n",
+            )
+            .file("apps/blog/templates/post.html", "")
+            // Without a `[tool.djangofmt]` table, it doesn't configure its files.
+            .file("apps/shop/pyproject.toml", "[project]\nname = \"shop\"\n")
+            .file("apps/shop/cart.html", "")
+            // A djangofmt config file does, even empty.
+            .file("apps/legacy/djangofmt.toml", "")
+            .file("apps/legacy/index.html", "");
+        let root = project.path().canonicalize().unwrap();
+        let files: Vec<_> = [
+            "base.html",
+            "apps/blog/templates/post.html",
+            "apps/shop/cart.html",
+            "apps/legacy/index.html",
+        ]
+        .iter()
+        .map(|file| root.join(file))
+        .collect();
+
+        let mut loaded = Vec::new();
+        let nested = NestedProjects::discover(&files, project.path(), |pyproject, directory| {
+            loaded.push(directory.to_path_buf());
+            Ok(pyproject.line_length)
+        })
+        .unwrap();
+        assert_eq!(loaded, [root.join("apps/blog"), root.join("apps/legacy")]);
+        assert_eq!(nested.get(&files[0]), None);
+        assert_eq!(
+            nested.get(&files[1]),
+            Some(&Some(LineLength::try_from(80u16).unwrap()))
+        );
+        assert_eq!(nested.get(&files[2]), None);
+        assert_eq!(nested.get(&files[3]), Some(&None));
+    }
+
+    #[test]
+    fn test_load_options_prefers_djangofmt_toml() {
+        let project = Project::new()
+            .file("pyproject.toml", "[tool.djangofmt]\nline-length = 100\n")
+            .file("djangofmt.toml", "line-length = 80\n")
+            .file("child/.djangofmt.toml", "indent-width = 2\n")
+            .file("child/djangofmt.toml", "line-length = 60\n");
+        let (result, root) = load_options(project.path()).unwrap();
+        assert_eq!(
+            result.line_length,
+            Some(LineLength::try_from(80u16).unwrap())
+        );
+        assert_eq!(root, project.path());
+
+        let (result, root) = load_options(project.join("child")).unwrap();
+        assert_eq!(
+            result,
+            PyprojectSettings {
+                indent_width: Some(IndentWidth::try_from(2u8).unwrap()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(root, project.join("child"));
+    }
+
+    #[test]
+    fn test_load_config_file_errors_on_unknown_field() {
+        let project = Project::new().file("djangofmt.toml", "[tool.djangofmt]\n");
+        let err = load_config_file(&project.join("djangofmt.toml")).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Failed to parse djangofmt.toml: ")
+        );
+    }
+
+    #[test]
+    fn test_load_config_file_merges_extended_settings() {
+        let project = Project::new()
+            .file(
+                "shared/djangofmt.toml",
+                r#"
+                line-length = 100
+                indent-width = 4
+                extend-exclude = ["vendor"]
+                [lint.per-file-ignores]
+                "emails/*" = ["invalid-attr-value"]
+                "legacy/*" = ["invalid-attr-value"]
+                "#,
+            )
+            .file(
+                "app/pyproject.toml",
+                r#"
+                [tool.djangofmt]
+                extend = "../shared/djangofmt.toml"
+                line-length = 120
+                extend-exclude = ["generated"]
+                [tool.djangofmt.lint.per-file-ignores]
+                "legacy/*" = ["category:all"]
+                "#,
+            );
+        let result = load_config_file(&project.join("app/pyproject.toml")).unwrap();
+        assert_eq!(result.line_length, LineLength::try_from(120u16).ok());
+        assert_eq!(result.indent_width, IndentWidth::try_from(4u8).ok());
+        assert_eq!(
+            result.extend_exclude,
+            Some(vec!["vendor".to_string(), "generated".to_string()])
+        );
+        let per_file_ignores = result.lint.unwrap().per_file_ignores.unwrap();
+        assert_eq!(
+            per_file_ignores.keys().collect::<Vec<_>>(),
+            ["emails/*", "legacy/*"]
+        );
+        assert_eq!(
+            per_file_ignores["legacy/*"]
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["category:all"]
+        );
+    }
+
+    #[test]
+    fn test_load_config_file_errors_on_missing_extend() {
+        let project = Project::new().file("djangofmt.toml", r#"extend = "base.toml""#);
+        let err = load_config_file(&project.join("djangofmt.toml")).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::MissingExtend(config, extended)
+                if config == project.join("djangofmt.toml") && extended.ends_with("base.toml")
+        ));
+    }
+
+    #[test]
+    fn test_load_config_file_errors_on_extend_cycle() {
+        let project = Project::new()
+            .file("djangofmt.toml", r#"extend = "a.toml""#)
+            .file("a.toml", r#"extend = "b.toml""#)
+            .file("b.toml", r#"extend = "a.toml""#);
+        let err = load_config_file(&project.join("djangofmt.toml")).unwrap_err();
+        let Error::ExtendCycle(config, cycle) = err else {
+            panic!("expected a cycle, got {err:?}");
+        };
+        assert!(config.ends_with("b.toml"));
+        let names: Vec<_> = cycle.iter().filter_map(|path| path.file_name()).collect();
+        assert_eq!(names, ["a.toml", "b.toml", "a.toml"]);
+    }
+
     #[test]
     fn test_load_options_returns_default_when_no_pyproject_toml() {
         let project = Project::new();
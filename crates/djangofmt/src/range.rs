@@ -0,0 +1,641 @@
+//! Range formatting: reformat only the nodes covering part of a document, leaving the rest of
+//! the source byte-identical.
+//!
+//! The covering nodes are the innermost sibling nodes overlapping the range. They are formatted
+//! on their own, then re-indented to the column of the line they start on. The content of
+//! [`OPAQUE_ELEMENTS`] is never narrowed down to, as it can't be formatted apart from its element.
+//! Whitespace-sensitive lines of the formatted nodes, such as the content of a `<pre>`, are never
+//! re-indented.
+
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::str::FromStr;
+
+use markup_fmt::FormatError;
+use markup_fmt::ast::{JinjaTagOrChildren, Node, NodeKind, Root};
+
+use crate::args::Profile;
+use crate::commands::format::{FormatterConfig, format_text, has_ignore_file_comment};
+use crate::template_whitespace::apply_edits;
+
+/// Elements whose content is raw text or whitespace-sensitive, formatted only as a whole.
+const OPAQUE_ELEMENTS: [&str; 4] = ["pre", "textarea", "script", "style"];
+
+/// Elements whose content is whitespace-sensitive as a whole.
+const VERBATIM_ELEMENTS: [&str; 2] = ["pre", "textarea"];
+
+/// Elements whose content is a script or a style, whitespace-sensitive only in its literals and
+/// comments.
+const CODE_ELEMENTS: [&str; 2] = ["script", "style"];
+
+/// A 1-based line and column, the column counting Unicode code points.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LineColumn {
+    pub line: NonZeroUsize,
+    pub column: NonZeroUsize,
+}
+
+impl LineColumn {
+    /// The byte offset of this position in `source`, clamped to the end of its line.
+    fn offset(self, source: &str) -> usize {
+        let mut line_start = 0;
+        for _ in 1..self.line.get() {
+            match source[line_start..].find('\n') {
+                Some(newline) => line_start += newline + 1,
+                None => return source.len(),
+            }
+        }
+        let line = &source[line_start..];
+        let line = line.find('\n').map_or(line, |newline| &line[..newline]);
+        line_start
+            + line
+                .char_indices()
+                .nth(self.column.get() - 1)
+                .map_or(line.len(), |(offset, _)| offset)
+    }
+}
+
+impl FromStr for LineColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (line, column) = s.split_once(':').unwrap_or((s, "1"));
+        let parse = |value: &str, name: &str| {
+            value
+                .trim()
+                .parse::<NonZeroUsize>()
+                .map_err(|err| format!("invalid {name} `{value}`: {err}"))
+        };
+        Ok(Self {
+            line: parse(line, "line")?,
+            column: parse(column, "column")?,
+        })
+    }
+}
+
+impl fmt::Display for LineColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A range of a document as passed to `--range`: `<start_line:col>-<end_line:col>`.
+///
+/// Lines and columns are 1-based and the end is exclusive. Columns default to 1, an omitted
+/// start to the start of the document and an omitted end to its end.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FormatRange {
+    start: Option<LineColumn>,
+    end: Option<LineColumn>,
+}
+
+impl FormatRange {
+    /// The bytes of `source` this range covers.
+    #[must_use]
+    pub fn to_byte_range(&self, source: &str) -> Range<usize> {
+        let start = self.start.map_or(0, |start| start.offset(source));
+        let end = self.end.map_or(source.len(), |end| end.offset(source));
+        start..end.max(start)
+    }
+}
+
+impl FromStr for FormatRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s.split_once('-').unwrap_or((s, ""));
+        let position = |value: &str| {
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.parse()).transpose()
+        };
+        let range = Self {
+            start: position(start)?,
+            end: position(end)?,
+        };
+        if let (Some(start), Some(end)) = (range.start, range.end)
+            && (end.line, end.column) < (start.line, start.column)
+        {
+            return Err(format!("the end {end} is before the start {start}"));
+        }
+        Ok(range)
+    }
+}
+
+/// The formatted replacement for the nodes covering a range of a document.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FormattedRange {
+    /// The replaced bytes of the document, spanning whole nodes.
+    pub range: Range<usize>,
+    pub formatted: String,
+}
+
+impl FormattedRange {
+    /// `source` with the range replaced by its formatted content.
+    #[must_use]
+    pub fn apply(&self, source: &str) -> String {
+        let mut output = source.to_string();
+        output.replace_range(self.range.clone(), &self.formatted);
+        output
+    }
+}
+
+/// Format the nodes of `source` covering the bytes in `range`.
+///
+/// `Ok(None)` when the document opts out of formatting, or when the range only covers
+/// whitespace between nodes.
+pub fn format_range(
+    source: &str,
+    range: &Range<usize>,
+    config: &FormatterConfig,
+    profile: Profile,
+) -> Result<Option<FormattedRange>, FormatError> {
+    if has_ignore_file_comment(source) {
+        return Ok(None);
+    }
+    let root = parse(source, config, profile)?;
+    let Some(covered) = covering_nodes(source, &root.children, range) else {
+        return Ok(None);
+    };
+    format_nodes(source, covered, config, profile)
+}
+
+/// Format the top-level nodes of `source` overlapping `lines`, 1-based line ranges with an
+/// exclusive end, as reported for the changed hunks of a file.
+///
+/// `Ok(None)` when the document opts out of formatting.
+pub fn format_lines(
+    source: &str,
+    lines: &[Range<usize>],
+    config: &FormatterConfig,
+    profile: Profile,
+) -> Result<Option<String>, FormatError> {
+    if has_ignore_file_comment(source) {
+        return Ok(None);
+    }
+    let root = parse(source, config, profile)?;
+    let mut edits = Vec::new();
+    for node in &root.children {
+        let Some(span) = extent(source, node) else {
+            continue;
+        };
+        let first_line = line_number(source, span.start);
+        let last_line = line_number(source, span.end);
+        if !lines
+            .iter()
+            .any(|changed| changed.start <= last_line && first_line < changed.end)
+        {
+            continue;
+        }
+        if let Some(formatted) = format_nodes(source, span, config, profile)? {
+            edits.push((formatted.range, formatted.formatted));
+        }
+    }
+    Ok(Some(apply_edits(source, edits).into_owned()))
+}
+
+/// Parse `source` the way [`format_text`] does.
+pub(crate) fn parse<'s>(
+    source: &'s str,
+    config: &FormatterConfig,
+    profile: Profile,
+) -> Result<Root<'s>, FormatError> {
+    let custom_blocks = config
+        .markup
+        .language
+        .custom_blocks
+        .clone()
+        .unwrap_or_default();
+    djangofmt_lint::parse(source, profile.into(), &custom_blocks).map_err(FormatError::Syntax)
+}
+
+/// Format the nodes spanning `span` on their own, indented like the line they start on.
+fn format_nodes(
+    source: &str,
+    span: Range<usize>,
+    config: &FormatterConfig,
+    profile: Profile,
+) -> Result<Option<FormattedRange>, FormatError> {
+    let line_start = source[..span.start].rfind('\n').map_or(0, |i| i + 1);
+    let line = &source[line_start..span.start];
+    let indent = &line[..line.len() - line.trim_start().len()];
+
+    // The nodes are printed `indent` columns in, so they get that much less room.
+    let mut config = config.clone();
+    let width = indent.chars().count();
+    config.markup.layout.print_width = config.markup.layout.print_width.saturating_sub(width);
+    config.malva.layout.print_width = config.malva.layout.print_width.saturating_sub(width);
+
+    let Some(formatted) = format_text(&source[span.clone()], &config, profile)? else {
+        return Ok(None);
+    };
+    let formatted = formatted.trim_end_matches('\n');
+    let mut verbatim = Vec::new();
+    if !indent.is_empty() {
+        let root = parse(formatted, &config, profile)?;
+        verbatim_regions(formatted, &root.children, &mut verbatim);
+    }
+    Ok(Some(FormattedRange {
+        range: span,
+        formatted: reindent(formatted, indent, &verbatim),
+    }))
+}
+
+/// The 1-based line of byte `offset`.
+fn line_number(source: &str, offset: usize) -> usize {
+    source[..offset].matches('\n').count() + 1
+}
+
+/// The bytes spanned by the innermost sibling nodes overlapping `range`.
+fn covering_nodes(source: &str, nodes: &[Node<'_>], range: &Range<usize>) -> Option<Range<usize>> {
+    let overlapping: Vec<(Range<usize>, &Node)> = nodes
+        .iter()
+        .filter_map(|node| Some((extent(source, node)?, node)))
+        .filter(|(span, _)| overlaps(span, range))
+        .collect();
+    match overlapping.as_slice() {
+        [] => None,
+        [(span, node)] if span.start <= range.start && range.end <= span.end => {
+            // Narrow down to the children when the range falls between the node's own tags.
+            children(node)
+                .into_iter()
+                .find_map(|children| {
+                    let first = children.iter().find_map(|child| extent(source, child))?;
+                    let last = children
+                        .iter()
+                        .rev()
+                        .find_map(|child| extent(source, child))?;
+                    (first.start <= range.start && range.end <= last.end)
+                        .then(|| covering_nodes(source, children, range))
+                        .flatten()
+                })
+                .or_else(|| Some(span.clone()))
+        }
+        [(first, _), .., (last, _)] => Some(first.start..last.end),
+        [(span, _)] => Some(span.clone()),
+    }
+}
+
+/// The bytes of `node` without its surrounding whitespace, `None` for whitespace-only text.
+fn extent(source: &str, node: &Node<'_>) -> Option<Range<usize>> {
+    let raw = node.raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    let start = raw.as_ptr() as usize - source.as_ptr() as usize;
+    Some(start..start + raw.len())
+}
+
+/// Whether `extent` overlaps `range`, or contains it when it is empty (a cursor).
+fn overlaps(extent: &Range<usize>, range: &Range<usize>) -> bool {
+    if range.is_empty() {
+        extent.contains(&range.start)
+    } else {
+        extent.start < range.end && range.start < extent.end
+    }
+}
+
+/// The child lists of `node`: an element's content, or each branch of a block. None for
+/// [`OPAQUE_ELEMENTS`].
+fn children<'a, 's>(node: &'a Node<'s>) -> Vec<&'a [Node<'s>]> {
+    match &node.kind {
+        NodeKind::Element(element) if is_any_of(element.tag_name, &OPAQUE_ELEMENTS) => Vec::new(),
+        NodeKind::Element(element) => vec![element.children.as_slice()],
+        NodeKind::JinjaBlock(block) => block
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                JinjaTagOrChildren::Children(children) => Some(children.as_slice()),
+                JinjaTagOrChildren::Tag(_) => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Whether `tag_name` is one of `names`, ignoring case.
+fn is_any_of(tag_name: &str, names: &[&str]) -> bool {
+    names.iter().any(|name| tag_name.eq_ignore_ascii_case(name))
+}
+
+/// Collect the bytes of `source`, parsed as `nodes`, whose lines must not be re-indented: the
+/// content of [`VERBATIM_ELEMENTS`], comments, and the literals and comments of
+/// [`CODE_ELEMENTS`].
+fn verbatim_regions(source: &str, nodes: &[Node<'_>], regions: &mut Vec<Range<usize>>) {
+    for node in nodes {
+        match &node.kind {
+            NodeKind::Comment(_) | NodeKind::JinjaComment(_) => {
+                regions.extend(extent(source, node));
+            }
+            NodeKind::Element(element) if is_any_of(element.tag_name, &VERBATIM_ELEMENTS) => {
+                if let (Some(first), Some(last)) =
+                    (element.children.first(), element.children.last())
+                {
+                    let start = offset(source, first.raw);
+                    regions.push(start..offset(source, last.raw) + last.raw.len());
+                }
+            }
+            NodeKind::Element(element) if is_any_of(element.tag_name, &CODE_ELEMENTS) => {
+                for child in &element.children {
+                    let start = offset(source, child.raw);
+                    let mut literals = Vec::new();
+                    scan_code(child.raw.as_bytes(), 0, false, &mut literals);
+                    regions.extend(
+                        literals
+                            .into_iter()
+                            .map(|literal| start + literal.start..start + literal.end),
+                    );
+                }
+            }
+            _ => {
+                for children in children(node) {
+                    verbatim_regions(source, children, regions);
+                }
+            }
+        }
+    }
+}
+
+/// The byte offset of `slice` in `source`, which it borrows from.
+fn offset(source: &str, slice: &str) -> usize {
+    slice.as_ptr() as usize - source.as_ptr() as usize
+}
+
+/// Collect the template literals and block comments of `code`, a script or a style, from byte
+/// `i` on, and return where the scan stopped: the end of `code`, or past the `}` closing a
+/// `${` substitution when `in_substitution`.
+fn scan_code(
+    code: &[u8],
+    mut i: usize,
+    in_substitution: bool,
+    literals: &mut Vec<Range<usize>>,
+) -> usize {
+    let mut depth = 0_usize;
+    while i < code.len() {
+        match (code[i], code.get(i + 1)) {
+            (b'{', _) => depth += 1,
+            (b'}', _) if in_substitution && depth == 0 => return i + 1,
+            (b'}', _) => depth = depth.saturating_sub(1),
+            (b'/', Some(b'*')) => {
+                let end = find(code, i + 2, b"*/").map_or(code.len(), |end| end + 2);
+                literals.push(i..end);
+                i = end;
+                continue;
+            }
+            (b'/', Some(b'/')) => {
+                i = find(code, i, b"\n").unwrap_or(code.len());
+                continue;
+            }
+            (quote @ (b'"' | b'\''), _) => {
+                // Skip the string, which ends at its line at the latest.
+                i += 1;
+                while i < code.len() && code[i] != quote && code[i] != b'\n' {
+                    i += if code[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            (b'`', _) => {
+                let start = i;
+                i = scan_template(code, i + 1, literals);
+                literals.push(start..i);
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    code.len()
+}
+
+/// Skip the template literal of `code` whose content starts at byte `i`, collecting the literals
+/// of its substitutions, and return the byte past its closing backtick.
+fn scan_template(code: &[u8], mut i: usize, literals: &mut Vec<Range<usize>>) -> usize {
+    while i < code.len() {
+        match (code[i], code.get(i + 1)) {
+            (b'\\', _) => i += 2,
+            (b'`', _) => return i + 1,
+            (b'$', Some(b'{')) => i = scan_code(code, i + 2, true, literals),
+            _ => i += 1,
+        }
+    }
+    code.len()
+}
+
+/// The offset of the first `needle` of `haystack` from byte `from` on.
+fn find(haystack: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|position| from + position)
+}
+
+/// Indent every line of `formatted` but the first, which stays where the nodes started, and the
+/// lines starting in one of the `verbatim` regions.
+fn reindent(formatted: &str, indent: &str, verbatim: &[Range<usize>]) -> String {
+    if indent.is_empty() {
+        return formatted.to_string();
+    }
+    let mut lines = formatted.split('\n');
+    let mut output = lines.next().unwrap_or_default().to_string();
+    let mut line_start = output.len();
+    for line in lines {
+        output.push('\n');
+        line_start += 1;
+        let in_verbatim = verbatim
+            .iter()
+            .any(|region| region.start < line_start && line_start <= region.end);
+        if !line.is_empty() && !in_verbatim {
+            output.push_str(indent);
+        }
+        output.push_str(line);
+        line_start += line.len();
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::line_width::{IndentWidth, LineLength, SelfClosing, StyleAttrLayout};
+
+    fn config() -> FormatterConfig {
+        FormatterConfig::new(
+            LineLength::default(),
+            IndentWidth::default(),
+            None,
+            SelfClosing::default(),
+            StyleAttrLayout::default(),
+            false,
+            false,
+            false,
+            None,
+        )
+    }
+
+    fn format(source: &str, range: &str) -> String {
+        let range: FormatRange = range.parse().unwrap();
+        format_range(
+            source,
+            &range.to_byte_range(source),
+            &config(),
+            Profile::Django,
+        )
+        .unwrap()
+        .map_or_else(|| source.to_string(), |formatted| formatted.apply(source))
+    }
+
+    #[test]
+    fn parse_range() {
+        let position = |line, column| LineColumn {
+            line: NonZeroUsize::new(line).unwrap(),
+            column: NonZeroUsize::new(column).unwrap(),
+        };
+        assert_eq!(
+            "2:3-4:1".parse(),
+            Ok(FormatRange {
+                start: Some(position(2, 3)),
+                end: Some(position(4, 1)),
+            })
+        );
+        assert_eq!(
+            "2-4".parse(),
+            Ok(FormatRange {
+                start: Some(position(2, 1)),
+                end: Some(position(4, 1)),
+            })
+        );
+        assert_eq!(
+            "-3".parse(),
+            Ok(FormatRange {
+                start: None,
+                end: Some(position(3, 1)),
+            })
+        );
+        assert!("0:1-2:1".parse::<FormatRange>().is_err());
+        assert!("3-2".parse::<FormatRange>().is_err());
+    }
+
+    #[test]
+    fn byte_range_counts_code_points_and_clamps() {
+        let source = "<p>é</p>\n<p>x</p>\n";
+        let range: FormatRange = "1:5-2:100".parse().unwrap();
+        assert_eq!(range.to_byte_range(source), 5..18);
+        let range: FormatRange = "5:1-".parse().unwrap();
+        assert_eq!(range.to_byte_range(source), source.len()..source.len());
+    }
+
+    #[test]
+    fn formats_only_the_covering_nodes() {
+        let source = "<div   id=\"a\"></div>\n<div>\n    <p   id=\"b\"></p>\n    <span   id=\"c\"></span>\n</div>\n";
+        // A cursor on the `<p>` line reformats just the `<p>`.
+        assert_eq!(
+            format(source, "3:6-3:6"),
+            "<div   id=\"a\"></div>\n<div>\n    <p id=\"b\"></p>\n    <span   id=\"c\"></span>\n</div>\n"
+        );
+        // A range across both children reformats both, and only them.
+        assert_eq!(
+            format(source, "3:1-4:10"),
+            "<div   id=\"a\"></div>\n<div>\n    <p id=\"b\"></p>\n    <span id=\"c\"></span>\n</div>\n"
+        );
+    }
+
+    #[test]
+    fn formats_pre_as_a_whole() {
+        let source = "<p   id=\"a\"></p>\n<pre   id=\"b\">  x   y\n     z</pre>\n";
+        assert_eq!(
+            format(source, "2:18-2:18"),
+            "<p   id=\"a\"></p>\n<pre id=\"b\">  x   y\n     z</pre>\n"
+        );
+    }
+
+    #[test]
+    fn formats_textarea_as_a_whole() {
+        let source = "<p   id=\"a\"></p>\n<textarea   name=\"b\">  x   y</textarea>\n";
+        assert_eq!(
+            format(source, "2:25-2:25"),
+            "<p   id=\"a\"></p>\n<textarea name=\"b\">  x   y</textarea>\n"
+        );
+    }
+
+    #[test]
+    fn formats_script_as_a_whole() {
+        let source =
+            "<p   id=\"a\"></p>\n<div>\n    <script>\n        let a=1\n    </script>\n</div>\n";
+        assert_eq!(
+            format(source, "4:10-4:10"),
+            "<p   id=\"a\"></p>\n<div>\n    <script>\n        let a = 1;\n    </script>\n</div>\n"
+        );
+    }
+
+    #[test]
+    fn formats_style_as_a_whole() {
+        let source = "<p   id=\"a\"></p>\n<div>\n    <style>\n        a{ color:red }\n    </style>\n</div>\n";
+        assert_eq!(
+            format(source, "4:10-4:10"),
+            "<p   id=\"a\"></p>\n<div>\n    <style>\n        a {\n            color: red;\n        }\n    </style>\n</div>\n"
+        );
+    }
+
+    #[test]
+    fn keeps_nested_pre_content_as_written() {
+        let source = "<div>\n    <section><pre>a\n  b</pre></section>\n</div>\n";
+        let formatted = format(source, "2:6-2:6");
+        assert!(formatted.contains("<pre>a\n  b</pre>"), "{formatted}");
+    }
+
+    #[test]
+    fn keeps_nested_script_literals_as_written() {
+        let source = "<div>\n    <section>\n        <script>\n            let a=`x\n  y`\n        </script>\n    </section>\n</div>\n";
+        let formatted = format(source, "2:6-2:6");
+        assert!(formatted.contains("`x\n  y`"), "{formatted}");
+    }
+
+    #[test]
+    fn skips_template_literals_and_comments_in_code() {
+        let code = "a = `x${ b ? `y` : \"}\" }`; /* c */ // `d`\n'`'";
+        let mut literals = Vec::new();
+        scan_code(code.as_bytes(), 0, false, &mut literals);
+        let literals: Vec<&str> = literals.into_iter().map(|range| &code[range]).collect();
+        assert_eq!(literals, ["`y`", "`x${ b ? `y` : \"}\" }`", "/* c */"]);
+    }
+
+    #[test]
+    fn formats_top_level_nodes_on_changed_lines() {
+        let source =
+            "<p   id=\"a\"></p>\n<div>\n    <p   id=\"b\"></p>\n</div>\n<p   id=\"c\"></p>\n";
+        assert_eq!(
+            format_lines(
+                source,
+                std::slice::from_ref(&(3..4)),
+                &config(),
+                Profile::Django
+            )
+            .unwrap(),
+            Some(
+                "<p   id=\"a\"></p>\n<div>\n    <p id=\"b\"></p>\n</div>\n<p   id=\"c\"></p>\n"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            format_lines(source, &[1..2, 5..6], &config(), Profile::Django).unwrap(),
+            Some(
+                "<p id=\"a\"></p>\n<div>\n    <p   id=\"b\"></p>\n</div>\n<p id=\"c\"></p>\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn reindents_to_the_start_line() {
+        assert_eq!(
+            reindent("<ul>\n    <li></li>\n\n</ul>", "  ", &[]),
+            "<ul>\n      <li></li>\n\n  </ul>"
+        );
+    }
+
+    #[test]
+    fn whitespace_only_range_is_left_alone() {
+        let source = "<p   id=\"a\"></p>\n\n<p   id=\"b\"></p>\n";
+        assert_eq!(format(source, "2:1-2:1"), source);
+    }
+}
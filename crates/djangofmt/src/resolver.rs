@@ -1,5 +1,7 @@
+use std::collections::BTreeSet;
+use std::fmt;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex, PoisonError};
 
 use ignore::WalkBuilder;
 use ignore::overrides::OverrideBuilder;
@@ -9,6 +11,7 @@ use tracing::{debug, warn};
 use crate::args::FileSelectionArgs;
 use crate::config::resolve_bool_arg;
 use crate::error::Error;
+use crate::git::ChangedFiles;
 use crate::pyproject::PyprojectSettings;
 
 /// Default file patterns to include when discovering files.
@@ -45,6 +48,8 @@ pub struct ResolvedDiscoveryConfig {
     pub include: Vec<String>,
     pub respect_gitignore: bool,
     pub force_exclude: bool,
+    /// When set, only these files are kept, after the exclusions.
+    pub changed: Option<ChangedFiles>,
 }
 
 impl ResolvedDiscoveryConfig {
@@ -82,8 +87,21 @@ impl ResolvedDiscoveryConfig {
             force_exclude: resolve_bool_arg(cli.force_exclude, cli.no_force_exclude)
                 .or(pyproject.force_exclude)
                 .unwrap_or(false),
+            changed: None,
         }
     }
+
+    /// The files under `root` selected by this config, whether or not they changed.
+    pub fn selected_files(&self, root: &Path) -> Result<Vec<PathBuf>, Error> {
+        let config = Self {
+            exclude: self.exclude.clone(),
+            include: self.include.clone(),
+            respect_gitignore: self.respect_gitignore,
+            force_exclude: self.force_exclude,
+            changed: None,
+        };
+        resolve_files(&[root.to_path_buf()], &config)
+    }
 }
 
 /// Build the include `Types` matcher from the resolved config.
@@ -100,13 +118,13 @@ fn build_types(config: &ResolvedDiscoveryConfig) -> Result<ignore::types::Types,
         .map_err(|e| Error::Resolve(format!("Failed to build file types: {e}")))
 }
 
-/// Build the exclude `Override` matcher from the resolved config.
-fn build_overrides(
+/// Build an `Override` matcher excluding `patterns`.
+fn build_exclude_overrides<'a>(
     root: &Path,
-    config: &ResolvedDiscoveryConfig,
+    patterns: impl IntoIterator<Item = &'a String>,
 ) -> Result<ignore::overrides::Override, Error> {
     let mut override_builder = OverrideBuilder::new(root);
-    for pattern in &config.exclude {
+    for pattern in patterns {
         override_builder
             .add(&format!("!{pattern}"))
             .map_err(|e| Error::Resolve(format!("Invalid exclude pattern '{pattern}': {e}")))?;
@@ -116,6 +134,38 @@ fn build_overrides(
         .map_err(|e| Error::Resolve(format!("Failed to build exclude overrides: {e}")))
 }
 
+/// The exclude patterns of the resolved config, telling the [`DEFAULT_EXCLUDE`] ones apart.
+struct ExcludeMatcher {
+    /// The exclude patterns that are [`DEFAULT_EXCLUDE`] ones.
+    defaults: ignore::overrides::Override,
+    /// The other exclude patterns.
+    user: ignore::overrides::Override,
+}
+
+impl ExcludeMatcher {
+    fn new(root: &Path, config: &ResolvedDiscoveryConfig) -> Result<Self, Error> {
+        let (defaults, user): (Vec<&String>, Vec<&String>) = config
+            .exclude
+            .iter()
+            .partition(|pattern| DEFAULT_EXCLUDE.contains(&pattern.as_str()));
+        Ok(Self {
+            defaults: build_exclude_overrides(root, defaults)?,
+            user: build_exclude_overrides(root, user)?,
+        })
+    }
+
+    /// The exclude pattern `path` matches, if any.
+    fn matched(&self, path: &Path, is_dir: bool) -> Option<Exclusion> {
+        if self.defaults.matched(path, is_dir).is_ignore() {
+            Some(Exclusion::DefaultExclude)
+        } else if self.user.matched(path, is_dir).is_ignore() {
+            Some(Exclusion::UserPattern)
+        } else {
+            None
+        }
+    }
+}
+
 /// Return `true` if the given filename should be force-excluded based on the resolved
 /// configuration. Returns `false` if `force_exclude` is disabled.
 pub fn is_force_excluded(filename: &Path, config: &ResolvedDiscoveryConfig) -> Result<bool, Error> {
@@ -123,8 +173,8 @@ pub fn is_force_excluded(filename: &Path, config: &ResolvedDiscoveryConfig) -> R
         return Ok(false);
     }
     let cwd = crate::fs::get_cwd();
-    let overrides = build_overrides(cwd, config)?;
-    Ok(overrides.matched(filename, false).is_ignore())
+    let excludes = ExcludeMatcher::new(cwd, config)?;
+    Ok(excludes.matched(filename, false).is_some())
 }
 
 /// Resolve a list of CLI paths (files and/or directories) into a flat,
@@ -133,8 +183,20 @@ pub fn resolve_files(
     paths: &[PathBuf],
     config: &ResolvedDiscoveryConfig,
 ) -> Result<Vec<PathBuf>, Error> {
+    let (files, _) = resolve_files_explained(paths, config, false)?;
+    Ok(files)
+}
+
+/// Like [`resolve_files`], also listing the candidates left out when `explain` is set, with the
+/// reason for each, sorted. The content of excluded directories is not listed.
+pub fn resolve_files_explained(
+    paths: &[PathBuf],
+    config: &ResolvedDiscoveryConfig,
+    explain: bool,
+) -> Result<(Vec<PathBuf>, Exclusions), Error> {
     let mut files: Vec<PathBuf> = Vec::with_capacity(paths.len());
     let mut dirs: Vec<&Path> = vec![];
+    let mut exclusions = Vec::new();
 
     // Process the provided paths, collecting directories for later recursive processing.
     // Explicit files paths are canonicalized.
@@ -155,24 +217,29 @@ pub fn resolve_files(
         }
     }
 
+    // The first directory, else the first file's, anchors the exclude patterns.
+    let Some(excludes) = dirs
+        .first()
+        .copied()
+        .or_else(|| files.first().and_then(|f| f.parent()))
+        .map(|root| ExcludeMatcher::new(root, config))
+        .transpose()?
+    else {
+        return Ok((files, exclusions));
+    };
+
     // When force_exclude is enabled, apply exclude patterns to explicitly-passed files too.
-    if config.force_exclude
-        && !files.is_empty()
-        && let Some(root) = dirs
-            .first()
-            .copied()
-            .or_else(|| files.first().and_then(|f| f.parent()))
-    {
-        let overrides = build_overrides(root, config)?;
+    if config.force_exclude && !files.is_empty() {
         let len_before = files.len();
         files.retain(|file| {
-            let dominated = overrides.matched(file, false);
-            if dominated.is_ignore() {
-                debug!("Force-excluded: {}", file.display());
-                false
-            } else {
-                true
+            let Some(exclusion) = excludes.matched(file, false) else {
+                return true;
+            };
+            debug!("Force-excluded: {}", file.display());
+            if explain {
+                exclusions.push((file.clone(), exclusion));
             }
+            false
         });
         if files.len() < len_before {
             debug!(
@@ -182,39 +249,165 @@ pub fn resolve_files(
         }
     }
 
-    // Walk all directories with a single parallel WalkBuilder.
-    if let Some((first, rest)) = dirs.split_first() {
-        let types = build_types(config)?;
-        let overrides = build_overrides(first, config)?;
+    let (walked, walk_exclusions) = walk_dirs(&dirs, config, excludes, explain)?;
+    files.extend(walked);
+    exclusions.extend(walk_exclusions);
 
-        let mut builder = WalkBuilder::new(first);
-        for dir in rest {
-            builder.add(dir);
-        }
-        builder.current_dir(crate::fs::get_cwd());
-        builder
-            .standard_filters(config.respect_gitignore)
-            .hidden(false)
-            .follow_links(true)
-            .types(types)
-            .overrides(overrides)
-            .threads(
-                std::thread::available_parallelism()
-                    .map_or(1, std::num::NonZeroUsize::get)
-                    .min(12),
-            );
-
-        let state = WalkFilesState::new();
-        let mut visitor_builder = FileVisitorBuilder::new(&state);
-        builder.build_parallel().visit(&mut visitor_builder);
-        files.extend(state.finish()?);
+    if let Some(changed) = &config.changed {
+        files.retain(|file| {
+            let is_changed = changed.contains(file);
+            if explain && !is_changed {
+                exclusions.push((file.clone(), Exclusion::Unchanged));
+            }
+            is_changed
+        });
     }
 
     files.sort();
     files.dedup();
+    exclusions.sort();
+    exclusions.dedup();
 
     debug!("Resolved {} files to process", files.len());
-    Ok(files)
+    Ok((files, exclusions))
+}
+
+/// Walks all `dirs` with a single parallel [`WalkBuilder`], listing the entries it leaves out when
+/// `explain` is set.
+fn walk_dirs(
+    dirs: &[&Path],
+    config: &ResolvedDiscoveryConfig,
+    excludes: ExcludeMatcher,
+    explain: bool,
+) -> Result<(Vec<PathBuf>, Exclusions), Error> {
+    let Some((first, rest)) = dirs.split_first() else {
+        return Ok((Vec::new(), Vec::new()));
+    };
+    let types = build_types(config)?;
+    let record = explain.then(|| Arc::new(Mutex::new(WalkRecord::default())));
+
+    let mut builder = WalkBuilder::new(first);
+    for dir in rest {
+        builder.add(dir);
+    }
+    builder.current_dir(crate::fs::get_cwd());
+    // The exclude and include patterns are applied by the filter rather than as overrides
+    // and types of the walker, so that it can tell why it leaves an entry out. The gitignore
+    // rules are applied first, which selects the same files.
+    let filter_record = record.clone();
+    builder
+        .standard_filters(config.respect_gitignore)
+        .hidden(false)
+        .follow_links(true)
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+            let exclusion = excludes.matched(entry.path(), is_dir).or_else(|| {
+                (!is_dir && !types.matched(entry.path(), false).is_whitelist())
+                    .then_some(Exclusion::NotIncluded)
+            });
+            if let Some(record) = &filter_record {
+                record.lock().unwrap_or_else(PoisonError::into_inner).add(
+                    entry.path(),
+                    is_dir,
+                    exclusion,
+                );
+            }
+            exclusion.is_none()
+        })
+        .threads(
+            std::thread::available_parallelism()
+                .map_or(1, std::num::NonZeroUsize::get)
+                .min(12),
+        );
+
+    let state = WalkFilesState::new();
+    let mut visitor_builder = FileVisitorBuilder::new(&state);
+    builder.build_parallel().visit(&mut visitor_builder);
+    let files = state.finish()?;
+
+    let exclusions = record.map_or_else(Vec::new, |record| {
+        let record = std::mem::take(&mut *record.lock().unwrap_or_else(PoisonError::into_inner));
+        record.finish(dirs, config.respect_gitignore)
+    });
+    Ok((files, exclusions))
+}
+
+/// The candidates left out by [`resolve_files_explained`], with the reason for each.
+pub type Exclusions = Vec<(PathBuf, Exclusion)>;
+
+/// Why [`resolve_files`] left out a candidate file or directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Exclusion {
+    /// It matches one of the [`DEFAULT_EXCLUDE`] patterns.
+    DefaultExclude,
+    /// It matches an `exclude` or `extend-exclude` pattern of the user.
+    UserPattern,
+    /// It is ignored by a `.gitignore` (or `.ignore`) file.
+    Gitignore,
+    /// It is a file matching none of the `include` patterns.
+    NotIncluded,
+    /// It is a file unchanged since the `--changed-since` revision.
+    Unchanged,
+}
+
+impl fmt::Display for Exclusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::DefaultExclude => "matches a default exclude pattern",
+            Self::UserPattern => "matches an exclude pattern",
+            Self::Gitignore => "ignored by gitignore",
+            Self::NotIncluded => "matches no include pattern",
+            Self::Unchanged => "unchanged since the `--changed-since` revision",
+        })
+    }
+}
+
+/// The entries the filter of the walk was given, when explaining the exclusions.
+#[derive(Default)]
+struct WalkRecord {
+    /// Every entry given to the filter.
+    seen: BTreeSet<PathBuf>,
+    /// The directories the walk descends into.
+    descended: Vec<PathBuf>,
+    /// The canonical paths of the entries the filter left out.
+    excluded: Exclusions,
+}
+
+impl WalkRecord {
+    fn add(&mut self, path: &Path, is_dir: bool, exclusion: Option<Exclusion>) {
+        self.seen.insert(path.to_path_buf());
+        match exclusion {
+            Some(exclusion) => self.excluded.push((canonical(path), exclusion)),
+            None if is_dir => self.descended.push(path.to_path_buf()),
+            None => {}
+        }
+    }
+
+    /// The entries left out by the walk below `roots`. Those of the descended directories never
+    /// given to the filter were left out by the gitignore rules.
+    fn finish(mut self, roots: &[&Path], respect_gitignore: bool) -> Exclusions {
+        if !respect_gitignore {
+            return self.excluded;
+        }
+        let directories = roots.iter().copied().map(Path::to_path_buf);
+        for dir in directories.chain(std::mem::take(&mut self.descended)) {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.filter_map(Result::ok) {
+                let path = dir.join(entry.file_name());
+                if !self.seen.contains(&path) {
+                    self.excluded.push((canonical(&path), Exclusion::Gitignore));
+                }
+            }
+        }
+        self.excluded
+    }
+}
+
+/// The canonical form of `path`, or `path` itself when it can't be resolved.
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
 }
 
 /// Shared state across all parallel walk visitors.
@@ -709,6 +902,43 @@ mod tests {
         assert_eq!(files.len(), 2);
     }
 
+    #[test]
+    fn test_resolve_files_explained() {
+        let dir = tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        std::process::Command::new("git")
+            .args(["init"])
+            .current_dir(&root)
+            .output()
+            .unwrap();
+        fs::write(root.join(".gitignore"), "ignored/\n").unwrap();
+        create_file(&root, "included.html");
+        create_file(&root, "notes.txt");
+        create_file(&root, "ignored/a.html");
+        create_file(&root, "node_modules/b.html");
+        create_file(&root, "vendor/c.html");
+
+        let pyproject = PyprojectSettings {
+            extend_exclude: Some(vec!["vendor".to_string()]),
+            ..Default::default()
+        };
+        let config = ResolvedDiscoveryConfig::new(&default_cli(), &pyproject);
+        let (files, excluded) =
+            resolve_files_explained(std::slice::from_ref(&root), &config, true).unwrap();
+        assert_eq!(files, vec![root.join("included.html")]);
+        assert_eq!(
+            excluded,
+            vec![
+                (root.join(".git"), Exclusion::DefaultExclude),
+                (root.join(".gitignore"), Exclusion::NotIncluded),
+                (root.join("ignored"), Exclusion::Gitignore),
+                (root.join("node_modules"), Exclusion::DefaultExclude),
+                (root.join("notes.txt"), Exclusion::NotIncluded),
+                (root.join("vendor"), Exclusion::UserPattern),
+            ]
+        );
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_resolve_files_follows_symlinks() {
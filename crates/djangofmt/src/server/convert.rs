@@ -0,0 +1,157 @@
+//! Conversions from djangofmt's byte offsets and diagnostics to their LSP counterparts.
+//!
+//! Positions count UTF-16 code units, the only encoding every client supports.
+
+use djangofmt_lint::{LintDiagnostic, clamp_offset};
+use lsp_types::{
+    CodeDescription, Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range, Url,
+};
+
+use crate::emitters::RULES_URL;
+use crate::error::ParseError;
+
+const SOURCE: &str = "djangofmt";
+
+/// The line starts of a document, to turn byte offsets into [`Position`]s.
+pub struct LineIndex<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    /// The position of byte `offset`, clamped to the document and to a char boundary.
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = self.text.floor_char_boundary(offset);
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let character = self.text[self.line_starts[line]..offset]
+            .encode_utf16()
+            .count();
+        Position::new(clamp_offset(line), clamp_offset(character))
+    }
+
+    /// The byte offset of `position`, clamped to the end of its line and of the document.
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(&line_start) = self.line_starts.get(position.line as usize) else {
+            return self.text.len();
+        };
+        let line = &self.text[line_start..];
+        let line = line.find('\n').map_or(line, |newline| &line[..newline]);
+        let mut units = 0;
+        for (offset, char) in line.char_indices() {
+            if units >= position.character as usize {
+                return line_start + offset;
+            }
+            units += char.len_utf16();
+        }
+        line_start + line.len()
+    }
+
+    pub fn range(&self, start: usize, end: usize) -> Range {
+        Range::new(self.position(start), self.position(end))
+    }
+
+    /// The range spanning the whole document.
+    pub fn full_range(&self) -> Range {
+        self.range(0, self.text.len())
+    }
+}
+
+/// `diagnostic` as reported to the client, linking to the rule documentation.
+pub fn lint_diagnostic(index: &LineIndex, diagnostic: &LintDiagnostic) -> Diagnostic {
+    let start = diagnostic.span.offset() as usize;
+    let message = diagnostic.help.as_ref().map_or_else(
+        || diagnostic.message.to_string(),
+        |help| format!("{}\n{help}", diagnostic.message),
+    );
+    Diagnostic {
+        range: index.range(start, start + diagnostic.span.len() as usize),
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(NumberOrString::String(diagnostic.code.to_string())),
+        code_description: Url::parse(&format!("{RULES_URL}/{}/", diagnostic.code))
+            .ok()
+            .map(|href| CodeDescription { href }),
+        source: Some(SOURCE.to_string()),
+        message,
+        ..Diagnostic::default()
+    }
+}
+
+/// The error of a document that doesn't parse, which stands in for its lint diagnostics.
+pub fn parse_error_diagnostic(index: &LineIndex, error: &ParseError) -> Diagnostic {
+    let span = error.span();
+    let start = span.offset() as usize;
+    let message = error.hint().map_or_else(
+        || error.message.clone(),
+        |hint| format!("{}\n{hint}", error.message),
+    );
+    Diagnostic {
+        range: index.range(start, start + span.len() as usize),
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some(SOURCE.to_string()),
+        message,
+        ..Diagnostic::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use djangofmt_lint::span;
+
+    use super::*;
+
+    #[test]
+    fn positions_count_utf16_code_units() {
+        let text = "<p>\n<p>é😀x</p>\n";
+        let index = LineIndex::new(text);
+        assert_eq!(index.position(0), Position::new(0, 0));
+        assert_eq!(index.position(4), Position::new(1, 0));
+        let x = text.find('x').unwrap();
+        assert_eq!(index.position(x), Position::new(1, 6));
+        // Inside the emoji: clamped back to its start.
+        assert_eq!(index.position(x - 1), Position::new(1, 4));
+        assert_eq!(index.full_range().end, Position::new(2, 0));
+        assert_eq!(index.position(text.len() + 10), Position::new(2, 0));
+        for offset in [0, 4, x - 4, x] {
+            assert_eq!(index.offset(index.position(offset)), offset);
+        }
+        assert_eq!(index.offset(Position::new(0, 50)), 3);
+        assert_eq!(index.offset(Position::new(7, 0)), text.len());
+    }
+
+    #[test]
+    fn lint_diagnostic_links_to_the_rule() {
+        let text = "<form method=\"put\"></form>\n";
+        let diagnostic = LintDiagnostic {
+            code: "invalid-attr-value",
+            message: "Invalid value 'put' for attribute 'method'.".into(),
+            span: span(14, 3),
+            help: Some("Use one of: get, post, dialog".into()),
+            fix: None,
+            fix_title: None,
+        };
+        let diagnostic = lint_diagnostic(&LineIndex::new(text), &diagnostic);
+        assert_eq!(
+            diagnostic.range,
+            Range::new(Position::new(0, 14), Position::new(0, 17))
+        );
+        assert_eq!(
+            diagnostic.code,
+            Some(NumberOrString::String("invalid-attr-value".into()))
+        );
+        assert_eq!(
+            diagnostic.code_description.unwrap().href.as_str(),
+            "https://unknownplatypus.github.io/djangofmt/docs/rules/invalid-attr-value/"
+        );
+        assert_eq!(
+            diagnostic.message,
+            "Invalid value 'put' for attribute 'method'.\nUse one of: get, post, dialog"
+        );
+    }
+}
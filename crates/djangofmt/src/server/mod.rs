@@ -0,0 +1,485 @@
+//! `djangofmt server`: a language server formatting templates, publishing their lint diagnostics
+//! and offering their fixes as quick fixes.
+//!
+//! Each document gets the settings of the innermost workspace folder containing it, resolved
+//! like the CLI resolves those of its working directory, unless a configuration file nearer to
+//! the document applies. Settings are reloaded when a configuration file or `.editorconfig`
+//! changes, if the client can watch files for us.
+
+mod convert;
+mod workspace;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use djangofmt_lint::Applicability;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidChangeWatchedFiles, DidChangeWorkspaceFolders, DidCloseTextDocument,
+    DidOpenTextDocument, Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{
+    CodeActionRequest, Formatting, RangeFormatting, RegisterCapability, Request as _,
+};
+use lsp_types::{
+    ClientCapabilities, CodeAction, CodeActionKind, CodeActionOptions, CodeActionOrCommand,
+    CodeActionParams, CodeActionProviderCapability, CodeActionResponse,
+    DidChangeWatchedFilesRegistrationOptions, DocumentFormattingParams,
+    DocumentRangeFormattingParams, FileSystemWatcher, GlobPattern, InitializeParams,
+    InitializeResult, OneOf, PublishDiagnosticsParams, Registration, RegistrationParams,
+    ServerCapabilities, ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit,
+    Url, WorkspaceEdit, WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities,
+};
+use markup_fmt::FormatError;
+use tracing::{debug, error, info, warn};
+
+use crate::ExitStatus;
+use crate::error::{Error, ParseError, Result};
+use convert::{LineIndex, lint_diagnostic, parse_error_diagnostic};
+use workspace::Workspace;
+
+/// Config files whose changes reload the settings.
+const WATCHED_FILES: [&str; 4] = [
+    "**/.djangofmt.toml",
+    "**/djangofmt.toml",
+    "**/pyproject.toml",
+    "**/.editorconfig",
+];
+
+/// Run the language server over stdin and stdout until the client asks it to exit.
+pub fn run() -> Result<ExitStatus> {
+    let (connection, io_threads) = Connection::stdio();
+    let (id, params) = connection.initialize_start().map_err(server_error)?;
+    let params: InitializeParams = serde_json::from_value(params).map_err(server_error)?;
+    let result = InitializeResult {
+        capabilities: capabilities(),
+        server_info: Some(ServerInfo {
+            name: "djangofmt".to_string(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        }),
+    };
+    connection
+        .initialize_finish(id, serde_json::to_value(result).map_err(server_error)?)
+        .map_err(server_error)?;
+    info!("Language server started");
+
+    let mut server = Server::new(connection, &params);
+    server.register_watchers(&params.capabilities)?;
+    server.main_loop()?;
+    // The writer thread only stops once every sender is gone.
+    drop(server);
+    io_threads.join()?;
+    Ok(ExitStatus::Success)
+}
+
+fn server_error(err: impl Display) -> Error {
+    Error::Server(err.to_string())
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        document_range_formatting_provider: Some(OneOf::Left(true)),
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+            ..CodeActionOptions::default()
+        })),
+        workspace: Some(WorkspaceServerCapabilities {
+            workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                supported: Some(true),
+                change_notifications: Some(OneOf::Left(true)),
+            }),
+            file_operations: None,
+        }),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// An open document, as last synced by the client.
+struct Document {
+    text: String,
+    version: i32,
+}
+
+struct Server {
+    connection: Connection,
+    /// One per workspace folder, plus one per directory of documents opened outside of them.
+    workspaces: Vec<Workspace>,
+    documents: BTreeMap<Url, Document>,
+}
+
+impl Server {
+    fn new(connection: Connection, params: &InitializeParams) -> Self {
+        let mut roots: Vec<PathBuf> = params
+            .workspace_folders
+            .iter()
+            .flatten()
+            .filter_map(|folder| folder.uri.to_file_path().ok())
+            .collect();
+        if roots.is_empty() {
+            roots.push(crate::fs::get_cwd().to_path_buf());
+        }
+        Self {
+            connection,
+            workspaces: roots.into_iter().map(load_workspace).collect(),
+            documents: BTreeMap::new(),
+        }
+    }
+
+    fn send(&self, message: impl Into<Message>) -> Result<()> {
+        self.connection
+            .sender
+            .send(message.into())
+            .map_err(server_error)
+    }
+
+    fn notify<N: lsp_types::notification::Notification>(&self, params: N::Params) -> Result<()> {
+        self.send(Notification::new(N::METHOD.to_string(), params))
+    }
+
+    /// Ask the client to tell us about config file changes, when it supports registering for it.
+    fn register_watchers(&self, capabilities: &ClientCapabilities) -> Result<()> {
+        let dynamic_registration = capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.did_change_watched_files)
+            .and_then(|watched_files| watched_files.dynamic_registration)
+            .unwrap_or(false);
+        if !dynamic_registration {
+            return Ok(());
+        }
+        let options = DidChangeWatchedFilesRegistrationOptions {
+            watchers: WATCHED_FILES
+                .iter()
+                .map(|glob| FileSystemWatcher {
+                    glob_pattern: GlobPattern::String((*glob).to_string()),
+                    kind: None,
+                })
+                .collect(),
+        };
+        let params = RegistrationParams {
+            registrations: vec![Registration {
+                id: "djangofmt-config-files".to_string(),
+                method: DidChangeWatchedFiles::METHOD.to_string(),
+                register_options: Some(serde_json::to_value(options).map_err(server_error)?),
+            }],
+        };
+        self.send(Request::new(
+            RequestId::from("register-config-files".to_string()),
+            RegisterCapability::METHOD.to_string(),
+            params,
+        ))
+    }
+
+    fn main_loop(&mut self) -> Result<()> {
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self
+                        .connection
+                        .handle_shutdown(&request)
+                        .map_err(server_error)?
+                    {
+                        return Ok(());
+                    }
+                    self.handle_request(request)?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                // Only the watcher registration is sent to the client, its answer carries nothing.
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result<()> {
+        let response = match request.method.as_str() {
+            Formatting::METHOD => self.respond::<Formatting>(request, Self::format_document),
+            RangeFormatting::METHOD => {
+                self.respond::<RangeFormatting>(request, Self::format_document_range)
+            }
+            CodeActionRequest::METHOD => {
+                self.respond::<CodeActionRequest>(request, Self::code_actions)
+            }
+            _ => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request: {}", request.method),
+            ),
+        };
+        self.send(response)
+    }
+
+    fn respond<R: lsp_types::request::Request>(
+        &mut self,
+        request: Request,
+        handler: fn(&mut Self, R::Params) -> R::Result,
+    ) -> Response {
+        let id = request.id.clone();
+        match request.extract::<R::Params>(R::METHOD) {
+            Ok((id, params)) => Response::new_ok(id, handler(self, params)),
+            Err(err) => Response::new_err(id, ErrorCode::InvalidParams as i32, err.to_string()),
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Some(params) = extract::<DidOpenTextDocument>(notification) else {
+                    return Ok(());
+                };
+                let document = params.text_document;
+                self.documents.insert(
+                    document.uri.clone(),
+                    Document {
+                        text: document.text,
+                        version: document.version,
+                    },
+                );
+                self.publish_diagnostics(&document.uri)
+            }
+            DidChangeTextDocument::METHOD => {
+                let Some(mut params) = extract::<DidChangeTextDocument>(notification) else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                // With full sync, the last change holds the whole document.
+                if let Some(change) = params.content_changes.pop()
+                    && let Some(document) = self.documents.get_mut(&uri)
+                {
+                    document.text = change.text;
+                    document.version = params.text_document.version;
+                }
+                self.publish_diagnostics(&uri)
+            }
+            DidCloseTextDocument::METHOD => {
+                let Some(params) = extract::<DidCloseTextDocument>(notification) else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                self.notify::<PublishDiagnostics>(PublishDiagnosticsParams::new(
+                    uri,
+                    Vec::new(),
+                    None,
+                ))
+            }
+            DidChangeWorkspaceFolders::METHOD => {
+                let Some(params) = extract::<DidChangeWorkspaceFolders>(notification) else {
+                    return Ok(());
+                };
+                let removed: Vec<PathBuf> = params
+                    .event
+                    .removed
+                    .iter()
+                    .filter_map(|folder| folder.uri.to_file_path().ok())
+                    .collect();
+                self.workspaces
+                    .retain(|workspace| !removed.iter().any(|root| root == workspace.root()));
+                for folder in params.event.added {
+                    if let Ok(root) = folder.uri.to_file_path() {
+                        self.workspaces.push(load_workspace(root));
+                    }
+                }
+                self.publish_all_diagnostics()
+            }
+            DidChangeWatchedFiles::METHOD => {
+                debug!("Config files changed, reloading the settings");
+                self.workspaces = std::mem::take(&mut self.workspaces)
+                    .iter()
+                    .map(|workspace| load_workspace(workspace.root().to_path_buf()))
+                    .collect();
+                self.publish_all_diagnostics()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Index of the workspace of the file at `path`: the innermost folder containing it, else
+    /// one resolved from the directory of the file.
+    fn workspace_index(&mut self, path: &Path) -> usize {
+        if let Some((index, _)) = self
+            .workspaces
+            .iter()
+            .enumerate()
+            .filter(|(_, workspace)| workspace.contains(path))
+            .max_by_key(|(_, workspace)| workspace.root().components().count())
+        {
+            return index;
+        }
+        let root = path
+            .parent()
+            .map_or_else(|| crate::fs::get_cwd().to_path_buf(), Path::to_path_buf);
+        self.workspaces.push(load_workspace(root));
+        self.workspaces.len() - 1
+    }
+
+    fn publish_diagnostics(&mut self, uri: &Url) -> Result<()> {
+        let Ok(path) = uri.to_file_path() else {
+            return Ok(());
+        };
+        let workspace = self.workspace_index(&path);
+        let Some(document) = self.documents.get(uri) else {
+            return Ok(());
+        };
+        let index = LineIndex::new(&document.text);
+        let diagnostics = match self.workspaces[workspace].lint(&path, &document.text) {
+            Ok(diagnostics) => diagnostics
+                .iter()
+                .map(|diagnostic| lint_diagnostic(&index, diagnostic))
+                .collect(),
+            Err(err) => {
+                let err =
+                    ParseError::new(Some(path), document.text.clone(), &FormatError::Syntax(err));
+                vec![parse_error_diagnostic(&index, &err)]
+            }
+        };
+        self.notify::<PublishDiagnostics>(PublishDiagnosticsParams::new(
+            uri.clone(),
+            diagnostics,
+            Some(document.version),
+        ))
+    }
+
+    fn publish_all_diagnostics(&mut self) -> Result<()> {
+        let uris: Vec<Url> = self.documents.keys().cloned().collect();
+        for uri in &uris {
+            self.publish_diagnostics(uri)?;
+        }
+        Ok(())
+    }
+
+    /// Replace the whole document with its formatted content, if that changes anything.
+    fn format_document(&mut self, params: DocumentFormattingParams) -> Option<Vec<TextEdit>> {
+        let uri = params.text_document.uri;
+        let path = uri.to_file_path().ok()?;
+        let workspace = self.workspace_index(&path);
+        let document = self.documents.get(&uri)?;
+        match self.workspaces[workspace].format(&path, &document.text) {
+            Ok(Some(formatted)) if formatted != document.text => Some(vec![TextEdit::new(
+                LineIndex::new(&document.text).full_range(),
+                formatted,
+            )]),
+            Ok(_) => None,
+            // Already published as a diagnostic.
+            Err(err) => {
+                debug!("Failed to format {}: {err:?}", path.display());
+                None
+            }
+        }
+    }
+
+    /// Format the nodes covering the requested range, leaving the rest of the document as is.
+    fn format_document_range(
+        &mut self,
+        params: DocumentRangeFormattingParams,
+    ) -> Option<Vec<TextEdit>> {
+        let uri = params.text_document.uri;
+        let path = uri.to_file_path().ok()?;
+        let workspace = self.workspace_index(&path);
+        let document = self.documents.get(&uri)?;
+        let index = LineIndex::new(&document.text);
+        let range = index.offset(params.range.start)..index.offset(params.range.end);
+        match self.workspaces[workspace].format_range(&path, &document.text, &range) {
+            Ok(Some(formatted))
+                if formatted.formatted != document.text[formatted.range.clone()] =>
+            {
+                Some(vec![TextEdit::new(
+                    index.range(formatted.range.start, formatted.range.end),
+                    formatted.formatted,
+                )])
+            }
+            Ok(_) => None,
+            // Already published as a diagnostic.
+            Err(err) => {
+                debug!("Failed to format {}: {err:?}", path.display());
+                None
+            }
+        }
+    }
+
+    /// A quick fix for each fixable diagnostic within the requested range.
+    fn code_actions(&mut self, params: CodeActionParams) -> Option<CodeActionResponse> {
+        let uri = params.text_document.uri;
+        let path = uri.to_file_path().ok()?;
+        let workspace = self.workspace_index(&path);
+        let document = self.documents.get(&uri)?;
+        let diagnostics = self.workspaces[workspace]
+            .lint(&path, &document.text)
+            .ok()?;
+        let index = LineIndex::new(&document.text);
+        let requested = params.range;
+
+        let mut actions = Vec::new();
+        for diagnostic in &diagnostics {
+            let Some(fix) = &diagnostic.fix else {
+                continue;
+            };
+            let lsp_diagnostic = lint_diagnostic(&index, diagnostic);
+            let range = lsp_diagnostic.range;
+            if range.end < requested.start || requested.end < range.start {
+                continue;
+            }
+            let edits = fix
+                .edits()
+                .iter()
+                .map(|edit| {
+                    TextEdit::new(
+                        index.range(edit.start(), edit.end()),
+                        edit.content().unwrap_or_default().to_string(),
+                    )
+                })
+                .collect();
+            let is_safe = fix.applies(Applicability::Safe);
+            let mut title = format!(
+                "{}: {}",
+                diagnostic.code,
+                diagnostic.fix_title.unwrap_or("Apply fix")
+            );
+            if !is_safe {
+                title.push_str(" (unsafe)");
+            }
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title,
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![lsp_diagnostic]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(HashMap::from([(uri.clone(), edits)])),
+                    ..WorkspaceEdit::default()
+                }),
+                is_preferred: Some(is_safe),
+                ..CodeAction::default()
+            }));
+        }
+        Some(actions)
+    }
+}
+
+/// The params of `notification`, logging a warning when they don't deserialize.
+fn extract<N: lsp_types::notification::Notification>(
+    notification: Notification,
+) -> Option<N::Params> {
+    match notification.extract(N::METHOD) {
+        Ok(params) => Some(params),
+        Err(err) => {
+            warn!("Ignoring invalid {} notification: {err}", N::METHOD);
+            None
+        }
+    }
+}
+
+/// The settings of the folder `root`, or the defaults when its configuration is invalid.
+fn load_workspace(root: PathBuf) -> Workspace {
+    match Workspace::load(root.clone()) {
+        Ok(workspace) => workspace,
+        Err(err) => {
+            error!(
+                "Failed to load the settings of {}, using the defaults: {err}",
+                root.display()
+            );
+            Workspace::with_defaults(root)
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! The settings of a workspace folder, resolved from it as the CLI resolves them from its working
+//! directory: the nearest `pyproject.toml` and `.editorconfig`, then the per-file overrides.
+//! Documents nearer to another configuration file follow it instead, as files do in the CLI.
+
+use std::borrow::Cow;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use djangofmt_lint::{LintDiagnostic, Settings, lint_source};
+use editorconfig_parser::EditorConfig;
+use markup_fmt::{FormatError, SyntaxError};
+use tracing::warn;
+
+use crate::args::{FormatCommand, Profile, RuleSelectionArgs};
+use crate::commands::format::{
+    FormatterConfig, file_config, format_text, merge_custom_blocks, shared_config,
+};
+use crate::config::{resolve_profile, resolve_rule_selection};
+use crate::editorconfig::load_editorconfig;
+use crate::error::Result;
+use crate::per_file_ignores::PerFileIgnores;
+use crate::pyproject::{NestedProjects, PyprojectSettings, load_options};
+use crate::range::{FormattedRange, format_range};
+
+pub struct Workspace {
+    /// The folder the settings were resolved from.
+    root: PathBuf,
+    /// The canonical directory of the folder's configuration file, or the folder itself.
+    project_root: PathBuf,
+    /// No CLI flags reach the server, so options come from the config files alone.
+    format_args: FormatCommand,
+    editorconfig: Option<EditorConfig>,
+    /// The settings of the documents following the folder's configuration file.
+    project: Project,
+    /// The settings of the configuration files nearer to some documents, found as they are used.
+    nested: NestedProjects<Project>,
+}
+
+/// The settings derived from one configuration file, rebuilt with the workspace when a config
+/// file changes.
+struct Project {
+    pyproject: PyprojectSettings,
+    /// Built once when `.editorconfig` can't vary per file.
+    config: Option<FormatterConfig>,
+    settings: Settings,
+    per_file_ignores: Option<PerFileIgnores>,
+    custom_blocks: Vec<String>,
+}
+
+impl Project {
+    /// The settings of `pyproject`, found in the canonical directory `root`.
+    fn load(
+        pyproject: PyprojectSettings,
+        root: &Path,
+        format_args: &FormatCommand,
+        editorconfig: Option<&EditorConfig>,
+    ) -> Result<Self> {
+        let per_file_ignores = pyproject
+            .lint
+            .as_ref()
+            .and_then(|lint| lint.per_file_ignores.as_ref())
+            .map(|patterns| PerFileIgnores::new(patterns, root))
+            .transpose()?;
+        Ok(Self::new(
+            pyproject,
+            per_file_ignores,
+            format_args,
+            editorconfig,
+        ))
+    }
+
+    fn new(
+        pyproject: PyprojectSettings,
+        per_file_ignores: Option<PerFileIgnores>,
+        format_args: &FormatCommand,
+        editorconfig: Option<&EditorConfig>,
+    ) -> Self {
+        let (settings, warnings) =
+            resolve_rule_selection(&RuleSelectionArgs::default(), pyproject.lint.as_ref())
+                .into_settings();
+        for warning in &warnings {
+            warn!("{warning}");
+        }
+        let custom_blocks =
+            merge_custom_blocks(None, pyproject.custom_blocks.clone()).unwrap_or_default();
+        Self {
+            config: shared_config(format_args, &pyproject, editorconfig),
+            pyproject,
+            settings,
+            per_file_ignores,
+            custom_blocks,
+        }
+    }
+
+    fn profile_for(&self, path: &Path) -> Profile {
+        resolve_profile(None, self.pyproject.profile, Some(path))
+    }
+}
+
+impl Workspace {
+    /// Resolve the settings of the folder `root`.
+    pub fn load(root: PathBuf) -> Result<Self> {
+        let (pyproject, project_root) = load_options(&root)?;
+        let format_args = FormatCommand::default();
+        let editorconfig = load_editorconfig(&root);
+        let project_root = project_root.canonicalize().unwrap_or(project_root);
+        let project = Project::load(
+            pyproject,
+            &project_root,
+            &format_args,
+            editorconfig.as_ref(),
+        )?;
+        Ok(Self {
+            root,
+            project_root,
+            format_args,
+            editorconfig,
+            project,
+            nested: NestedProjects::default(),
+        })
+    }
+
+    /// The default settings, for a folder whose `pyproject.toml` is invalid.
+    pub fn with_defaults(root: PathBuf) -> Self {
+        let format_args = FormatCommand::default();
+        let editorconfig = load_editorconfig(&root);
+        let project = Project::new(
+            PyprojectSettings::default(),
+            None,
+            &format_args,
+            editorconfig.as_ref(),
+        );
+        Self {
+            project_root: root.canonicalize().unwrap_or_else(|_| root.clone()),
+            root,
+            format_args,
+            editorconfig,
+            project,
+            nested: NestedProjects::default(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Whether the file at `path` lives in this folder.
+    pub fn contains(&self, path: &Path) -> bool {
+        path.starts_with(&self.root)
+    }
+
+    /// Format `source`, the content of the file at `path`.
+    /// `Ok(None)` when the file opts out of formatting with an ignore comment.
+    pub fn format(
+        &mut self,
+        path: &Path,
+        source: &str,
+    ) -> std::result::Result<Option<String>, FormatError> {
+        let path = self.discover(path);
+        let project = self.project(&path);
+        format_text(
+            source,
+            &self.config_for(project, &path),
+            project.profile_for(&path),
+        )
+    }
+
+    /// Format the nodes of `source` covering the bytes in `range`.
+    pub fn format_range(
+        &mut self,
+        path: &Path,
+        source: &str,
+        range: &Range<usize>,
+    ) -> std::result::Result<Option<FormattedRange>, FormatError> {
+        let path = self.discover(path);
+        let project = self.project(&path);
+        format_range(
+            source,
+            range,
+            &self.config_for(project, &path),
+            project.profile_for(&path),
+        )
+    }
+
+    /// Lint `source`, the content of the file at `path`.
+    pub fn lint(
+        &mut self,
+        path: &Path,
+        source: &str,
+    ) -> std::result::Result<Vec<LintDiagnostic>, SyntaxError> {
+        let canonical = self.discover(path);
+        let project = self.project(&canonical);
+        // Reuse the project settings unless per-file-ignores narrow them for this path.
+        let file_settings = project.per_file_ignores.as_ref().map(|pfi| Settings {
+            rules: pfi.rules_for(&canonical, &project.settings.rules),
+            ..project.settings.clone()
+        });
+        let settings = file_settings.as_ref().unwrap_or(&project.settings);
+        lint_source(
+            source,
+            project.profile_for(path).into(),
+            &project.custom_blocks,
+            settings,
+            Some(path),
+        )
+    }
+
+    /// Find the nearest configuration file of the document at `path`, unless its directory was
+    /// looked up already, and return its canonical path.
+    fn discover(&mut self, path: &Path) -> PathBuf {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let (format_args, editorconfig) = (&self.format_args, self.editorconfig.as_ref());
+        if let Err(err) =
+            self.nested
+                .discover_file(&path, &self.project_root, &mut |pyproject, root| {
+                    Project::load(pyproject, root, format_args, editorconfig)
+                })
+        {
+            warn!(
+                "Failed to load the settings of {}, using those of {}: {err}",
+                path.display(),
+                self.root.display()
+            );
+        }
+        path
+    }
+
+    /// The settings of the canonical `path`, once discovered.
+    fn project(&self, path: &Path) -> &Project {
+        self.nested.get(path).unwrap_or(&self.project)
+    }
+
+    fn config_for<'a>(&self, project: &'a Project, path: &Path) -> Cow<'a, FormatterConfig> {
+        file_config(
+            &self.format_args,
+            &project.pyproject,
+            self.editorconfig.as_ref(),
+            project.config.as_ref(),
+            path,
+        )
+    }
+}
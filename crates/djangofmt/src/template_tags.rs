@@ -0,0 +1,175 @@
+//! Wrapping of the template tags exceeding the line length, one keyword argument per line:
+//!
+//! ```jinja
+//! {% with
+//!     title=page.title,
+//!     subtitle=page.subtitle|default(site.tagline) %}
+//! ```
+
+use std::borrow::Cow;
+
+use markup_fmt::ast::Root;
+
+use crate::args::Profile;
+use crate::template_whitespace::{Delimited, TemplateSyntax, apply_edits, walk_template_syntax};
+
+/// Wrap the tags of `source`, parsed as `root`, whose line is wider than `print_width`.
+///
+/// Tags without keyword arguments, already spanning several lines or following a
+/// `djangofmt:ignore` comment are left untouched.
+#[must_use]
+pub fn wrap_template_tags<'s>(
+    source: &'s str,
+    root: &Root<'s>,
+    print_width: usize,
+    indent_width: usize,
+) -> Cow<'s, str> {
+    let mut edits = Vec::new();
+    walk_template_syntax(source, root, &mut |syntax| {
+        let TemplateSyntax::Node(range) = syntax else {
+            return;
+        };
+        let raw = &source[range.clone()];
+        if !raw.starts_with("{%") || raw.contains('\n') {
+            return;
+        }
+        let line_start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[range.end..]
+            .find('\n')
+            .map_or(source.len(), |i| range.end + i);
+        let line = &source[line_start..line_end];
+        if line.chars().count() <= print_width {
+            return;
+        }
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let hanging_indent = format!("{indent}{}", " ".repeat(indent_width));
+        if let Some(wrapped) = wrap_tag(raw, &hanging_indent) {
+            edits.push((range, wrapped));
+        }
+    });
+    apply_edits(source, edits)
+}
+
+/// Put each keyword argument of `tag` on its own line, `None` when it has none.
+///
+/// The tag name and the arguments before the first keyword argument stay on the first line,
+/// the arguments following a keyword argument (`only`, `as name`) on their own line.
+fn wrap_tag(tag: &str, hanging_indent: &str) -> Option<String> {
+    // Only Jinja tags may span several lines, so only they are wrapped.
+    let Delimited {
+        opening,
+        body,
+        closing,
+    } = Delimited::split(tag, Profile::Jinja)?;
+    let arguments = split_arguments(body);
+    let first_keyword = arguments
+        .iter()
+        .position(|argument| is_keyword_argument(argument))
+        .filter(|&position| position > 0)?;
+
+    let mut output = format!("{opening} {}", arguments[..first_keyword].join(" "));
+    let mut after_keyword = false;
+    for argument in &arguments[first_keyword..] {
+        let is_keyword = is_keyword_argument(argument);
+        if is_keyword || after_keyword {
+            output.push('\n');
+            output.push_str(hanging_indent);
+        } else {
+            output.push(' ');
+        }
+        output.push_str(argument);
+        after_keyword = is_keyword;
+    }
+    output.push(' ');
+    output.push_str(closing);
+    Some(output)
+}
+
+/// Split `body` on whitespace outside of string literals and brackets.
+fn split_arguments(body: &str) -> Vec<&str> {
+    let mut arguments = Vec::new();
+    let mut start = None;
+    let mut quote = None;
+    let mut depth = 0_usize;
+    let mut escaped = false;
+    for (offset, c) in body.char_indices() {
+        if let Some(open) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == open {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(start) = start.take() {
+                    arguments.push(&body[start..offset]);
+                }
+                continue;
+            }
+            '"' | '\'' => quote = Some(c),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        start.get_or_insert(offset);
+    }
+    if let Some(start) = start {
+        arguments.push(&body[start..]);
+    }
+    arguments
+}
+
+/// Whether `argument` reads `name=value`.
+fn is_keyword_argument(argument: &str) -> bool {
+    argument.split_once('=').is_some_and(|(name, value)| {
+        !name.is_empty()
+            && !value.starts_with('=')
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(
+        "{% with title=page.title, subtitle=page.subtitle %}",
+        "{% with\n    title=page.title,\n    subtitle=page.subtitle %}"
+    )]
+    #[case(
+        "{%- trans user=user.username, count=items|length -%}",
+        "{%- trans\n    user=user.username,\n    count=items|length -%}"
+    )]
+    #[case(
+        r#"{% trans note="a b=c", count=items|length %}"#,
+        "{% trans\n    note=\"a b=c\",\n    count=items|length %}"
+    )]
+    fn wraps_keyword_arguments(#[case] tag: &str, #[case] expected: &str) {
+        assert_eq!(wrap_tag(tag, "    ").as_deref(), Some(expected));
+    }
+
+    #[rstest]
+    #[case("{% if a == b and c %}")]
+    #[case("{% set x = y %}")]
+    #[case("{% with %}")]
+    fn leaves_tags_without_keyword_arguments(#[case] tag: &str) {
+        assert_eq!(wrap_tag(tag, "    "), None);
+    }
+
+    #[test]
+    fn splits_arguments_outside_strings_and_brackets() {
+        assert_eq!(
+            split_arguments(r#"include "a b.html" with x=f(1, 2) y='c d'"#),
+            vec!["include", r#""a b.html""#, "with", "x=f(1, 2)", "y='c d'"]
+        );
+    }
+}
@@ -0,0 +1,406 @@
+//! Normalization of the whitespace inside template variables, tags and comments.
+//!
+//! `{{user.name|default:"x"}}` becomes `{{ user.name|default:"x" }}` and `{%if  a==b%}` becomes
+//! `{% if a == b %}`. String literals, Jinja whitespace-control markers (`{%-`, `-%}`) and
+//! anything spanning several lines are kept as written.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use markup_fmt::ast::{Attribute, JinjaBlock, JinjaTag, JinjaTagOrChildren, Node, NodeKind, Root};
+
+use crate::args::Profile;
+use crate::commands::format::DJANGOFMT_IGNORE_COMMENT_DIRECTIVE;
+
+/// Operators written with a single space on each side, longest first.
+const COMPARISON_OPERATORS: [&str; 6] = ["==", "!=", "<=", ">=", "<", ">"];
+
+/// Normalize the whitespace of the template syntax of `source`, parsed as `root` with `profile`.
+///
+/// Nodes following a `djangofmt:ignore` comment are left untouched.
+#[must_use]
+pub fn normalize_template_whitespace<'s>(
+    source: &'s str,
+    root: &Root<'s>,
+    profile: Profile,
+) -> Cow<'s, str> {
+    let mut edits = Vec::new();
+    walk_template_syntax(source, root, &mut |syntax| match syntax {
+        TemplateSyntax::Node(range) | TemplateSyntax::Attribute(range) => {
+            let raw = &source[range.clone()];
+            if let Some(normalized) = normalize_delimited(raw, profile)
+                && normalized != raw
+            {
+                edits.push((range, normalized));
+            }
+        }
+        TemplateSyntax::AttributeValue(range) => {
+            let value = &source[range.clone()];
+            if let Cow::Owned(normalized) = normalize_segments(value, profile) {
+                edits.push((range, normalized));
+            }
+        }
+        TemplateSyntax::Attributes(_) => {}
+    });
+    apply_edits(source, edits)
+}
+
+/// `source` with the bytes of each range of `edits` replaced, the ranges being in source order
+/// and disjoint.
+pub(crate) fn apply_edits(
+    source: &str,
+    edits: Vec<(Range<usize>, impl AsRef<str>)>,
+) -> Cow<'_, str> {
+    if edits.is_empty() {
+        return Cow::Borrowed(source);
+    }
+    let mut output = source.to_string();
+    // Replace from the end so the offsets of the earlier edits stay valid.
+    for (range, replacement) in edits.into_iter().rev() {
+        output.replace_range(range, replacement.as_ref());
+    }
+    Cow::Owned(output)
+}
+
+/// What [`walk_template_syntax`] found in the source, template syntax as bytes of it.
+pub(crate) enum TemplateSyntax<'a> {
+    /// A `{{ }}`, `{% %}` or `{# #}` node, delimiters included.
+    Node(Range<usize>),
+    /// A `{% %}` among the attributes of an element, delimiters included.
+    Attribute(Range<usize>),
+    /// The value of an attribute, which may contain template syntax.
+    AttributeValue(Range<usize>),
+    /// The attributes of an element, visited before the template syntax among them.
+    Attributes(&'a [Attribute<'a>]),
+}
+
+/// Call `visit` with the template syntax of `source`, parsed as `root`, in source order.
+///
+/// Nodes following a `djangofmt:ignore` comment are skipped.
+pub(crate) fn walk_template_syntax<'a>(
+    source: &str,
+    root: &'a Root<'a>,
+    visit: &mut dyn FnMut(TemplateSyntax<'a>),
+) {
+    walk_nodes(source, &root.children, visit);
+}
+
+fn walk_nodes<'a>(source: &str, nodes: &'a [Node<'a>], visit: &mut dyn FnMut(TemplateSyntax<'a>)) {
+    let mut ignore_next = false;
+    for node in nodes {
+        if matches!(&node.kind, NodeKind::Text(text) if text.raw.trim().is_empty()) {
+            continue;
+        }
+        if std::mem::take(&mut ignore_next) {
+            continue;
+        }
+        match &node.kind {
+            NodeKind::Comment(_) => {
+                ignore_next = node.raw.contains(DJANGOFMT_IGNORE_COMMENT_DIRECTIVE);
+            }
+            NodeKind::JinjaComment(_) => {
+                ignore_next = node.raw.contains(DJANGOFMT_IGNORE_COMMENT_DIRECTIVE);
+                visit(TemplateSyntax::Node(slice_range(source, node.raw.trim())));
+            }
+            NodeKind::JinjaInterpolation(_) => {
+                visit(TemplateSyntax::Node(slice_range(source, node.raw.trim())));
+            }
+            NodeKind::JinjaTag(tag) => visit(TemplateSyntax::Node(tag_range(tag))),
+            NodeKind::JinjaBlock(block) => {
+                walk_block(source, block, TemplateSyntax::Node, walk_nodes, visit);
+            }
+            NodeKind::Element(element) => {
+                visit(TemplateSyntax::Attributes(&element.attrs));
+                walk_attributes(source, &element.attrs, visit);
+                walk_nodes(source, &element.children, visit);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn walk_attributes<'a>(
+    source: &str,
+    attrs: &'a [Attribute<'a>],
+    visit: &mut dyn FnMut(TemplateSyntax<'a>),
+) {
+    for attr in attrs {
+        match attr {
+            Attribute::Native(native) => {
+                if let Some((value, offset)) = native.value {
+                    visit(TemplateSyntax::AttributeValue(offset..offset + value.len()));
+                }
+            }
+            Attribute::JinjaTag(tag) => visit(TemplateSyntax::Attribute(tag_range(tag))),
+            Attribute::JinjaBlock(block) => {
+                walk_block(
+                    source,
+                    block,
+                    TemplateSyntax::Attribute,
+                    walk_attributes,
+                    visit,
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+fn walk_block<'a, T>(
+    source: &str,
+    block: &'a JinjaBlock<'a, T>,
+    tag: fn(Range<usize>) -> TemplateSyntax<'a>,
+    walk_children: fn(&str, &'a [T], &mut dyn FnMut(TemplateSyntax<'a>)),
+    visit: &mut dyn FnMut(TemplateSyntax<'a>),
+) {
+    for item in &block.body {
+        match item {
+            JinjaTagOrChildren::Tag(open_or_close) => visit(tag(tag_range(open_or_close))),
+            JinjaTagOrChildren::Children(children) => walk_children(source, children, visit),
+        }
+    }
+}
+
+/// The bytes of `tag`, delimiters included.
+const fn tag_range(tag: &JinjaTag<'_>) -> Range<usize> {
+    tag.start - "{%".len()..tag.start + tag.content.len() + "%}".len()
+}
+
+/// The bytes `slice` spans in `source`.
+fn slice_range(source: &str, slice: &str) -> Range<usize> {
+    let start = slice.as_ptr() as usize - source.as_ptr() as usize;
+    start..start + slice.len()
+}
+
+/// Normalize every `{{ }}`, `{% %}` and `{# #}` within `text`, like an attribute value.
+fn normalize_segments(text: &str, profile: Profile) -> Cow<'_, str> {
+    let mut output = String::new();
+    let mut rest = text;
+    let mut changed = false;
+    while let Some(start) = find_opening(rest) {
+        let Some(len) = delimited_len(&rest[start..]) else {
+            break;
+        };
+        let raw = &rest[start..start + len];
+        output.push_str(&rest[..start]);
+        match normalize_delimited(raw, profile) {
+            Some(normalized) => {
+                changed |= normalized != raw;
+                output.push_str(&normalized);
+            }
+            None => output.push_str(raw),
+        }
+        rest = &rest[start + len..];
+    }
+    if changed {
+        output.push_str(rest);
+        Cow::Owned(output)
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// The offset of the next `{{`, `{%` or `{#` in `text`.
+fn find_opening(text: &str) -> Option<usize> {
+    text.match_indices('{')
+        .map(|(offset, _)| offset)
+        .find(|&offset| matches!(text.as_bytes().get(offset + 1), Some(b'{' | b'%' | b'#')))
+}
+
+/// The length of the template syntax `text` starts with, up to its closing delimiter.
+fn delimited_len(text: &str) -> Option<usize> {
+    let closing = match &text[..2] {
+        "{{" => "}}",
+        "{%" => "%}",
+        _ => return text.find("#}").map(|end| end + "#}".len()),
+    };
+    let mut offset = 2;
+    while offset < text.len() {
+        let rest = &text[offset..];
+        if rest.starts_with(closing) {
+            return Some(offset + closing.len());
+        }
+        offset += match rest.as_bytes()[0] {
+            quote @ (b'"' | b'\'') => string_literal_len(rest, quote),
+            _ => rest.chars().next().map_or(1, char::len_utf8),
+        };
+    }
+    None
+}
+
+/// The length of the string literal `text` starts with, quotes included.
+fn string_literal_len(text: &str, quote: u8) -> usize {
+    let bytes = text.as_bytes();
+    let mut offset = 1;
+    while offset < bytes.len() {
+        match bytes[offset] {
+            b'\\' => offset += 2,
+            byte if byte == quote => return offset + 1,
+            _ => offset += 1,
+        }
+    }
+    text.len()
+}
+
+/// Normalize a single `{{ }}`, `{% %}` or `{# #}`, `None` when it is left as written.
+fn normalize_delimited(raw: &str, profile: Profile) -> Option<String> {
+    if raw.contains('\n') {
+        return None;
+    }
+    let Delimited {
+        opening,
+        body,
+        closing,
+    } = Delimited::split(raw, profile)?;
+    // Without whitespace control, a tag can't start or end with a marker: leave it as written
+    // rather than detach the marker from its delimiter.
+    if !profile.supports_whitespace_control()
+        && opening == "{%"
+        && (body.starts_with(['-', '+']) || body.ends_with(['-', '+']))
+    {
+        return None;
+    }
+    let body = if opening.starts_with("{#") {
+        Cow::Borrowed(body)
+    } else {
+        Cow::Owned(normalize_expression(body))
+    };
+    Some(format!("{opening} {body} {closing}"))
+}
+
+/// A `{{ }}`, `{% %}` or `{# #}` split into its delimiters and what they enclose.
+pub(crate) struct Delimited<'a> {
+    /// The opening delimiter, with its whitespace-control marker under Jinja: `{%-`.
+    pub(crate) opening: &'a str,
+    /// The trimmed content.
+    pub(crate) body: &'a str,
+    /// The closing delimiter, with its whitespace-control marker under Jinja: `-%}`.
+    pub(crate) closing: &'a str,
+}
+
+impl<'a> Delimited<'a> {
+    /// Split `raw`, written for `profile`, `None` when it encloses nothing.
+    pub(crate) fn split(raw: &'a str, profile: Profile) -> Option<Self> {
+        let inner = raw.get(2..raw.len().checked_sub(2)?)?;
+        let is_marker = |byte: Option<&u8>| {
+            profile.supports_whitespace_control() && matches!(byte, Some(b'-' | b'+'))
+        };
+        let lead = usize::from(is_marker(inner.as_bytes().first()));
+        let inner = &inner[lead..];
+        let trail = usize::from(is_marker(inner.as_bytes().last()));
+        let body = inner[..inner.len() - trail].trim();
+        (!body.is_empty()).then(|| Self {
+            opening: &raw[..2 + lead],
+            body,
+            closing: &raw[raw.len() - 2 - trail..],
+        })
+    }
+}
+
+/// Collapse whitespace runs, remove the spaces around filter pipes and put a single space around
+/// comparison operators, leaving string literals as written.
+fn normalize_expression(body: &str) -> String {
+    let mut output = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(c) = rest.chars().next() {
+        if let quote @ (b'"' | b'\'') = rest.as_bytes()[0] {
+            let len = string_literal_len(rest, quote);
+            output.push_str(&rest[..len]);
+            rest = &rest[len..];
+        } else if c.is_whitespace() {
+            output.push(' ');
+            rest = rest.trim_start();
+        } else if c == '|' {
+            output.truncate(output.trim_end().len());
+            output.push('|');
+            rest = rest[1..].trim_start();
+        } else if let Some(operator) = COMPARISON_OPERATORS
+            .iter()
+            .find(|operator| rest.starts_with(**operator))
+        {
+            output.truncate(output.trim_end().len());
+            if !output.is_empty() {
+                output.push(' ');
+            }
+            output.push_str(operator);
+            output.push(' ');
+            rest = rest[operator.len()..].trim_start();
+        } else {
+            output.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("{{x}}", "{{ x }}")]
+    #[case("{{  user.name|default:\"x\"  }}", "{{ user.name|default:\"x\" }}")]
+    #[case(
+        "{{ value | lower | truncatechars:10 }}",
+        "{{ value|lower|truncatechars:10 }}"
+    )]
+    #[case("{%if  a==b%}", "{% if a == b %}")]
+    #[case("{% if a!=b and c>=1 or d<2 %}", "{% if a != b and c >= 1 or d < 2 %}")]
+    #[case("{% url 'a  ==  b' pk=1 %}", "{% url 'a  ==  b' pk=1 %}")]
+    #[case("{#comment#}", "{# comment #}")]
+    #[case("{#   spaced   comment #}", "{# spaced   comment #}")]
+    fn normalizes_delimited(#[case] raw: &str, #[case] expected: &str) {
+        assert_eq!(
+            normalize_delimited(raw, Profile::Django).as_deref(),
+            Some(expected)
+        );
+    }
+
+    #[rstest]
+    #[case("{%- if x -%}", "{%- if x -%}")]
+    #[case("{%-if x+%}", "{%- if x +%}")]
+    #[case("{{-x-}}", "{{- x -}}")]
+    fn keeps_jinja_whitespace_control(#[case] raw: &str, #[case] expected: &str) {
+        assert_eq!(
+            normalize_delimited(raw, Profile::Jinja).as_deref(),
+            Some(expected)
+        );
+    }
+
+    #[rstest]
+    #[case("{{-1}}", "{{ -1 }}")]
+    #[case("{{x|add:-1}}", "{{ x|add:-1 }}")]
+    #[case("{{ -x- }}", "{{ -x- }}")]
+    fn django_has_no_whitespace_control(#[case] raw: &str, #[case] expected: &str) {
+        assert_eq!(
+            normalize_delimited(raw, Profile::Django).as_deref(),
+            Some(expected)
+        );
+    }
+
+    #[rstest]
+    #[case("{{}}")]
+    #[case("{%  %}")]
+    #[case("{% if a\n   and b %}")]
+    #[case("{%- if x -%}")]
+    fn leaves_delimited_as_written(#[case] raw: &str) {
+        assert_eq!(normalize_delimited(raw, Profile::Django), None);
+    }
+
+    #[test]
+    fn normalizes_segments_of_attribute_values() {
+        assert_eq!(
+            normalize_segments(
+                "btn {{kind}} {%if active%}on{%endif%} {{ '}}' }}",
+                Profile::Django
+            ),
+            "btn {{ kind }} {% if active %}on{% endif %} {{ '}}' }}"
+        );
+        assert!(matches!(
+            normalize_segments("btn {{ kind }}", Profile::Django),
+            Cow::Borrowed(_)
+        ));
+    }
+}
@@ -1,4 +1,5 @@
 use insta_cmd::{assert_cmd_snapshot, get_cargo_bin};
+use std::fmt::Write as _;
 use std::process::Command;
 
 #[path = "../src/test_support.rs"]
@@ -143,6 +144,90 @@ fn format_file_parse_error_exits_2() {
     "###);
 }
 
+#[test]
+fn format_check_reports_unformatted_file() {
+    let original = "<div   class=\"foo\"  >\n</div>\n";
+    let project = Project::new().file("test.html", original);
+    assert_cmd_snapshot_tmpdir!(cli().arg("--check").arg(project.join("test.html")), @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Would reformat: [TMP]/test.html
+    1 file would be reformatted !
+    "#);
+    assert_eq!(project.read("test.html"), original);
+}
+
+#[test]
+fn format_check_formatted_file() {
+    let project = Project::new().file("test.html", "<div class=\"foo\"></div>\n");
+    assert_cmd_snapshot!(cli().arg("--check").arg(project.join("test.html")), @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    1 file left unchanged !
+    "#);
+}
+
+#[test]
+fn format_diff_prints_unified_diff() {
+    let original = "<div   class=\"foo\"  >\n</div>\n";
+    let project = Project::new()
+        .file("test.html", original)
+        .file("clean.html", "<p></p>\n");
+    assert_cmd_snapshot_tmpdir!(cli().arg("--diff").arg(project.path()), @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    --- [TMP]/test.html
+    +++ [TMP]/test.html
+    @@ -1,2 +1 @@
+    -<div   class="foo"  >
+    -</div>
+    +<div class="foo"></div>
+
+    ----- stderr -----
+    1 file would be reformatted, 1 file left unchanged !
+    "#);
+    assert_eq!(project.read("test.html"), original);
+}
+
+#[test]
+fn show_files_lists_the_selected_files() {
+    let unformatted = "<div   class=\"foo\"  >\n</div>\n";
+    let project = Project::new()
+        .file("templates/a.html", unformatted)
+        .file("templates/b.jinja", unformatted)
+        .file("templates/notes.txt", "")
+        .file("node_modules/lib/c.html", unformatted);
+    assert_cmd_snapshot!(cli().current_dir(project.path()).args(["--show-files", "."]), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    templates/a.html
+    templates/b.jinja
+
+    ----- stderr -----
+    ");
+    assert_eq!(project.read("templates/a.html"), unformatted);
+    assert_cmd_snapshot!(
+        cli()
+            .current_dir(project.path())
+            .args(["check", "--show-files", "--extend-exclude", "*.jinja", "."]),
+        @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    templates/a.html
+
+    ----- stderr -----
+    ");
+}
+
 // ── Format from stdin ────────────────────────────────────────────────
 
 #[test]
@@ -209,6 +294,27 @@ fn format_stdin_pyproject_profile_beats_extension() {
     "#);
 }
 
+#[test]
+fn format_stdin_filename_follows_its_nearest_config() {
+    // Like a file on disk, the stdin filename follows the config of its app over the root's.
+    let project = Project::new()
+        .file("pyproject.toml", "[tool.djangofmt]\nprofile = \"django\"\n")
+        .file("apps/blog/djangofmt.toml", "profile = \"jinja\"\n");
+    assert_cmd_snapshot!(
+        cli()
+            .current_dir(project.path())
+            .args(["--stdin-filename", "apps/blog/post.html"])
+            .pass_stdin("{{- foo -}}\n"),
+        @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    {{- foo -}}
+
+    ----- stderr -----
+    "#);
+}
+
 #[test]
 fn format_stdin_with_filename_infers_jinja_profile() {
     // Jinja whitespace-control modifiers (`{{- ... -}}`) are preserved under the jinja
@@ -305,6 +411,78 @@ fn format_stdin_force_exclude_parrots_input() {
     "#);
 }
 
+#[test]
+fn format_stdin_check() {
+    assert_cmd_snapshot!(
+        cli()
+            .args(["--check", "--stdin-filename", "foo.html"])
+            .pass_stdin("<div   class=\"foo\"  ></div>\n"),
+        @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Would reformat: foo.html
+    "#);
+}
+
+#[test]
+fn format_stdin_diff() {
+    assert_cmd_snapshot!(
+        cli()
+            .args(["--diff", "--stdin-filename", "foo.html"])
+            .pass_stdin("<div   class=\"foo\"  ></div>\n"),
+        @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    --- foo.html
+    +++ foo.html
+    @@ -1 +1 @@
+    -<div   class="foo"  ></div>
+    +<div class="foo"></div>
+
+    ----- stderr -----
+    "#);
+}
+
+#[test]
+fn format_stdin_range_formats_only_the_covering_nodes() {
+    // Only the `<p>` under line 3 is reformatted; its badly spaced neighbours stay untouched.
+    let source = "<div   id=\"a\"></div>\n<div>\n    <p   id=\"b\"></p>\n    <span   id=\"c\"></span>\n</div>\n";
+    assert_cmd_snapshot!(
+        cli()
+            .args(["--range", "3:5-3:10", "--stdin-filename", "foo.html"])
+            .pass_stdin(source),
+        @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    <div   id="a"></div>
+    <div>
+        <p id="b"></p>
+        <span   id="c"></span>
+    </div>
+
+    ----- stderr -----
+    "#);
+}
+
+#[test]
+fn format_range_requires_stdin() {
+    assert_cmd_snapshot!(
+        cli().args(["--range", "1-2", "foo.html"]),
+        @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    `--range` can only be used when formatting standard input
+    ");
+}
+
 #[test]
 fn format_stdin_extra_file_warns_but_uses_stdin() {
     // When --stdin-filename is set, any other file path is ignored with a warning.
@@ -451,6 +629,202 @@ fn check_concise_output_format() {
     "###);
 }
 
+#[test]
+fn check_json_lines_output_format() {
+    let project = Project::new()
+        .file("test.html", "<form method=\"put\"></form>\n")
+        .file("unparsable.html", "<div>\n");
+    assert_cmd_snapshot!(
+        cli().current_dir(project.path()).args(["check", "--output-format", "json-lines", "."]),
+        @r###"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+    {"kind":"diagnostic","filename":"test.html","code":"invalid-attr-value","message":"Invalid value 'put' for attribute 'method'.","help":"Use one of: get, post, dialog","location":{"line":1,"column":15},"end_location":{"line":1,"column":18},"span":{"start":14,"end":17},"fix":null}
+    {"kind":"parse-error","filename":"unparsable.html","message":"expected close tag for opening tag <div>","help":"If a `</div>` does exist, it must live in the same block as the opening tag: https://unknownplatypus.github.io/djangofmt/docs/known-limitations/#conditional-openclose-tags","location":{"line":1,"column":2}}
+
+    ----- stderr -----
+    Couldn't check 1 files!
+    Found 1 errors.
+    "###);
+}
+
+#[test]
+fn check_json_output_format_without_diagnostics() {
+    let project = Project::new().file("test.html", "<form method=\"post\"></form>\n");
+    assert_cmd_snapshot!(
+        cli().current_dir(project.path()).args(["check", "--output-format", "json", "."]),
+        @r###"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    []
+
+    ----- stderr -----
+    All checks passed!
+    "###);
+}
+
+#[test]
+fn check_statistics() {
+    let project = Project::new().file(
+        "test.html",
+        "<form method=\"put\"></form>\n<form method=\"patch\"></form>\n{% blocktranslate %}Hi{% endblocktranslate %}\n",
+    );
+    assert_cmd_snapshot!(
+        cli().current_dir(project.path()).args(["check", "--statistics", "."]),
+        @r###"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    Count  Fixable  Rule                      Category
+        2        0  invalid-attr-value        correctness
+        1        1  untrimmed-blocktranslate  correctness
+
+    ----- stderr -----
+    Found 3 errors. [*] 1 fixable with the --fix option.
+    "###);
+
+    let output = cli()
+        .current_dir(project.path())
+        .args(["check", "--statistics", "--output-format", "json", "."])
+        .output()
+        .unwrap();
+    let statistics: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        statistics[0],
+        serde_json::json!({
+            "code": "invalid-attr-value",
+            "count": 2,
+            "fixable": 0,
+            "category": "correctness",
+        })
+    );
+
+    let output = cli()
+        .current_dir(project.path())
+        .args([
+            "check",
+            "--statistics",
+            "--output-format",
+            "json-lines",
+            ".",
+        ])
+        .output()
+        .unwrap();
+    let lines: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1]["code"], "untrimmed-blocktranslate");
+}
+
+#[test]
+fn check_sarif_output_format() {
+    let project = Project::new().file("test.html", "<form method=\"put\"></form>\n");
+    let output = cli()
+        .current_dir(project.path())
+        .args(["check", "--output-format", "sarif", "."])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(log["version"], "2.1.0");
+    let result = &log["runs"][0]["results"][0];
+    assert_eq!(result["ruleId"], "invalid-attr-value");
+    assert_eq!(
+        result["locations"][0]["physicalLocation"],
+        serde_json::json!({
+            "artifactLocation": {"uri": "test.html"},
+            "region": {"startLine": 1, "startColumn": 15, "endLine": 1, "endColumn": 18},
+        })
+    );
+}
+
+#[test]
+fn check_github_output_format() {
+    let project = Project::new()
+        .file("test.html", "<form method=\"put\"></form>\n")
+        .file("unparsable.html", "<div>\n");
+    assert_cmd_snapshot!(
+        cli().current_dir(project.path()).args(["check", "--output-format", "github", "."]),
+        @r###"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+    ::error file=test.html,line=1,col=15,endLine=1,endColumn=18,title=invalid-attr-value::Invalid value 'put' for attribute 'method'.
+    ::error file=unparsable.html,line=1,col=2,title=parse-error::expected close tag for opening tag <div>
+
+    ----- stderr -----
+    Couldn't check 1 files!
+    Found 1 errors.
+    "###);
+}
+
+#[test]
+fn check_gitlab_output_format() {
+    let project = Project::new().file("test.html", "<form method=\"put\"></form>\n");
+    let output = cli()
+        .current_dir(project.path())
+        .args(["check", "--output-format", "gitlab", "."])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+
+    let issues: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let issue = &issues[0];
+    assert_eq!(issue["check_name"], "invalid-attr-value");
+    assert_eq!(issue["location"]["path"], "test.html");
+    assert_eq!(issue["location"]["positions"]["begin"]["line"], 1);
+    assert!(issue["fingerprint"].is_string());
+}
+
+#[test]
+fn rule_json() {
+    let output = cli()
+        .args(["rule", "invalid-attr-value", "--output-format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let info: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(info["name"], "invalid-attr-value");
+    assert_eq!(info["category"], "correctness");
+    assert!(
+        info["explanation"]
+            .as_str()
+            .unwrap()
+            .contains("## What it does")
+    );
+}
+
+#[test]
+fn rule_all_json() {
+    let output = cli()
+        .args(["rule", "--all", "--output-format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let infos: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(infos.as_array().unwrap().len() > 1);
+}
+
+#[test]
+fn rule_unknown() {
+    assert_cmd_snapshot!(cli().args(["rule", "not-a-rule"]), @r###"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'not-a-rule' for '[RULE]': unknown rule `not-a-rule`
+
+    For more information, try '--help'.
+    "###);
+}
+
 #[test]
 fn check_nonexistent_file() {
     assert_cmd_snapshot!(cli().args(["check", "/nonexistent/path.html"]), @r#"
@@ -627,6 +1001,223 @@ fn check_respects_pyproject_custom_blocks() {
     "###);
 }
 
+#[test]
+fn check_respects_suppression_comments() {
+    let project = Project::new().file(
+        "test.html",
+        "{# djangofmt: disable-next-line=invalid-attr-value #}\n<form method=\"put\"></form>\n\
+         <!-- djangofmt: disable=invalid-attr-value -->\n<form method=\"put\"></form>\n\
+         <!-- djangofmt: enable=invalid-attr-value -->\n",
+    );
+    assert_cmd_snapshot!(cli().arg("check").arg(project.join("test.html")), @r###"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    All checks passed!
+    "###);
+}
+
+#[test]
+fn check_add_suppressions() {
+    let project = Project::new().file(
+        "test.html",
+        "<div>\n    <form method=\"put\"></form>\n</div>\n",
+    );
+    assert_cmd_snapshot!(cli().arg("check").arg("--add-suppressions").arg(project.join("test.html")), @r###"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    Added 1 suppression comment.
+    All checks passed!
+    "###);
+    let suppressed = project.read("test.html");
+    assert_eq!(
+        suppressed,
+        "<div>\n    {# djangofmt: disable-next-line=invalid-attr-value #}\n    <form method=\"put\"></form>\n</div>\n"
+    );
+
+    // A second run finds nothing left to suppress.
+    assert_cmd_snapshot!(cli().arg("check").arg("--add-suppressions").arg(project.join("test.html")), @r###"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    Added 0 suppression comments.
+    All checks passed!
+    "###);
+    assert_eq!(project.read("test.html"), suppressed);
+}
+
+#[test]
+fn check_baseline() {
+    let violation = "<form method=\"put\"></form>\n";
+    let project = Project::new().file("test.html", violation);
+    let check = || {
+        let mut command = cli();
+        command.current_dir(project.path()).args([
+            "check",
+            "--baseline",
+            "baseline.json",
+            "test.html",
+        ]);
+        command
+    };
+
+    // The baseline must be created explicitly.
+    assert_cmd_snapshot!(check(), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    djangofmt failed
+      Error: Baseline file baseline.json doesn't exist, create it with --update-baseline
+    ");
+
+    // The first update records the existing diagnostics.
+    assert_cmd_snapshot!(check().arg("--update-baseline"), @r###"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    Wrote 1 diagnostic to the baseline baseline.json.
+    All checks passed!
+    "###);
+
+    // Shifting the baselined line doesn't bring it back; only the new violation is reported.
+    std::fs::write(
+        project.join("test.html"),
+        format!("<p>Intro</p>\n\n<div>\n    {violation}</div>\n<form method=\"patch\"></form>\n"),
+    )
+    .unwrap();
+    assert_cmd_snapshot!(check(), @r###"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+      × Invalid value 'patch' for attribute 'method'.
+       ╭─[test.html:6:15]
+     5 │ </div>
+     6 │ <form method="patch"></form>
+       ·               ──┬──
+       ·                 ╰── here
+       ╰────
+      help: Use one of: get, post, dialog
+
+    Found 1 errors.
+    "###);
+
+    // Updating drops the stale entry once the violation is gone.
+    std::fs::write(
+        project.join("test.html"),
+        "<form method=\"patch\"></form>\n",
+    )
+    .unwrap();
+    assert_cmd_snapshot!(check().arg("--update-baseline"), @r###"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    Wrote 1 diagnostic to the baseline baseline.json.
+    All checks passed!
+    "###);
+    let baseline = project.read("baseline.json");
+    assert_eq!(baseline.matches("\"code\"").count(), 1);
+}
+
+#[test]
+fn check_update_baseline_keeps_unchecked_files() {
+    let violation = "<form method=\"put\"></form>\n";
+    let project = Project::new()
+        .file("a.html", violation)
+        .file("b.html", violation);
+    let check = |paths: &[&str]| {
+        let mut command = cli();
+        command
+            .current_dir(project.path())
+            .args(["check", "--baseline", "baseline.json", "--update-baseline"])
+            .args(paths);
+        command
+    };
+
+    assert_cmd_snapshot!(check(&["a.html", "b.html"]), @r###"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    Wrote 2 diagnostics to the baseline baseline.json.
+    All checks passed!
+    "###);
+
+    // Fixing `a.html` and updating from it alone keeps the entry of `b.html`.
+    std::fs::write(project.join("a.html"), "<form method=\"post\"></form>\n").unwrap();
+    assert_cmd_snapshot!(check(&["a.html"]), @r###"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    Wrote 1 diagnostic to the baseline baseline.json.
+    All checks passed!
+    "###);
+    let baseline = project.read("baseline.json");
+    assert!(baseline.contains("\"path\": \"b.html\""));
+    assert!(!baseline.contains("\"path\": \"a.html\""));
+}
+
+#[test]
+fn check_update_baseline_drops_deleted_and_excluded_files() {
+    let violation = "<form method=\"put\"></form>\n";
+    let project = Project::new()
+        .file("a.html", violation)
+        .file("b.html", violation)
+        .file("vendor/c.html", violation);
+    let check = |args: &[&str]| {
+        let mut command = cli();
+        command
+            .current_dir(project.path())
+            .args(["check", "--baseline", "baseline.json", "--update-baseline"])
+            .args(args);
+        command
+    };
+
+    assert_cmd_snapshot!(check(&["."]), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    Wrote 3 diagnostics to the baseline baseline.json.
+    All checks passed!
+    ");
+
+    // Updating from `a.html` alone drops the entries of the deleted `b.html` and of the newly
+    // excluded `vendor/c.html`.
+    std::fs::remove_file(project.join("b.html")).unwrap();
+    assert_cmd_snapshot!(check(&["--extend-exclude", "vendor", "a.html"]), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    Wrote 1 diagnostic to the baseline baseline.json.
+    All checks passed!
+    ");
+    let baseline = project.read("baseline.json");
+    assert!(baseline.contains("\"path\": \"a.html\""));
+    assert!(!baseline.contains("\"path\": \"b.html\""));
+    assert!(!baseline.contains("\"path\": \"vendor/c.html\""));
+}
+
 #[test]
 fn check_respects_pyproject_per_file_ignores() {
     // Same violation in both files: the glob must silence it in `legacy/` only.
@@ -674,3 +1265,433 @@ fn check_respects_pyproject_per_file_ignores() {
     Found 1 errors.
     "###);
 }
+
+#[test]
+fn check_reads_the_config_option() {
+    let project = Project::new()
+        .file("djangofmt.toml", "[lint]\nselect = [\"category:all\"]\n")
+        .file(
+            "configs/lenient.toml",
+            "[lint]\nignore = [\"invalid-attr-value\"]\n",
+        )
+        .file("test.html", "<form method=\"put\"></form>\n");
+    let check = cli()
+        .current_dir(project.path())
+        .args(["check", "."])
+        .output()
+        .unwrap();
+    assert_eq!(check.status.code(), Some(1));
+    assert_cmd_snapshot!(
+        cli()
+            .current_dir(project.path())
+            .args(["check", "--config", "configs/lenient.toml", "."]),
+        @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    All checks passed!
+    ");
+}
+
+#[test]
+fn check_follows_the_nearest_pyproject() {
+    let violation = "<form method=\"put\"></form>\n";
+    let project = Project::new()
+        .file("pyproject.toml", "[tool.djangofmt]\n")
+        .file(
+            "apps/legacy/pyproject.toml",
+            "[tool.djangofmt.lint.per-file-ignores]\n\"old/*\" = [\"invalid-attr-value\"]\n",
+        )
+        .file("apps/legacy/old/a.html", violation)
+        .file("apps/legacy/new.html", violation)
+        .file("old/b.html", violation);
+    // `old/*` is anchored at `apps/legacy`, and only applies to the files below it.
+    assert_cmd_snapshot!(
+        cli()
+            .current_dir(project.path())
+            .args(["check", "--output-format", "concise", "."]),
+        @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    apps/legacy/new.html:1:15: invalid-attr-value Invalid value 'put' for attribute 'method'.
+    old/b.html:1:15: invalid-attr-value Invalid value 'put' for attribute 'method'.
+    Found 2 errors.
+    ");
+}
+
+// ── Show settings ────────────────────────────────────────────────────
+
+/// A project whose `legacy/a.html` takes its settings from every kind of source.
+fn layered_settings() -> Project {
+    Project::new()
+        .file("shared.toml", "custom-blocks = [\"cache\"]\n")
+        .file(
+            "djangofmt.toml",
+            "extend = \"shared.toml\"\nline-length = 100\n\n[lint.per-file-ignores]\n\"legacy/*\" = [\"invalid-attr-value\"]\n",
+        )
+        .file(".editorconfig", "[*]\nindent_size = 4\n\n[*.html]\nindent_size = 2\n")
+        .file("legacy/a.html", "<p></p>\n")
+}
+
+#[test]
+fn show_settings() {
+    let project = layered_settings();
+    assert_cmd_snapshot!(
+        cli().current_dir(project.path()).args([
+            "show-settings",
+            "legacy/a.html",
+            "--select",
+            "invalid-attr-value,missing-img-alt",
+        ]),
+        @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Settings for legacy/a.html
+    Configuration file: djangofmt.toml
+
+    line-length = 100                      # djangofmt.toml
+    indent-width = 2                       # .editorconfig [*.html]
+    custom-blocks = ["cache"]              # shared.toml
+    html-void-self-closing = "never"       # default
+    style-attr-layout = "single-line"      # default
+    preserve-unquoted-attrs = false        # default
+    normalize-template-whitespace = false  # default
+    wrap-template-tags = false             # default
+    attribute-order = null                 # default
+    profile = "django"                     # file extension
+
+    [lint]
+    select = ["invalid-attr-value", "missing-img-alt"]  # cli
+    ignore = []                                         # default
+    preview = false                                     # default
+    per-file-ignores = ["invalid-attr-value"]           # djangofmt.toml
+    rules = ["missing-img-alt"]
+
+    ----- stderr -----
+    "#);
+}
+
+#[test]
+fn show_settings_json() {
+    let project = layered_settings();
+    let output = cli()
+        .current_dir(project.path())
+        .args([
+            "show-settings",
+            "legacy/a.html",
+            "--output-format",
+            "json",
+            "--indent-width",
+            "8",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let settings: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(settings["config-file"], "djangofmt.toml");
+    assert_eq!(
+        settings["format"]["line-length"],
+        serde_json::json!({"value": 100, "source": "djangofmt.toml"})
+    );
+    assert_eq!(
+        settings["format"]["indent-width"],
+        serde_json::json!({"value": 8, "source": "cli"})
+    );
+    assert_eq!(
+        settings["format"]["custom-blocks"],
+        serde_json::json!({"value": ["cache"], "source": "shared.toml"})
+    );
+    assert_eq!(
+        settings["profile"],
+        serde_json::json!({"value": "django", "source": "file extension"})
+    );
+    assert_eq!(
+        settings["lint"]["per-file-ignores"],
+        serde_json::json!({"value": ["invalid-attr-value"], "source": "djangofmt.toml"})
+    );
+    let rules = settings["lint"]["rules"].as_array().unwrap();
+    assert!(!rules.contains(&serde_json::json!("invalid-attr-value")));
+    assert!(rules.contains(&serde_json::json!("missing-img-alt")));
+}
+
+// ── Changed since ───────────────────────────────────────────────────
+
+fn git(project: &Project, args: &[&str]) {
+    let output = Command::new("git")
+        .args([
+            "-c",
+            "user.name=djangofmt",
+            "-c",
+            "user.email=djangofmt@example.com",
+        ])
+        .args(args)
+        .current_dir(project.path())
+        .output()
+        .expect("Failed to run git");
+    assert!(output.status.success(), "{output:?}");
+}
+
+/// A repository with two unformatted templates committed, then one of them edited.
+fn edited_repository() -> Project {
+    let project = Project::new()
+        .file("untouched.html", "<p   id=\"a\"></p>\n")
+        .file("edited.html", "<p   id=\"b\"></p>\n<p   id=\"c\"></p>\n");
+    git(&project, &["init", "-q"]);
+    git(&project, &["add", "."]);
+    git(&project, &["commit", "-qm", "initial"]);
+    std::fs::write(
+        project.join("edited.html"),
+        "<p   id=\"b\"></p>\n<p   id=\"d\"></p>\n",
+    )
+    .expect("Failed to edit the template");
+    project
+}
+
+#[test]
+fn format_changed_since_skips_unchanged_files() {
+    let project = edited_repository();
+    assert_cmd_snapshot!(
+        cli().current_dir(project.path()).args(["--changed-since", "HEAD", "."]),
+        @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    1 file reformatted !
+    ");
+    assert_eq!(
+        project.read("edited.html"),
+        "<p id=\"b\"></p>\n<p id=\"d\"></p>\n"
+    );
+    assert_eq!(project.read("untouched.html"), "<p   id=\"a\"></p>\n");
+}
+
+#[test]
+fn format_lines_changed_only() {
+    let project = edited_repository();
+    assert_cmd_snapshot!(
+        cli()
+            .current_dir(project.path())
+            .args(["--changed-since", "HEAD", "--lines-changed-only", "."]),
+        @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    1 file reformatted !
+    ");
+    assert_eq!(
+        project.read("edited.html"),
+        "<p   id=\"b\"></p>\n<p id=\"d\"></p>\n"
+    );
+}
+
+// ── Cache ────────────────────────────────────────────────────────────
+
+#[test]
+fn check_replays_cached_diagnostics() {
+    let project = Project::new()
+        .file("pyproject.toml", "[tool.djangofmt]\n")
+        .file("test.html", "<form method=\"put\"></form>\n");
+    let check = || {
+        cli()
+            .current_dir(project.path())
+            .args(["check", "."])
+            .output()
+            .unwrap()
+    };
+    let first = check();
+    assert!(project.join(".djangofmt_cache/CACHEDIR.TAG").exists());
+    let second = check();
+    assert_eq!(second.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&first.stderr),
+        String::from_utf8_lossy(&second.stderr)
+    );
+
+    // Editing the file invalidates its entry.
+    std::fs::write(project.join("test.html"), "<form method=\"post\"></form>\n").unwrap();
+    assert_eq!(check().status.code(), Some(0));
+}
+
+#[test]
+fn format_skips_cached_files() {
+    let project = Project::new()
+        .file("pyproject.toml", "[tool.djangofmt]\n")
+        .file("test.html", "<p   id=\"a\"></p>\n");
+    assert_cmd_snapshot!(cli().current_dir(project.path()).arg("."), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    1 file reformatted !
+    ");
+    assert_cmd_snapshot!(cli().current_dir(project.path()).arg("."), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    1 file left unchanged !
+    ");
+}
+
+#[test]
+fn clean_removes_the_cache() {
+    let project = Project::new()
+        .file(
+            "pyproject.toml",
+            "[tool.djangofmt]\ncache-dir = \"build/cache\"\n",
+        )
+        .file("test.html", "<p></p>\n");
+    let check = cli()
+        .current_dir(project.path())
+        .args(["check", "."])
+        .output()
+        .unwrap();
+    assert!(check.status.success());
+    assert!(project.join("build/cache").exists());
+
+    assert_cmd_snapshot!(cli().current_dir(project.path()).arg("clean"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    Removed cache at: build/cache
+    ");
+    assert!(!project.join("build/cache").exists());
+    assert_cmd_snapshot!(cli().current_dir(project.path()).arg("clean"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    No cache found at: build/cache
+    ");
+}
+
+// ── Language server ──────────────────────────────────────────────────
+
+/// Frame each of `messages` with the `Content-Length` header of the LSP base protocol.
+fn lsp_frames(messages: &[serde_json::Value]) -> String {
+    messages.iter().fold(String::new(), |mut frames, message| {
+        let body = message.to_string();
+        let _ = write!(frames, "Content-Length: {}\r\n\r\n{body}", body.len());
+        frames
+    })
+}
+
+/// Split the output of the server back into its messages.
+fn lsp_messages(mut output: &str) -> Vec<serde_json::Value> {
+    let mut messages = Vec::new();
+    while let Some((header, rest)) = output.split_once("\r\n\r\n") {
+        let length: usize = header
+            .trim_start_matches("Content-Length: ")
+            .parse()
+            .expect("Invalid Content-Length header");
+        messages.push(serde_json::from_str(&rest[..length]).expect("Invalid message"));
+        output = &rest[length..];
+    }
+    messages
+}
+
+#[test]
+fn server_formats_and_publishes_diagnostics() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let source = "<div>\n<p>Hi</p>\n</div>\n{% blocktranslate %}Hi{% endblocktranslate %}\n";
+    let project = Project::new()
+        .file("pyproject.toml", "[tool.djangofmt]\nindent-width = 2\n")
+        .file("templates/test.html", source);
+    let root = format!("file://{}", project.path().display());
+    let uri = format!("{root}/templates/test.html");
+    let document = serde_json::json!({"uri": uri});
+
+    let input = lsp_frames(&[
+        serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {
+            "processId": null,
+            "capabilities": {},
+            "workspaceFolders": [{"uri": root, "name": "project"}],
+        }}),
+        serde_json::json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+        serde_json::json!({"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+            "textDocument": {"uri": uri, "languageId": "html", "version": 1, "text": source},
+        }}),
+        serde_json::json!({"jsonrpc": "2.0", "id": 2, "method": "textDocument/formatting", "params": {
+            "textDocument": document,
+            "options": {"tabSize": 4, "insertSpaces": true},
+        }}),
+        serde_json::json!({"jsonrpc": "2.0", "id": 3, "method": "textDocument/codeAction", "params": {
+            "textDocument": document,
+            "range": {"start": {"line": 3, "character": 0}, "end": {"line": 3, "character": 0}},
+            "context": {"diagnostics": []},
+        }}),
+        serde_json::json!({"jsonrpc": "2.0", "id": 4, "method": "shutdown"}),
+        serde_json::json!({"jsonrpc": "2.0", "method": "exit"}),
+    ]);
+
+    let mut server = cli()
+        .arg("server")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    server
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = server.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let messages = lsp_messages(std::str::from_utf8(&output.stdout).unwrap());
+    let response = |id: u64| {
+        messages
+            .iter()
+            .find(|message| message["id"] == id)
+            .unwrap()
+            .clone()
+    };
+
+    let diagnostics = messages
+        .iter()
+        .find(|message| message["method"] == "textDocument/publishDiagnostics")
+        .unwrap();
+    assert_eq!(diagnostics["params"]["version"], 1);
+    assert_eq!(
+        diagnostics["params"]["diagnostics"][0]["code"],
+        "untrimmed-blocktranslate"
+    );
+
+    // `indent-width` comes from the `pyproject.toml` of the workspace folder.
+    let edits = response(2)["result"].clone();
+    assert!(
+        edits[0]["newText"]
+            .as_str()
+            .unwrap()
+            .starts_with("<div>\n  <p>Hi</p>\n</div>\n")
+    );
+
+    let action = &response(3)["result"][0];
+    assert_eq!(action["kind"], "quickfix");
+    assert!(
+        action["title"]
+            .as_str()
+            .unwrap()
+            .starts_with("untrimmed-blocktranslate: ")
+    );
+    assert!(action["edit"]["changes"][&uri].is_array());
+}
@@ -22,7 +22,8 @@ fn fmt_snapshot() {
         if let Some(options) = options {
             for (option_name, pyproject) in options {
                 let config = build_config(&pyproject);
-                let output = run_format_test(path, &input, &config);
+                let profile = pyproject.profile.unwrap_or_default();
+                let output = run_format_test(path, &input, &config, profile);
                 build_settings(path).bind(|| {
                     let name = path.file_stem().unwrap().to_str().unwrap();
                     assert_snapshot!(format!("{name}.{option_name}"), output);
@@ -30,7 +31,7 @@ fn fmt_snapshot() {
             }
         } else {
             let config = build_config(&PyprojectSettings::default());
-            let output = run_format_test(path, &input, &config);
+            let output = run_format_test(path, &input, &config, Profile::default());
             build_settings(path).bind(|| {
                 let name = path.file_stem().unwrap().to_str().unwrap();
                 assert_snapshot!(name, output);
@@ -45,13 +46,15 @@ fn build_config(pyproject: &PyprojectSettings) -> FormatterConfig {
         pyproject.indent_width.unwrap_or_default(),
         pyproject.custom_blocks.clone(),
         pyproject.html_void_self_closing.unwrap_or_default(),
+        pyproject.style_attr_layout.unwrap_or_default(),
         pyproject.preserve_unquoted_attrs.unwrap_or_default(),
+        pyproject.normalize_template_whitespace.unwrap_or_default(),
+        pyproject.wrap_template_tags.unwrap_or_default(),
+        pyproject.attribute_order.as_ref(),
     )
 }
 
-fn run_format_test(path: &Path, input: &str, config: &FormatterConfig) -> String {
-    let profile = Profile::Django;
-
+fn run_format_test(path: &Path, input: &str, config: &FormatterConfig, profile: Profile) -> String {
     let output = format_text(input, config, profile)
         .map_err(|err| format!("failed to format '{}': {:?}", path.display(), err))
         .expect("Failed to format text in test")
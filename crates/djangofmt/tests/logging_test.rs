@@ -1,6 +1,6 @@
 use djangofmt::args::Profile;
 use djangofmt::commands::format::{FormatterConfig, format_text};
-use djangofmt::line_width::{IndentWidth, LineLength, SelfClosing};
+use djangofmt::line_width::{IndentWidth, LineLength, SelfClosing, StyleAttrLayout};
 use tracing_test::traced_test;
 
 #[test]
@@ -18,7 +18,11 @@ invalid-css-property;
         IndentWidth::default(),
         None,
         SelfClosing::default(),
+        StyleAttrLayout::default(),
         false,
+        false,
+        false,
+        None,
     );
     let profile = Profile::Django;
 
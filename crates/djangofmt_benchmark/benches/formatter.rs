@@ -1,6 +1,6 @@
 use djangofmt::{
     commands::format::{FormatterConfig, format_text},
-    line_width::{IndentWidth, LineLength, SelfClosing},
+    line_width::{IndentWidth, LineLength, SelfClosing, StyleAttrLayout},
 };
 use djangofmt_benchmark::{ALL_TEMPLATES, TestFile};
 
@@ -15,7 +15,11 @@ fn format_templates(bencher: divan::Bencher, template: &'static TestFile) {
         IndentWidth::default(),
         None,
         SelfClosing::default(),
+        StyleAttrLayout::default(),
         false,
+        false,
+        false,
+        None,
     );
 
     bencher
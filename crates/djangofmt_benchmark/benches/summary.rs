@@ -1,4 +1,4 @@
-use djangofmt::commands::format::{FormatResult, build_summary};
+use djangofmt::commands::format::{FormatMode, FormatResult, build_summary};
 
 fn main() {
     divan::main();
@@ -23,5 +23,5 @@ fn build_summary_mixed(bencher: divan::Bencher, n: usize) {
     // Roughly 1/3 of each variant
     let results = make_results(n / 2, n / 3, n / 4);
 
-    bencher.bench(|| build_summary(divan::black_box(&results)));
+    bencher.bench(|| build_summary(divan::black_box(&results), FormatMode::Write));
 }
@@ -25,7 +25,7 @@ pub struct Checker<'a> {
 
 impl<'a> Checker<'a> {
     #[must_use]
-    pub const fn new(source: &'a str, settings: &'a Settings, path: Option<&'a Path>) -> Self {
+    pub fn new(source: &'a str, settings: &'a Settings, path: Option<&'a Path>) -> Self {
         Self {
             context: LintContext::new(source, settings, path),
             block_names: SmallVec::new_const(),
@@ -111,6 +111,11 @@ impl<'a> Checker<'a> {
         if self.is_rule_enabled(Rule::DuplicateBlockName) {
             rules::correctness::duplicate_block_name::check(self);
         }
+
+        // Last: needs every other diagnostic to have been matched against the suppressions.
+        if self.is_rule_enabled(Rule::UnusedSuppression) {
+            rules::correctness::unused_suppression::check(self);
+        }
     }
 
     fn visit_node(&mut self, node: &Node<'a>) {
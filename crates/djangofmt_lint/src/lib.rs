@@ -22,6 +22,7 @@ pub mod rule_selector;
 pub mod rule_set;
 mod rules;
 pub mod settings;
+pub mod suppression;
 mod violation;
 
 pub use checker::Checker;
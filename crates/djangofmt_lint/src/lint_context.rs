@@ -2,7 +2,7 @@
 //!
 //! The `LintContext` owns the diagnostic buffer and is shared by reference through the AST visitor.
 //! Reporting a diagnostic returns a guard that buffers a partial diagnostic; on Drop the guard
-//! pushes it into the context's buffer.
+//! pushes it into the context's buffer, unless an inline suppression comment silences it.
 
 use std::cell::RefCell;
 use std::path::Path;
@@ -13,6 +13,7 @@ use crate::LintDiagnostic;
 use crate::Settings;
 use crate::fix::Fix;
 use crate::registry::Rule;
+use crate::suppression::Suppressions;
 use crate::violation::Violation;
 
 /// A type for collecting diagnostics in a given file.
@@ -24,15 +25,17 @@ pub struct LintContext<'a> {
     source: &'a str,
     settings: &'a Settings,
     path: Option<&'a Path>,
+    suppressions: Suppressions<'a>,
 }
 
 impl<'a> LintContext<'a> {
     #[must_use]
-    pub const fn new(source: &'a str, settings: &'a Settings, path: Option<&'a Path>) -> Self {
+    pub fn new(source: &'a str, settings: &'a Settings, path: Option<&'a Path>) -> Self {
         Self {
             source,
             settings,
             path,
+            suppressions: Suppressions::from_source(source),
             diagnostics: RefCell::new(Vec::new()),
         }
     }
@@ -49,6 +52,12 @@ impl<'a> LintContext<'a> {
         self.path
     }
 
+    /// The inline suppression comments of the source.
+    #[must_use]
+    pub const fn suppressions(&self) -> &Suppressions<'a> {
+        &self.suppressions
+    }
+
     /// The settings active for this run.
     #[must_use]
     pub const fn settings(&self) -> &'a Settings {
@@ -110,6 +119,7 @@ impl<'a> LintContext<'a> {
     ) -> DiagnosticGuard<'_, 'a> {
         DiagnosticGuard {
             context: self,
+            rule: V::RULE,
             diagnostic: Some(LintDiagnostic {
                 code: V::RULE.into(),
                 message: violation.message(),
@@ -131,6 +141,7 @@ impl<'a> LintContext<'a> {
 /// Guard that holds a partially-built diagnostic and pushes it on Drop.
 pub struct DiagnosticGuard<'ctx, 'a> {
     context: &'ctx LintContext<'a>,
+    rule: Rule,
     diagnostic: Option<LintDiagnostic>,
 }
 
@@ -150,7 +161,12 @@ impl Drop for DiagnosticGuard<'_, '_> {
             // Don't push half-built diagnostics if we're unwinding.
             return;
         }
-        if let Some(diag) = self.diagnostic.take() {
+        if let Some(diag) = self.diagnostic.take()
+            && !self
+                .context
+                .suppressions
+                .suppresses(self.rule, diag.span.offset() as usize)
+        {
             self.context.diagnostics.borrow_mut().push(diag);
         }
     }
@@ -23,6 +23,17 @@ pub enum RuleGroup {
 }
 
 impl RuleGroup {
+    /// The name of this lifecycle status, as shown in the rule docs and reports.
+    #[must_use]
+    pub const fn status(self) -> &'static str {
+        match self {
+            Self::Stable { .. } => "stable",
+            Self::Preview { .. } => "preview",
+            Self::Deprecated { .. } => "deprecated",
+            Self::Removed { .. } => "removed",
+        }
+    }
+
     /// The version at which this lifecycle status was set.
     #[must_use]
     pub const fn since(self) -> &'static str {
@@ -264,4 +275,5 @@ define_rules! {
     (TableHeaderMissingScope, rules::accessibility::table_header_missing_scope::TableHeaderMissingScope),
     (SameFilePartialInclude, rules::style::same_file_partial_include::SameFilePartialInclude),
     (UnsortedTailwindClasses, rules::style::unsorted_tailwind_classes::UnsortedTailwindClasses),
+    (UnusedSuppression, rules::correctness::unused_suppression::UnusedSuppression),
 }
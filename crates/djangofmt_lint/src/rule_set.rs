@@ -9,7 +9,7 @@ use crate::registry::Rule;
 const RULESET_SIZE: usize = Rule::COUNT.div_ceil(64);
 
 /// A compact bitset of enabled lint rules: one bit per rule, tested with an array index + shift.
-#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub struct RuleSet([u64; RULESET_SIZE]);
 
 // Todo: add [`from_rules`](https://github.com/astral-sh/ruff/blob/f414174695c9c2067b04c95b51709c28d27a1d03/crates/ruff_linter/src/registry/rule_set.rs#L54) ?
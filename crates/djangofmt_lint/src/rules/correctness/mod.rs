@@ -1,3 +1,4 @@
 pub mod duplicate_block_name;
 pub mod invalid_attr_value;
 pub mod untrimmed_blocktranslate;
+pub mod unused_suppression;
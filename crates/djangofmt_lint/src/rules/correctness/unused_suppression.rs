@@ -0,0 +1,169 @@
+use std::borrow::Cow;
+
+use crate::fix::{Edit, Fix, FixAvailability};
+use crate::registry::{Rule, RuleCategory};
+use crate::suppression::{Action, Directive};
+use crate::violation::{Violation, ViolationMetadata, derive_message_formats};
+use crate::{Checker, span};
+
+/// ## What it does
+/// Checks for `djangofmt:` suppression comments that don't silence any diagnostic, or that name
+/// unknown or non-enabled rules.
+///
+/// Suppression comments can use either the Django/Jinja or the HTML comment syntax:
+/// - `{# djangofmt: disable-next-line=missing-img-alt #}` silences the listed rules on the next line.
+/// - `{# djangofmt: disable=use-https #}` silences the listed rules up to the next
+///   `{# djangofmt: enable=use-https #}`, or to the end of the file.
+///
+/// Omitting the rule list (`{# djangofmt: disable-next-line #}`) silences every rule.
+///
+/// ## Why is this bad?
+/// A suppression that no longer matches a diagnostic is dead weight: it misleads readers and
+/// silently hides any future diagnostic of the same rule on that line.
+///
+/// ## Example
+/// ```html
+/// {# djangofmt: disable-next-line=missing-img-alt #}
+/// <img src="logo.png" alt="Logo">
+/// ```
+///
+/// Use instead:
+/// ```html
+/// <img src="logo.png" alt="Logo">
+/// ```
+///
+/// ## Fix safety
+/// This rule's fix is marked as safe: it only removes comments, or unused rule names from them.
+#[derive(Debug, Default, PartialEq, Eq, ViolationMetadata)]
+#[violation_metadata(preview_since = "NEXT_DJANGOFMT_VERSION")]
+pub struct UnusedSuppression {
+    /// Listed rules that didn't silence any diagnostic.
+    pub unused: Vec<String>,
+    /// Listed rules that are known but not enabled.
+    pub disabled: Vec<String>,
+    /// Listed names that aren't rules.
+    pub unknown: Vec<String>,
+}
+
+impl UnusedSuppression {
+    /// A violation naming no rule is reported for an unused blanket directive.
+    const fn is_blanket(&self) -> bool {
+        self.unused.is_empty() && self.disabled.is_empty() && self.unknown.is_empty()
+    }
+
+    fn details(&self) -> String {
+        [
+            ("unused", &self.unused),
+            ("non-enabled", &self.disabled),
+            ("unknown", &self.unknown),
+        ]
+        .iter()
+        .filter(|(_, names)| !names.is_empty())
+        .map(|(label, names)| {
+            let names: Vec<String> = names.iter().map(|name| format!("`{name}`")).collect();
+            format!("{label}: {}", names.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+    }
+}
+
+impl Violation for UnusedSuppression {
+    const RULE: Rule = Rule::UnusedSuppression;
+    const CATEGORY: RuleCategory = RuleCategory::Correctness;
+    const FIX_AVAILABILITY: FixAvailability = FixAvailability::Always;
+
+    #[derive_message_formats]
+    fn message(&self) -> Cow<'static, str> {
+        if self.is_blanket() {
+            "Unused blanket suppression.".into()
+        } else {
+            format!("Unused suppression ({}).", self.details()).into()
+        }
+    }
+
+    fn fix_title(&self) -> Option<&'static str> {
+        Some("Remove unused suppression")
+    }
+}
+
+/// Flag every `disable` / `disable-next-line` directive with a rule name that silenced nothing.
+///
+/// Runs after every other rule, once all diagnostics have been matched against the directives.
+pub fn check(checker: &Checker<'_>) {
+    let context = checker.context();
+    for directive in context.suppressions().directives() {
+        if directive.action == Action::Enable {
+            continue;
+        }
+
+        let mut violation = UnusedSuppression::default();
+        if directive.is_blanket() {
+            if directive.is_used() {
+                continue;
+            }
+        } else {
+            for code in &directive.codes {
+                match code.rule {
+                    None => violation.unknown.push(code.name.into()),
+                    // Whether it is used is only known once this very pass is over.
+                    Some(Rule::UnusedSuppression) => {}
+                    Some(rule) if !checker.is_rule_enabled(rule) => {
+                        violation.disabled.push(code.name.into());
+                    }
+                    Some(_) if !code.is_used() => violation.unused.push(code.name.into()),
+                    Some(_) => {}
+                }
+            }
+            // Every listed rule is in use.
+            if violation.is_blanket() {
+                continue;
+            }
+        }
+
+        let fix = removal_fix(context.source(), directive, &violation);
+        let mut guard = checker.report_diagnostic(
+            &violation,
+            span(directive.range.start, directive.range.len()),
+        );
+        guard.set_fix(fix);
+    }
+}
+
+/// Remove the unused names from the directive, or the whole comment when none is left.
+fn removal_fix(source: &str, directive: &Directive<'_>, violation: &UnusedSuppression) -> Fix {
+    let kept: Vec<&str> = directive
+        .codes
+        .iter()
+        .map(|code| code.name)
+        .filter(|name| {
+            ![&violation.unused, &violation.disabled, &violation.unknown]
+                .iter()
+                .any(|names| names.iter().any(|flagged| flagged == *name))
+        })
+        .collect();
+
+    if !kept.is_empty() {
+        let range = &directive.codes_range;
+        return Fix::safe_edit(Edit::replacement(
+            kept.join(", "),
+            span(range.start, range.len()),
+        ));
+    }
+
+    // A comment alone on its line takes the whole line with it.
+    let line_start = source[..directive.range.start]
+        .rfind('\n')
+        .map_or(0, |i| i + 1);
+    let line_end = source[directive.range.end..]
+        .find('\n')
+        .map_or(source.len(), |i| directive.range.end + i + 1);
+    let alone = source[line_start..directive.range.start].trim().is_empty()
+        && source[directive.range.end..line_end].trim().is_empty();
+    let (start, end) = if alone {
+        (line_start, line_end)
+    } else {
+        (directive.range.start, directive.range.end)
+    };
+    Fix::safe_edit(Edit::deletion(span(start, end - start)))
+}
@@ -7,7 +7,7 @@ use crate::rule_set::RuleSet;
 /// Per-rule config is keyed by rule name, since djangofmt has no plugin grouping to key it by.
 pub mod unsorted_tailwind_classes {
     /// Settings for [`crate::registry::Rule::UnsortedTailwindClasses`].
-    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
     pub struct Settings {
         /// Custom Tailwind prefix (e.g. `tw-` for v3, `tw:` for v4). `None` means no prefix.
         pub prefix: Option<String>,
@@ -15,7 +15,7 @@ pub mod unsorted_tailwind_classes {
 }
 
 /// Configuration settings for the linter.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Hash)]
 pub struct Settings {
     /// The set of rules that are active for this run.
     pub rules: RuleSet,
@@ -0,0 +1,636 @@
+//! Inline suppression comments.
+//!
+//! A suppression is a Django/Jinja or HTML comment whose body starts with `djangofmt:`:
+//!
+//! ```text
+//! {# djangofmt: disable-next-line=missing-img-alt #}
+//! <!-- djangofmt: disable-next-line=missing-img-alt,use-https -->
+//! {# djangofmt: disable=use-https #} … {# djangofmt: enable=use-https #}
+//! ```
+//!
+//! - `disable-next-line` silences diagnostics starting on the line after the comment.
+//! - `disable` silences diagnostics from the end of the comment up to the next `enable`
+//!   that names one of its rules (or lists none), or to the end of the file.
+//!
+//! Omitting `=…` makes a blanket directive that applies to every rule.
+//! [`Suppressions`] is consulted by the [`crate::LintContext`] when a diagnostic is pushed,
+//! and records which directives were used so unused ones can be reported.
+//! [`add_suppressions`] writes the directives silencing a set of diagnostics.
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::str::FromStr;
+
+use markup_fmt::ast::{JinjaTag, JinjaTagOrChildren, Node, NodeKind, Root};
+
+use crate::LintDiagnostic;
+use crate::fix::apply::{ApplyResult, apply_fixes};
+use crate::fix::{Applicability, Edit, Fix};
+use crate::registry::Rule;
+use crate::span;
+
+/// Prefix shared by every djangofmt comment directive.
+const DIRECTIVE_PREFIX: &str = "djangofmt:";
+
+/// Comment delimiters a directive can live in.
+const COMMENT_DELIMITERS: [(&str, &str); 2] = [("{#", "#}"), ("<!--", "-->")];
+
+/// What a directive does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// `disable`: open a region where the listed rules are silenced.
+    Disable,
+    /// `disable-next-line`: silence the listed rules on the following line.
+    DisableNextLine,
+    /// `enable`: close the `disable` regions of the listed rules.
+    Enable,
+}
+
+impl Action {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "disable" => Some(Self::Disable),
+            "disable-next-line" => Some(Self::DisableNextLine),
+            "enable" => Some(Self::Enable),
+            _ => None,
+        }
+    }
+}
+
+/// A rule name listed by a directive.
+#[derive(Debug)]
+pub struct SuppressedCode<'a> {
+    /// The name as written in the comment.
+    pub name: &'a str,
+    /// The rule it names, or [`None`] for an unknown name.
+    pub rule: Option<Rule>,
+    used: Cell<bool>,
+}
+
+impl SuppressedCode<'_> {
+    /// Whether this code silenced at least one diagnostic.
+    #[must_use]
+    pub const fn is_used(&self) -> bool {
+        self.used.get()
+    }
+}
+
+/// A single parsed `djangofmt:` comment.
+#[derive(Debug)]
+pub struct Directive<'a> {
+    /// Byte range of the whole comment, delimiters included.
+    pub range: Range<usize>,
+    pub action: Action,
+    /// The listed rules; empty for a blanket directive.
+    pub codes: Vec<SuppressedCode<'a>>,
+    /// Byte range of the comma-separated rule list, empty for a blanket directive.
+    pub codes_range: Range<usize>,
+    /// Byte range in which a diagnostic must start to be silenced.
+    scope: Range<usize>,
+    /// Whether a blanket directive silenced at least one diagnostic.
+    used: Cell<bool>,
+}
+
+impl<'a> Directive<'a> {
+    /// Parse the comment spanning `range`, whose body (delimiters excluded) is `body`.
+    fn parse(source: &'a str, range: Range<usize>, body: &'a str) -> Option<Self> {
+        let body = body
+            .trim()
+            .trim_start_matches('-')
+            .trim_end_matches('-')
+            .trim()
+            .strip_prefix(DIRECTIVE_PREFIX)?
+            .trim_start();
+        let (action, codes) = match body.split_once('=') {
+            Some((action, codes)) => (action.trim_end(), codes.trim()),
+            None => (body, &body[body.len()..]),
+        };
+        let action = Action::from_name(action)?;
+
+        let codes_start = offset_of(source, codes);
+        Some(Self {
+            range,
+            action,
+            codes: codes
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(|name| SuppressedCode {
+                    name,
+                    rule: Rule::from_str(name).ok(),
+                    used: Cell::new(false),
+                })
+                .collect(),
+            codes_range: codes_start..codes_start + codes.len(),
+            scope: 0..0,
+            used: Cell::new(false),
+        })
+    }
+
+    /// Whether the directive applies to every rule.
+    #[must_use]
+    pub const fn is_blanket(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    /// Whether a blanket directive silenced at least one diagnostic.
+    #[must_use]
+    pub const fn is_used(&self) -> bool {
+        self.used.get()
+    }
+
+    fn names(&self, rule: Rule) -> bool {
+        self.codes.iter().any(|code| code.rule == Some(rule))
+    }
+
+    /// Whether this `enable` directive closes the region opened by `disable`.
+    fn closes(&self, disable: &Self) -> bool {
+        self.is_blanket()
+            || disable.is_blanket()
+            || self
+                .codes
+                .iter()
+                .any(|code| code.rule.is_some_and(|rule| disable.names(rule)))
+    }
+}
+
+/// Every suppression directive of a file.
+#[derive(Debug, Default)]
+pub struct Suppressions<'a> {
+    directives: Vec<Directive<'a>>,
+}
+
+impl<'a> Suppressions<'a> {
+    /// Collect the directives of `source`, in source order.
+    #[must_use]
+    pub fn from_source(source: &'a str) -> Self {
+        // Fast path: most files carry no directive at all.
+        if !source.contains(DIRECTIVE_PREFIX) {
+            return Self::default();
+        }
+
+        let mut directives = Vec::new();
+        for (open, close) in COMMENT_DELIMITERS {
+            let mut cursor = 0;
+            while let Some(start) = source[cursor..].find(open).map(|i| cursor + i) {
+                let body_start = start + open.len();
+                let Some(body_end) = source[body_start..].find(close).map(|i| body_start + i)
+                else {
+                    break;
+                };
+                cursor = body_end + close.len();
+                directives.extend(Directive::parse(
+                    source,
+                    start..cursor,
+                    &source[body_start..body_end],
+                ));
+            }
+        }
+        directives.sort_by_key(|directive| directive.range.start);
+
+        for i in 0..directives.len() {
+            let scope = match directives[i].action {
+                Action::DisableNextLine => next_line(source, directives[i].range.end),
+                Action::Disable => {
+                    let end = directives[i + 1..]
+                        .iter()
+                        .find(|other| {
+                            other.action == Action::Enable && other.closes(&directives[i])
+                        })
+                        .map_or(source.len(), |enable| enable.range.start);
+                    directives[i].range.end..end
+                }
+                Action::Enable => continue,
+            };
+            directives[i].scope = scope;
+        }
+
+        Self { directives }
+    }
+
+    /// The parsed directives, in source order.
+    #[must_use]
+    pub fn directives(&self) -> &[Directive<'a>] {
+        &self.directives
+    }
+
+    /// Whether a diagnostic of `rule` starting at `offset` is silenced, marking the matching
+    /// directives as used.
+    #[must_use]
+    pub fn suppresses(&self, rule: Rule, offset: usize) -> bool {
+        let mut suppressed = false;
+        for directive in &self.directives {
+            if !directive.scope.contains(&offset) {
+                continue;
+            }
+            if directive.is_blanket() {
+                directive.used.set(true);
+                suppressed = true;
+            } else if let Some(code) = directive.codes.iter().find(|code| code.rule == Some(rule)) {
+                code.used.set(true);
+                suppressed = true;
+            }
+        }
+        suppressed
+    }
+}
+
+/// A directive comment with `body`, written as `{# … #}` so it never reaches the rendered HTML.
+fn comment(body: &str) -> String {
+    format!("{{# {DIRECTIVE_PREFIX} {body} #}}")
+}
+
+/// Diagnostics sharing the line a directive is inserted above.
+#[derive(Default)]
+struct SuppressionGroup {
+    codes: Vec<&'static str>,
+    /// Where an `enable` directive goes when the diagnostics don't all start on the anchor line.
+    region_end: Option<usize>,
+}
+
+/// Insert the directives silencing every diagnostic of `diagnostics` in `source`, parsed as
+/// `root`, applied through [`apply_fixes`] so conflicting insertions are skipped rather than
+/// interleaved.
+///
+/// Diagnostics starting on the same line share one `disable-next-line` comment, inserted above
+/// that line with its indentation; an existing `disable-next-line` directive for that line is
+/// extended instead. A diagnostic inside a tag spanning several lines can't be reached from the
+/// line above it, so the whole tag is wrapped in a `disable` / `enable` pair.
+///
+/// Running it again on the output is a no-op: the directives silence the diagnostics they were
+/// written for.
+#[must_use]
+pub fn add_suppressions(
+    source: &str,
+    root: &Root<'_>,
+    diagnostics: &[LintDiagnostic],
+) -> ApplyResult {
+    let constructs = multiline_constructs(source, root);
+    let mut groups: BTreeMap<usize, SuppressionGroup> = BTreeMap::new();
+    for diagnostic in diagnostics {
+        let offset = diagnostic.span.offset() as usize;
+        let (anchor, region_end) = enclosing_construct(&constructs, line_start(source, offset))
+            .map_or_else(
+                || (line_start(source, offset), None),
+                |construct| {
+                    (
+                        line_start(source, construct.start),
+                        Some(next_line_start(source, construct.end)),
+                    )
+                },
+            );
+        let group = groups.entry(anchor).or_default();
+        if !group.codes.contains(&diagnostic.code) {
+            group.codes.push(diagnostic.code);
+        }
+        group.region_end = group.region_end.max(region_end);
+    }
+
+    let suppressions = Suppressions::from_source(source);
+    let carriers: Vec<LintDiagnostic> = groups
+        .into_iter()
+        .map(|(anchor, mut group)| {
+            group.codes.sort_unstable();
+            let fix = suppression_fix(source, &suppressions, anchor, &group);
+            LintDiagnostic {
+                code: group.codes[0],
+                message: "Add suppression comment".into(),
+                span: span(anchor, 0),
+                help: None,
+                fix: Some(fix),
+                fix_title: Some("Add suppression comment"),
+            }
+        })
+        .collect();
+
+    apply_fixes(source, &carriers, Applicability::Safe)
+}
+
+/// The edits silencing `group`, whose directives go above the line starting at `anchor`.
+fn suppression_fix(
+    source: &str,
+    suppressions: &Suppressions<'_>,
+    anchor: usize,
+    group: &SuppressionGroup,
+) -> Fix {
+    let indent: String = source[anchor..]
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect();
+    let codes = group.codes.join(", ");
+
+    let Some(region_end) = group.region_end else {
+        let existing = suppressions.directives().iter().find(|directive| {
+            directive.action == Action::DisableNextLine
+                && !directive.is_blanket()
+                && directive.scope.start == anchor
+        });
+        if let Some(directive) = existing {
+            let mut names: Vec<&str> = directive.codes.iter().map(|code| code.name).collect();
+            for code in &group.codes {
+                if !names.contains(code) {
+                    names.push(code);
+                }
+            }
+            let range = &directive.codes_range;
+            return Fix::safe_edit(Edit::replacement(
+                names.join(", "),
+                span(range.start, range.len()),
+            ));
+        }
+        return Fix::safe_edit(Edit::insertion(
+            format!(
+                "{indent}{}\n",
+                comment(&format!("disable-next-line={codes}"))
+            ),
+            anchor,
+        ));
+    };
+
+    let enable = comment(&format!("enable={codes}"));
+    let enable = if source[..region_end].ends_with('\n') {
+        format!("{indent}{enable}\n")
+    } else {
+        format!("\n{indent}{enable}")
+    };
+    Fix::safe_edits(
+        Edit::insertion(
+            format!("{indent}{}\n", comment(&format!("disable={codes}"))),
+            anchor,
+        ),
+        [Edit::insertion(enable, region_end)],
+    )
+}
+
+/// Byte ranges of the start tags and template tags of `root` spanning several lines: a line
+/// inside one of them can't carry a directive of its own.
+fn multiline_constructs(source: &str, root: &Root<'_>) -> Vec<Range<usize>> {
+    let mut constructs = Vec::new();
+    collect_constructs(source, &root.children, &mut constructs);
+    constructs.retain(|construct| source[construct.clone()].contains('\n'));
+    constructs
+}
+
+fn collect_constructs(source: &str, nodes: &[Node<'_>], constructs: &mut Vec<Range<usize>>) {
+    for node in nodes {
+        match &node.kind {
+            NodeKind::Element(element) => {
+                let start = offset_of(source, node.raw);
+                constructs.push(start..start + start_tag_len(node.raw));
+                collect_constructs(source, &element.children, constructs);
+            }
+            NodeKind::JinjaTag(tag) => constructs.push(tag_range(tag)),
+            NodeKind::JinjaBlock(block) => {
+                for item in &block.body {
+                    match item {
+                        JinjaTagOrChildren::Tag(tag) => constructs.push(tag_range(tag)),
+                        JinjaTagOrChildren::Children(children) => {
+                            collect_constructs(source, children, constructs);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Byte range of `tag`, delimiters included.
+const fn tag_range(tag: &JinjaTag<'_>) -> Range<usize> {
+    tag.start - "{%".len()..tag.start + tag.content.len() + "%}".len()
+}
+
+/// Length of the start tag opening the raw text of an element, up to its closing `>`.
+///
+/// A `>` in a quoted attribute value or in template syntax (`{% if a > b %}`) doesn't close it.
+fn start_tag_len(raw: &str) -> usize {
+    let bytes = raw.as_bytes();
+    let mut i = 0;
+    while let Some(&byte) = bytes.get(i) {
+        let skip_to = match (byte, bytes.get(i + 1)) {
+            (b'>', _) => return i + 1,
+            (b'"' | b'\'', _) => raw[i + 1..].find(char::from(byte)).map(|end| i + 1 + end),
+            (b'{', Some(b'%')) => raw[i..].find("%}").map(|end| i + end + 1),
+            (b'{', Some(b'{')) => raw[i..].find("}}").map(|end| i + end + 1),
+            (b'{', Some(b'#')) => raw[i..].find("#}").map(|end| i + end + 1),
+            _ => Some(i),
+        };
+        let Some(skip_to) = skip_to else {
+            break;
+        };
+        i = skip_to + 1;
+    }
+    raw.len()
+}
+
+/// The outermost of `constructs` the line starting at `offset` is inside of, e.g. a start tag
+/// whose attributes wrap onto that line.
+fn enclosing_construct(constructs: &[Range<usize>], offset: usize) -> Option<&Range<usize>> {
+    constructs
+        .iter()
+        .filter(|construct| construct.start < offset && offset < construct.end)
+        .min_by_key(|construct| construct.start)
+}
+
+/// Byte offset of the start of the line containing `offset`.
+fn line_start(source: &str, offset: usize) -> usize {
+    source[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Byte offset of the start of the line after the one containing `offset`.
+fn next_line_start(source: &str, offset: usize) -> usize {
+    source[offset..]
+        .find('\n')
+        .map_or(source.len(), |i| offset + i + 1)
+}
+
+/// Byte range of the line following the one containing `offset`, without its line terminator.
+fn next_line(source: &str, offset: usize) -> Range<usize> {
+    let Some(newline) = source[offset..].find('\n') else {
+        return source.len()..source.len();
+    };
+    let start = offset + newline + 1;
+    let end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |i| start + i);
+    start..end
+}
+
+/// Byte offset of `slice` within `source`.
+fn offset_of(source: &str, slice: &str) -> usize {
+    slice.as_ptr() as usize - source.as_ptr() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use markup_fmt::Language;
+
+    use super::*;
+
+    fn suppresses(source: &str, rule: Rule, needle: &str) -> bool {
+        let offset = source.find(needle).expect("needle in source");
+        Suppressions::from_source(source).suppresses(rule, offset)
+    }
+
+    #[test]
+    fn no_directive() {
+        let source = "<img src=\"a.png\">\n";
+        assert!(Suppressions::from_source(source).directives().is_empty());
+        assert!(!suppresses(source, Rule::MissingImgAlt, "img"));
+    }
+
+    #[test]
+    fn parses_both_comment_styles() {
+        let source = "{# djangofmt: disable-next-line=missing-img-alt #}\n\
+                      <!-- djangofmt:disable=use-https, bogus -->\n\
+                      {#- djangofmt: enable -#}\n";
+        let suppressions = Suppressions::from_source(source);
+        let directives = suppressions.directives();
+        assert_eq!(directives.len(), 3);
+
+        assert_eq!(directives[0].action, Action::DisableNextLine);
+        assert_eq!(directives[0].codes[0].rule, Some(Rule::MissingImgAlt));
+
+        assert_eq!(directives[1].action, Action::Disable);
+        assert_eq!(
+            &source[directives[1].codes_range.clone()],
+            "use-https, bogus"
+        );
+        assert_eq!(directives[1].codes[1].name, "bogus");
+        assert_eq!(directives[1].codes[1].rule, None);
+
+        assert_eq!(directives[2].action, Action::Enable);
+        assert!(directives[2].is_blanket());
+    }
+
+    #[test]
+    fn ignores_other_comments() {
+        let source =
+            "{# djangofmt:ignore #}\n<!-- a comment -->\n{# djangofmt: nope=use-https #}\n";
+        assert!(Suppressions::from_source(source).directives().is_empty());
+    }
+
+    #[test]
+    fn disable_next_line_only_covers_the_next_line() {
+        let source =
+            "{# djangofmt: disable-next-line=missing-img-alt #}\n<img id=\"a\">\n<img id=\"b\">\n";
+        assert!(suppresses(source, Rule::MissingImgAlt, "<img id=\"a\""));
+        assert!(!suppresses(source, Rule::MissingImgAlt, "<img id=\"b\""));
+        assert!(!suppresses(source, Rule::UseHttps, "<img id=\"a\""));
+    }
+
+    #[test]
+    fn disable_region_ends_at_matching_enable() {
+        let source = "<!-- djangofmt: disable=use-https -->\n\
+                      <a href=\"http://a\"></a>\n\
+                      <!-- djangofmt: enable=missing-img-alt -->\n\
+                      <a href=\"http://b\"></a>\n\
+                      <!-- djangofmt: enable=use-https -->\n\
+                      <a href=\"http://c\"></a>\n";
+        assert!(suppresses(source, Rule::UseHttps, "http://a"));
+        assert!(suppresses(source, Rule::UseHttps, "http://b"));
+        assert!(!suppresses(source, Rule::UseHttps, "http://c"));
+    }
+
+    #[test]
+    fn unclosed_disable_runs_to_end_of_file() {
+        let source = "{# djangofmt: disable #}\n<div></div>\n<img>\n";
+        assert!(suppresses(source, Rule::MissingImgAlt, "<img>"));
+        assert!(suppresses(source, Rule::EmptyTagPair, "<div>"));
+    }
+
+    #[test]
+    fn marks_used_codes() {
+        let source = "{# djangofmt: disable-next-line=missing-img-alt,use-https #}\n<img>\n";
+        let suppressions = Suppressions::from_source(source);
+        assert!(suppressions.suppresses(Rule::MissingImgAlt, source.find("<img>").unwrap()));
+        let codes = &suppressions.directives()[0].codes;
+        assert!(codes[0].is_used());
+        assert!(!codes[1].is_used());
+    }
+
+    fn diagnostic(source: &str, code: &'static str, needle: &str) -> LintDiagnostic {
+        LintDiagnostic {
+            code,
+            message: "message".into(),
+            span: span(source.find(needle).expect("needle in source"), needle.len()),
+            help: None,
+            fix: None,
+            fix_title: None,
+        }
+    }
+
+    fn suppress(source: &str, diagnostics: &[LintDiagnostic]) -> ApplyResult {
+        let root = crate::parse(source, Language::Django, &[]).expect("valid template");
+        add_suppressions(source, &root, diagnostics)
+    }
+
+    #[test]
+    fn add_suppressions_above_indented_line() {
+        let source = "<div>\n    <img src=\"http://a.png\">\n</div>\n";
+        let diagnostics = [
+            diagnostic(source, "use-https", "http://a.png"),
+            diagnostic(source, "missing-img-alt", "<img"),
+        ];
+        let result = suppress(source, &diagnostics);
+        assert_eq!(
+            result.output,
+            "<div>\n    {# djangofmt: disable-next-line=missing-img-alt, use-https #}\n    <img src=\"http://a.png\">\n</div>\n"
+        );
+        assert_eq!(result.applied_count, 1);
+    }
+
+    #[test]
+    fn add_suppressions_extends_existing_directive() {
+        let source =
+            "<!-- djangofmt: disable-next-line=use-https -->\n<img src=\"http://a.png\">\n";
+        let diagnostics = [diagnostic(source, "missing-img-alt", "<img")];
+        let result = suppress(source, &diagnostics);
+        assert_eq!(
+            result.output,
+            "<!-- djangofmt: disable-next-line=use-https, missing-img-alt -->\n<img src=\"http://a.png\">\n"
+        );
+    }
+
+    #[test]
+    fn add_suppressions_wraps_multiline_tag() {
+        let source = "<img\n  src=\"http://a.png\"\n>\n<p></p>";
+        let diagnostics = [diagnostic(source, "use-https", "http://a.png")];
+        let result = suppress(source, &diagnostics);
+        assert_eq!(
+            result.output,
+            "{# djangofmt: disable=use-https #}\n<img\n  src=\"http://a.png\"\n>\n{# djangofmt: enable=use-https #}\n<p></p>"
+        );
+        assert!(suppresses(&result.output, Rule::UseHttps, "http://a.png"));
+        assert!(!suppresses(&result.output, Rule::UseHttps, "<p>"));
+    }
+
+    #[test]
+    fn add_suppressions_wraps_tag_with_template_comparison() {
+        let source = "<div>\n  <img\n    {% if a > b %}hidden{% endif %}\n    src=\"http://a.png\"\n  >\n</div>\n";
+        let diagnostics = [diagnostic(source, "use-https", "http://a.png")];
+        let result = suppress(source, &diagnostics);
+        assert_eq!(
+            result.output,
+            "<div>\n  {# djangofmt: disable=use-https #}\n  <img\n    {% if a > b %}hidden{% endif %}\n    src=\"http://a.png\"\n  >\n  {# djangofmt: enable=use-https #}\n</div>\n"
+        );
+    }
+
+    #[test]
+    fn start_tag_ends_outside_values_and_template_syntax() {
+        let raw = "<a title=\"x > y\" {% if a > b %}hidden{% endif %}>text</a>";
+        assert_eq!(
+            &raw[..start_tag_len(raw)],
+            &raw[..=raw.find(">text").unwrap()]
+        );
+    }
+
+    #[test]
+    fn add_suppressions_without_diagnostics() {
+        let source = "<img>\n";
+        let result = suppress(source, &[]);
+        assert_eq!(result.output, source);
+        assert_eq!(result.applied_count, 0);
+    }
+}
@@ -1,7 +1,7 @@
 use djangofmt::args::Profile;
 use djangofmt::commands::format::{FormatterConfig, format_text};
 use djangofmt::error::ParseError;
-use djangofmt::line_width::{IndentWidth, LineLength, SelfClosing};
+use djangofmt::line_width::{IndentWidth, LineLength, SelfClosing, StyleAttrLayout};
 use djangofmt_lint::{FileDiagnostics, Settings, lint_source};
 use miette::{GraphicalReportHandler, GraphicalTheme};
 use serde::Serialize;
@@ -19,7 +19,11 @@ pub fn format(source: &str, line_length: u16, indent_width: u8, profile: &str) -
         indent_width,
         None,
         SelfClosing::default(),
+        StyleAttrLayout::default(),
         false,
+        false,
+        false,
+        None,
     );
 
     match format_text(source, &config, profile) {
@@ -49,7 +53,11 @@ pub fn doc_tree(source: &str, line_length: u16, indent_width: u8, profile: &str)
         indent_width,
         None,
         SelfClosing::default(),
+        StyleAttrLayout::default(),
         false,
+        false,
+        false,
+        None,
     );
 
     markup_fmt::debug_doc_tree(